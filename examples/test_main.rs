@@ -0,0 +1,15 @@
+//! Tests the greeting passed as the first argument, using `test_main!`.
+//!
+//! Run with `cargo run --example test_main -- "Hello, world!"` to pass,
+//! and with any other argument to see a failure.
+
+use std::process::ExitCode;
+use test_eq::{test_eq, test_main};
+
+fn main() -> ExitCode {
+    test_main! {
+        let greeting = std::env::args().nth(1).unwrap_or_default();
+        test_eq!(greeting.len(), 13, "greeting is {:?}", greeting)?;
+        test_eq!(greeting, "Hello, world!")?;
+    }
+}
@@ -0,0 +1,58 @@
+//! A global allocator that counts the allocations of every thread.
+//!
+//! Implementing [`GlobalAlloc`] requires `unsafe`, which is why this lives in its own module.
+#![allow(unsafe_code)]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+thread_local! {
+    /// The number of allocations made on this thread.
+    ///
+    /// This has a `const` initializer without a destructor, so accessing it never allocates.
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Count an allocation on this thread.
+fn count() {
+    // Fails only while the thread is being torn down, those allocations are not interesting
+    let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+}
+
+/// The number of allocations made on this thread so far.
+pub fn allocations() -> usize {
+    ALLOCATIONS.try_with(Cell::get).unwrap_or_default()
+}
+
+/// Wraps the [`System`] allocator, counting every allocation and reallocation.
+struct CountingAllocator;
+
+// SAFETY: every call is forwarded unchanged to the system allocator
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count();
+        // SAFETY: the caller upholds the contract of `GlobalAlloc::alloc`
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count();
+        // SAFETY: the caller upholds the contract of `GlobalAlloc::alloc_zeroed`
+        unsafe { System.alloc_zeroed(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // SAFETY: the caller upholds the contract of `GlobalAlloc::dealloc`
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count();
+        // SAFETY: the caller upholds the contract of `GlobalAlloc::realloc`
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+/// The global allocator of every program that enables the `alloc-counting` feature.
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
@@ -0,0 +1,3342 @@
+//! # Helper functions
+//! These functions implement the checks that are too involved to write out in the macros.
+//! They are not part of the public API.
+//!
+//! A helper returns `Err(details)` when the check fails, where `details` describes the failure.
+//! The macros combine this with the failure message using [`TestFailure::test_failed_details`].
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{Binary, Debug, Display};
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use super::TestFailure;
+
+/// Compare the elements of `left` and `right` while ignoring their order.
+///
+/// On failure, the elements that occur more often in one side than the other are listed.
+pub fn multiset_difference<L, R, T>(
+    left: L,
+    right: R,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    L: IntoIterator<Item = T>,
+    R: IntoIterator<Item = T>,
+    T: Eq + Hash + Debug,
+{
+    // Count the items as they are yielded, remembering the order in which they were first seen so the
+    // report is deterministic
+    let mut counts: HashMap<T, (usize, usize, usize)> = HashMap::new();
+    for item in left {
+        let seen = counts.len();
+        counts.entry(item).or_insert((0, 0, seen)).0 += 1;
+    }
+    for item in right {
+        let seen = counts.len();
+        counts.entry(item).or_insert((0, 0, seen)).1 += 1;
+    }
+
+    let mut differing: Vec<_> = counts.iter().filter(|(_, (l, r, _))| l != r).collect();
+    if differing.is_empty() {
+        return Ok(());
+    }
+    differing.sort_unstable_by_key(|(_, (_, _, seen))| *seen);
+
+    let mut only_left = Vec::new();
+    let mut only_right = Vec::new();
+    for (item, &(l, r, _)) in differing {
+        only_left.extend(std::iter::repeat(item).take(l.saturating_sub(r)));
+        only_right.extend(std::iter::repeat(item).take(r.saturating_sub(l)));
+    }
+
+    let mut details = Vec::new();
+    if !only_left.is_empty() {
+        details.push(format!("only in {left_ident}: {only_left:?}"));
+    }
+    if !only_right.is_empty() {
+        details.push(format!("only in {right_ident}: {only_right:?}"));
+    }
+    Err(details.join("\n"))
+}
+
+/// Check that `actual` is within `percent` percent of `expected`.
+///
+/// On failure, both values and the actual difference in percent are shown.
+pub fn within_percent<A, E, P>(
+    actual: A,
+    expected: E,
+    percent: P,
+    actual_ident: &'static str,
+    expected_ident: &'static str,
+) -> Result<(), String>
+where
+    A: Into<f64> + Copy + Debug,
+    E: Into<f64> + Copy + Debug,
+    P: Into<f64> + Copy + Debug,
+{
+    let difference = (actual.into() - expected.into()).abs();
+    let allowed = expected.into().abs() * percent.into();
+    // Compare without dividing by 100, so a difference exactly on the boundary passes
+    if difference * 100.0 <= allowed {
+        return Ok(());
+    }
+
+    let difference_percent = difference / expected.into().abs() * 100.0;
+    Err(format!(
+        "{actual_ident}: {actual:?}\n{expected_ident}: {expected:?}\ndifference: {difference_percent}% (allowed: {percent:?}%)"
+    ))
+}
+
+/// Find the first index where `left` and `right` differ.
+///
+/// If one slice is a prefix of the other, the length of the shorter one is returned.
+fn first_difference<T: PartialEq>(left: &[T], right: &[T]) -> Option<usize> {
+    left.iter()
+        .zip(right)
+        .position(|(l, r)| l != r)
+        .or_else(|| (left.len() != right.len()).then(|| left.len().min(right.len())))
+}
+
+/// Describe the element at `index` in `slice`, or that the slice ended before it.
+fn describe_index<T: Debug>(slice: &[T], index: usize) -> String {
+    slice.get(index).map_or_else(
+        || format!("<end, length {}>", slice.len()),
+        |item| format!("{item:?}"),
+    )
+}
+
+/// Compare sorted copies of `left` and `right`.
+///
+/// On failure, the sorted slices and the first index where they differ are shown.
+pub fn sorted_difference<L, R, T>(
+    left: &L,
+    right: &R,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    L: AsRef<[T]> + ?Sized,
+    R: AsRef<[T]> + ?Sized,
+    T: Ord + Clone + Debug,
+{
+    let mut left = left.as_ref().to_vec();
+    let mut right = right.as_ref().to_vec();
+    left.sort();
+    right.sort();
+
+    match first_difference(&left, &right) {
+        None => Ok(()),
+        Some(index) => Err(format!(
+            "sorted {left_ident}: {left:?}\nsorted {right_ident}: {right:?}\nfirst difference at index {index}: {} != {}",
+            describe_index(&left, index),
+            describe_index(&right, index),
+        )),
+    }
+}
+
+/// Check that the byte buffers `left` and `right` have the same length.
+///
+/// On failure, both lengths are shown.
+pub fn byte_lengths_error<L, R>(
+    left: &L,
+    right: &R,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    L: AsRef<[u8]> + ?Sized,
+    R: AsRef<[u8]> + ?Sized,
+{
+    let (left, right) = (left.as_ref(), right.as_ref());
+    if left.len() == right.len() {
+        Ok(())
+    } else {
+        Err(format!(
+            "lengths differ: {left_ident} has {} bytes, {right_ident} has {} bytes",
+            left.len(),
+            right.len()
+        ))
+    }
+}
+
+/// Check that the number of differing bytes between `left` and `right` is at most `max`.
+///
+/// The buffers must have the same length, which is checked by [`byte_lengths_error`]. On failure, the
+/// distance and the differing offsets are shown.
+pub fn hamming_distance_le<L, R>(left: &L, right: &R, max: usize) -> Result<(), String>
+where
+    L: AsRef<[u8]> + ?Sized,
+    R: AsRef<[u8]> + ?Sized,
+{
+    let left = left.as_ref();
+    let right = right.as_ref();
+    let differing: Vec<usize> = left
+        .iter()
+        .zip(right)
+        .enumerate()
+        .filter_map(|(offset, (l, r))| (l != r).then_some(offset))
+        .collect();
+    if differing.len() <= max {
+        Ok(())
+    } else {
+        Err(format!(
+            "distance: {} bytes (maximum: {max})\ndiffering offsets: {differing:?}",
+            differing.len()
+        ))
+    }
+}
+
+/// How long [`recv_sequence`] waits for each item, if no timeout is given.
+pub const DEFAULT_RECV_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Receive exactly `expected.len()` items from `receiver` and compare them in order with `expected`,
+/// waiting at most `timeout` for each item.
+///
+/// On failure, the first mismatch, timeout or disconnect is shown.
+pub fn recv_sequence<E, T>(
+    receiver: &Receiver<T>,
+    expected: &E,
+    timeout: Duration,
+) -> Result<(), String>
+where
+    E: AsRef<[T]> + ?Sized,
+    T: PartialEq + Debug,
+{
+    for (index, expected) in expected.as_ref().iter().enumerate() {
+        match receiver.recv_timeout(timeout) {
+            Ok(value) if value == *expected => {}
+            Ok(value) => {
+                return Err(format!(
+                    "first mismatch at index {index}\nreceived: {value:?}\nexpected: {expected:?}"
+                ));
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                return Err(format!(
+                    "timed out after {timeout:?} waiting for index {index}\nexpected: {expected:?}"
+                ));
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err(format!(
+                    "channel disconnected before index {index}\nexpected: {expected:?}"
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// An integer type that cannot be zero, like [`NonZeroU32`](std::num::NonZeroU32).
+pub trait NonZeroInteger: Copy {
+    /// The plain integer type.
+    type Integer: PartialEq + Debug;
+
+    /// Get the plain integer.
+    fn get(self) -> Self::Integer;
+}
+
+/// Implement [`NonZeroInteger`] for the `NonZero*` types.
+macro_rules! impl_non_zero_integer {
+    ($($non_zero:ty => $integer:ty),+ $(,)?) => {
+        $(
+            impl NonZeroInteger for $non_zero {
+                type Integer = $integer;
+
+                fn get(self) -> $integer {
+                    <$non_zero>::get(self)
+                }
+            }
+        )+
+    };
+}
+
+impl_non_zero_integer!(
+    std::num::NonZeroU8 => u8,
+    std::num::NonZeroU16 => u16,
+    std::num::NonZeroU32 => u32,
+    std::num::NonZeroU64 => u64,
+    std::num::NonZeroU128 => u128,
+    std::num::NonZeroUsize => usize,
+    std::num::NonZeroI8 => i8,
+    std::num::NonZeroI16 => i16,
+    std::num::NonZeroI32 => i32,
+    std::num::NonZeroI64 => i64,
+    std::num::NonZeroI128 => i128,
+    std::num::NonZeroIsize => isize,
+);
+
+/// Check that `timestamps` never decrease, and that every gap is between `min_gap` and `max_gap`.
+///
+/// On failure, the first violating pair is shown.
+pub fn timestamps_monotonic<S>(
+    timestamps: &S,
+    min_gap: Duration,
+    max_gap: Duration,
+) -> Result<(), String>
+where
+    S: AsRef<[Instant]> + ?Sized,
+{
+    for (index, pair) in timestamps.as_ref().windows(2).enumerate() {
+        let (earlier, later) = (pair[0], pair[1]);
+        let next = index + 1;
+        let Some(gap) = later.checked_duration_since(earlier) else {
+            return Err(format!(
+                "timestamps {index} and {next} are out of order\n{next} is {:?} before {index}",
+                earlier.duration_since(later)
+            ));
+        };
+        if gap < min_gap || gap > max_gap {
+            return Err(format!(
+                "gap between timestamps {index} and {next} is {gap:?}\nallowed: {min_gap:?}..={max_gap:?}"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Format the row of 16 bytes starting at `row_start` as a hex dump line.
+fn hex_dump_row(bytes: &[u8], row_start: usize) -> String {
+    let row = bytes.get(row_start..).unwrap_or_default();
+    let row = &row[..row.len().min(16)];
+    if row.is_empty() {
+        return format!("{row_start:08x}: <end>");
+    }
+    let hex: Vec<String> = row.iter().map(|byte| format!("{byte:02x}")).collect();
+    format!("{row_start:08x}: {}", hex.join(" "))
+}
+
+/// Compare two byte slices, showing a hex dump of the first row that differs.
+pub fn bytes_difference(
+    left: &[u8],
+    right: &[u8],
+    left_ident: &str,
+    right_ident: &str,
+) -> Result<(), String> {
+    let Some(offset) = first_difference(left, right) else {
+        return Ok(());
+    };
+
+    let row_start = offset - offset % 16;
+    let mut details = Vec::new();
+    if left.len() != right.len() {
+        details.push(format!(
+            "lengths differ: {left_ident} has {} bytes, {right_ident} has {} bytes",
+            left.len(),
+            right.len()
+        ));
+    }
+    details.push(format!("first difference at offset {offset:#x}"));
+    details.push(format!("{left_ident}: {}", hex_dump_row(left, row_start)));
+    details.push(format!("{right_ident}: {}", hex_dump_row(right, row_start)));
+    Err(details.join("\n"))
+}
+
+/// Decode the hex string `expected` and compare it with `actual`.
+///
+/// On failure, either the decoding error or a hex dump of the first difference is shown.
+#[cfg(feature = "hex")]
+pub fn hex_difference<A>(
+    actual: &A,
+    expected: &str,
+    actual_ident: &'static str,
+    expected_ident: &'static str,
+) -> Result<(), String>
+where
+    A: AsRef<[u8]> + ?Sized,
+{
+    match hex::decode(expected) {
+        Ok(expected) => bytes_difference(actual.as_ref(), &expected, actual_ident, expected_ident),
+        Err(error) => Err(format!("{expected_ident} is not valid hex: {error}")),
+    }
+}
+
+/// Decode the base64 string `expected` and compare it with `actual`.
+///
+/// On failure, either the decoding error or a hex dump of the first difference is shown.
+#[cfg(feature = "base64")]
+pub fn base64_difference<A>(
+    actual: &A,
+    expected: &str,
+    actual_ident: &'static str,
+    expected_ident: &'static str,
+) -> Result<(), String>
+where
+    A: AsRef<[u8]> + ?Sized,
+{
+    use base64::Engine;
+
+    match base64::engine::general_purpose::STANDARD.decode(expected) {
+        Ok(expected) => bytes_difference(actual.as_ref(), &expected, actual_ident, expected_ident),
+        Err(error) => Err(format!("{expected_ident} is not valid base64: {error}")),
+    }
+}
+
+/// A waker that does nothing, for [`poll_once`].
+#[cfg(feature = "async")]
+struct NoopWaker;
+
+#[cfg(feature = "async")]
+impl std::task::Wake for NoopWaker {
+    fn wake(self: std::sync::Arc<Self>) {}
+}
+
+/// Poll `future` exactly once, with a waker that does nothing.
+#[cfg(feature = "async")]
+pub fn poll_once<F: std::future::Future>(future: F) -> std::task::Poll<F::Output> {
+    let future = std::pin::pin!(future);
+    let waker = std::task::Waker::from(std::sync::Arc::new(NoopWaker));
+    future.poll(&mut std::task::Context::from_waker(&waker))
+}
+
+/// Compare the elements of `left` one by one with the items of `right`, without collecting `right`.
+///
+/// On failure, the first difference or the length difference is shown.
+pub fn iter_difference<L, T, R>(
+    left: &L,
+    right: R,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    L: AsRef<[T]> + ?Sized,
+    R: IntoIterator,
+    T: PartialEq<R::Item> + Debug,
+    R::Item: Debug,
+{
+    let left = left.as_ref();
+    let mut right = right.into_iter();
+    for (index, left_item) in left.iter().enumerate() {
+        match right.next() {
+            Some(right_item) if *left_item == right_item => {}
+            Some(right_item) => {
+                return Err(format!(
+                    "first difference at index {index}: {left_item:?} != {right_item:?}"
+                ));
+            }
+            None => {
+                return Err(format!(
+                    "lengths differ: {left_ident} has {} elements, {right_ident} has {index}",
+                    left.len()
+                ));
+            }
+        }
+    }
+    match right.next() {
+        // Don't count the remaining items, `right` might be infinite
+        Some(right_item) => Err(format!(
+            "lengths differ: {left_ident} has {} elements, {right_ident} has more\nnext item of {right_ident}: {right_item:?}",
+            left.len()
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Compare two grids row by row, then cell by cell.
+///
+/// On failure, the row counts, the lengths of the first row that differs in length, or the
+/// coordinates and values of the first cell that differs are shown.
+pub fn grid_difference<L, R, LR, RR, T>(
+    left: &L,
+    right: &R,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    L: AsRef<[LR]> + ?Sized,
+    R: AsRef<[RR]> + ?Sized,
+    LR: AsRef<[T]>,
+    RR: AsRef<[T]>,
+    T: PartialEq + Debug,
+{
+    let (left, right) = (left.as_ref(), right.as_ref());
+    if left.len() != right.len() {
+        return Err(format!(
+            "row counts differ: {left_ident} has {} rows, {right_ident} has {}",
+            left.len(),
+            right.len()
+        ));
+    }
+    for (row, (left_row, right_row)) in left.iter().zip(right).enumerate() {
+        let (left_row, right_row) = (left_row.as_ref(), right_row.as_ref());
+        if left_row.len() != right_row.len() {
+            return Err(format!(
+                "lengths of row {row} differ: {left_ident} has {} columns, {right_ident} has {}",
+                left_row.len(),
+                right_row.len()
+            ));
+        }
+    }
+    for (row, (left_row, right_row)) in left.iter().zip(right).enumerate() {
+        if let Some(col) = first_difference(left_row.as_ref(), right_row.as_ref()) {
+            return Err(format!(
+                "first difference at ({row}, {col})\n{left_ident}: {:?}\n{right_ident}: {:?}",
+                left_row.as_ref()[col],
+                right_row.as_ref()[col]
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Compare `left` with `right` and check that the result is `expected`.
+///
+/// On failure, both operands and the actual ordering are shown.
+pub fn cmp_difference<T: Ord + Debug + ?Sized>(
+    left: &T,
+    right: &T,
+    expected: std::cmp::Ordering,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String> {
+    let actual = left.cmp(right);
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(format!(
+            "{left_ident}: {left:?}\n{right_ident}: {right:?}\nexpected: {expected:?}\nactual: {actual:?}"
+        ))
+    }
+}
+
+/// Compare `actual` with the `expected` value of the current platform.
+///
+/// On failure, both values and the platform branch that was used are shown.
+pub fn platform_difference<A, E>(
+    actual: &A,
+    expected: &E,
+    platform: &'static str,
+    actual_ident: &'static str,
+) -> Result<(), String>
+where
+    A: PartialEq<E> + Debug + ?Sized,
+    E: Debug + ?Sized,
+{
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(format!(
+            "{actual_ident}: {actual:?}\n{platform}: {expected:?}"
+        ))
+    }
+}
+
+/// The number of differences `test_slice_eq_all!` shows if no cap is given.
+pub const DEFAULT_DIFFERENCE_CAP: usize = 10;
+
+/// The maximum number of cells in the table of [`lcs_len`] and [`changed_lines`].
+const MAX_LCS_CELLS: usize = 1_000_000;
+
+/// The length of the longest common subsequence of `left` and `right`.
+///
+/// Returns [`None`] if the table would have more than [`MAX_LCS_CELLS`] cells.
+fn lcs_len<T: PartialEq>(left: &[T], right: &[T]) -> Option<usize> {
+    if left.len().checked_mul(right.len())? > MAX_LCS_CELLS {
+        return None;
+    }
+    // Only the previous row of the table is needed for the length
+    let mut previous = vec![0_usize; right.len() + 1];
+    let mut current = vec![0_usize; right.len() + 1];
+    for l in left {
+        for (j, r) in right.iter().enumerate() {
+            current[j + 1] = if l == r {
+                previous[j] + 1
+            } else {
+                previous[j + 1].max(current[j])
+            };
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    Some(previous[right.len()])
+}
+
+/// A line with the length of the longest common subsequence, if the `diff` feature is enabled.
+fn describe_lcs<T: PartialEq>(left: &[T], right: &[T]) -> String {
+    if !cfg!(feature = "diff") {
+        return String::new();
+    }
+    lcs_len(left, right).map_or_else(
+        || String::from("\nlongest common subsequence: too long to compute"),
+        |len| format!("\nlongest common subsequence: {len} elements"),
+    )
+}
+
+/// Compare `left` and `right` element by element.
+///
+/// On failure, the length difference or every index that differs is shown, up to `cap` indices.
+/// With the `diff` feature, the length of the longest common subsequence is shown as well.
+pub fn all_differences<L, R, T>(
+    left: &L,
+    right: &R,
+    cap: usize,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    L: AsRef<[T]> + ?Sized,
+    R: AsRef<[T]> + ?Sized,
+    T: PartialEq + Debug,
+{
+    let (left, right) = (left.as_ref(), right.as_ref());
+    if left.len() != right.len() {
+        return Err(format!(
+            "lengths differ: {left_ident} has {} elements, {right_ident} has {}{}",
+            left.len(),
+            right.len(),
+            describe_lcs(left, right)
+        ));
+    }
+    let differences: Vec<usize> = left
+        .iter()
+        .zip(right)
+        .enumerate()
+        .filter_map(|(index, (l, r))| (l != r).then_some(index))
+        .collect();
+    if differences.is_empty() {
+        return Ok(());
+    }
+    let mut lines = vec![format!(
+        "{} differing indices ({left_ident} != {right_ident}):",
+        differences.len()
+    )];
+    lines.extend(
+        differences
+            .iter()
+            .take(cap)
+            .map(|&index| format!("  [{index}]: {:?} != {:?}", left[index], right[index])),
+    );
+    if differences.len() > cap {
+        lines.push(format!("  ... and {} more", differences.len() - cap));
+    }
+    Err(lines.join("\n") + &describe_lcs(left, right))
+}
+
+/// Check that `bytes` is valid UTF-8.
+///
+/// On failure, the offset of the first invalid sequence and the error are shown.
+pub fn utf8_error<B: AsRef<[u8]> + ?Sized>(bytes: &B) -> Result<(), String> {
+    let bytes = bytes.as_ref();
+    std::str::from_utf8(bytes).map(drop).map_err(|error| {
+        let offset = error.valid_up_to();
+        let end = error.error_len().map_or(bytes.len(), |len| offset + len);
+        format!(
+            "invalid sequence at offset {offset}: {:02x?}\nerror: {error}",
+            &bytes[offset..end]
+        )
+    })
+}
+
+/// Find the end of the marker value that starts at `start`, if there is one.
+///
+/// Marker values are [`PhantomData`](std::marker::PhantomData) and [`PhantomPinned`](std::marker::PhantomPinned).
+fn marker_end(debug: &str, start: usize) -> Option<usize> {
+    let rest = &debug[start..];
+    if rest.starts_with("PhantomPinned") {
+        return Some(start + "PhantomPinned".len());
+    }
+    let generics = rest.strip_prefix("PhantomData<")?;
+    let mut depth = 1_usize;
+    let mut previous = '<';
+    for (index, c) in generics.char_indices() {
+        match c {
+            '<' => depth += 1,
+            // Skip the arrow of a function type like `fn() -> u8`
+            '>' if previous != '-' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(start + "PhantomData<".len() + index + 1);
+                }
+            }
+            _ => {}
+        }
+        previous = c;
+    }
+    None
+}
+
+/// Find the next marker value at or after `from`, returning where it starts and ends.
+///
+/// String and character literals are skipped, so a string that contains `"PhantomData<"` is not
+/// mistaken for a marker.
+fn next_marker(debug: &str, from: usize) -> Option<(usize, usize)> {
+    let mut quote = None;
+    let mut escaped = false;
+    let mut previous = debug[..from].chars().next_back();
+    for (index, c) in debug[from..].char_indices() {
+        let index = from + index;
+        if let Some(delimiter) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == delimiter {
+                quote = None;
+            }
+        } else if c == '"' || c == '\'' {
+            quote = Some(c);
+        } else if !previous.is_some_and(|p| p.is_alphanumeric() || p == '_') {
+            // Only match at the start of an identifier, not inside one like `MyPhantomData`
+            if let Some(end) = marker_end(debug, index) {
+                return Some((index, end));
+            }
+        }
+        previous = Some(c);
+    }
+    None
+}
+
+/// Remove zero-sized marker fields from the [`Debug`] output of a value.
+fn strip_markers(debug: &str) -> String {
+    let mut out = debug.to_owned();
+    let mut search = 0;
+    while let Some((mut start, mut end)) = next_marker(&out, search) {
+        // Include the field name of a struct field
+        if let Some(before) = out[..start].strip_suffix(": ") {
+            let name = before.trim_end_matches(|c: char| c.is_alphanumeric() || c == '_');
+            if name.len() < before.len() {
+                start = name.len();
+            }
+        }
+        let (before, after) = (&out[..start], &out[end..]);
+        if before.ends_with(" { ") && after.starts_with(" }") {
+            // The only field of a struct
+            start -= 3;
+            end += 2;
+        } else if before.ends_with('(') && after.starts_with(')') && before.len() > 1 {
+            // The only field of a tuple struct
+            start -= 1;
+            end += 1;
+        } else if before.ends_with(", ") {
+            start -= 2;
+        } else if after.starts_with(", ") {
+            end += 2;
+        } else {
+            // A marker that isn't a field, keep it
+            search = end;
+            continue;
+        }
+        out.replace_range(start..end, "");
+        search = start;
+    }
+    out
+}
+
+/// Compare `left` and `right`.
+///
+/// On failure, both values are shown without their zero-sized marker fields.
+pub fn ignore_zst_difference<L, R>(
+    left: &L,
+    right: &R,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    L: PartialEq<R> + Debug + ?Sized,
+    R: Debug + ?Sized,
+{
+    if left == right {
+        Ok(())
+    } else {
+        Err(format!(
+            "{left_ident}: {}\n{right_ident}: {}",
+            strip_markers(&format!("{left:?}")),
+            strip_markers(&format!("{right:?}"))
+        ))
+    }
+}
+
+/// An unsigned integer, for checking if it is a power of two.
+pub trait UnsignedInteger: Copy + Display + Binary {
+    /// Returns `true` if exactly one bit is set.
+    fn is_power_of_two(self) -> bool;
+}
+
+/// Implement [`UnsignedInteger`] for the unsigned integers.
+macro_rules! impl_unsigned_integer {
+    ($($ty:ty),+ $(,)?) => {
+        $(
+            impl UnsignedInteger for $ty {
+                fn is_power_of_two(self) -> bool {
+                    <$ty>::is_power_of_two(self)
+                }
+            }
+        )+
+    };
+}
+
+impl_unsigned_integer!(u8, u16, u32, u64, u128, usize);
+
+/// Check that `value` is a power of two.
+///
+/// On failure, the value is shown in decimal and binary.
+pub fn power_of_two<T: UnsignedInteger>(value: T, ident: &'static str) -> Result<(), String> {
+    if value.is_power_of_two() {
+        Ok(())
+    } else {
+        Err(format!("{ident}: {value} ({value:#b})"))
+    }
+}
+
+/// Check that `result` is an IO error of the `expected` kind.
+///
+/// On failure, the value is shown if `result` is [`Ok`], otherwise the actual kind and the error message.
+pub fn io_error_kind<T: Debug>(
+    result: &std::io::Result<T>,
+    expected: std::io::ErrorKind,
+    ident: &'static str,
+) -> Result<(), String> {
+    match result {
+        Ok(value) => Err(format!("{ident} was Ok\n{ident}: Ok({value:?})")),
+        Err(error) if error.kind() == expected => Ok(()),
+        Err(error) => Err(format!(
+            "expected kind: {expected:?}\nactual kind: {:?}\nerror: {error}",
+            error.kind()
+        )),
+    }
+}
+
+/// Compare `lines` with the lines of `expected`, split on `\n`.
+///
+/// On failure, the first line that differs or the line counts are shown.
+pub fn lines_difference<L, S>(
+    lines: &L,
+    expected: &str,
+    lines_ident: &'static str,
+    expected_ident: &'static str,
+) -> Result<(), String>
+where
+    L: AsRef<[S]> + ?Sized,
+    S: AsRef<str>,
+{
+    let lines = lines.as_ref();
+    let expected: Vec<&str> = expected.split('\n').collect();
+    if let Some(index) = lines
+        .iter()
+        .zip(&expected)
+        .position(|(line, expected)| line.as_ref() != *expected)
+    {
+        return Err(format!(
+            "first difference at line {index}\n{lines_ident}: {:?}\n{expected_ident}: {:?}",
+            lines[index].as_ref(),
+            expected[index]
+        ));
+    }
+    if lines.len() == expected.len() {
+        return Ok(());
+    }
+    let first_extra = lines.get(expected.len()).map_or_else(
+        || format!("first missing line: {:?}", expected[lines.len()]),
+        |line| format!("first extra line: {:?}", line.as_ref()),
+    );
+    Err(format!(
+        "line counts differ: {lines_ident} has {} lines, {expected_ident} has {}\n{first_extra}",
+        lines.len(),
+        expected.len()
+    ))
+}
+
+/// A pointer or reference, for checking its alignment.
+pub trait Pointer {
+    /// The address this points to.
+    fn as_byte_ptr(&self) -> *const u8;
+}
+
+impl<T: ?Sized> Pointer for *const T {
+    fn as_byte_ptr(&self) -> *const u8 {
+        self.cast()
+    }
+}
+
+impl<T: ?Sized> Pointer for *mut T {
+    fn as_byte_ptr(&self) -> *const u8 {
+        self.cast_const().cast()
+    }
+}
+
+impl<T: ?Sized> Pointer for &T {
+    fn as_byte_ptr(&self) -> *const u8 {
+        let ptr: *const T = *self;
+        ptr.cast()
+    }
+}
+
+impl<T: ?Sized> Pointer for &mut T {
+    fn as_byte_ptr(&self) -> *const u8 {
+        let ptr: *const T = &**self;
+        ptr.cast()
+    }
+}
+
+/// Check that `ptr` is aligned to `align` bytes.
+///
+/// On failure, the address and the required alignment are shown.
+pub fn alignment_error<P: Pointer + ?Sized>(
+    ptr: &P,
+    align: usize,
+    ident: &'static str,
+) -> Result<(), String> {
+    let ptr = ptr.as_byte_ptr();
+    if !align.is_power_of_two() {
+        Err(format!("alignment {align} is not a power of two"))
+    } else if ptr.align_offset(align) == 0 {
+        Ok(())
+    } else {
+        Err(format!("{ident}: {ptr:p}\nrequired alignment: {align}"))
+    }
+}
+
+/// Describe the first pair of items that breaks the symmetry of `items`, if there is one.
+fn first_asymmetry<T: PartialEq + Debug>(items: &[T]) -> Option<String> {
+    let half = items.len() / 2;
+    items[..half]
+        .iter()
+        .zip(items.iter().rev())
+        .position(|(front, back)| front != back)
+        .map(|index| {
+            let mirror = items.len() - 1 - index;
+            format!(
+                "first asymmetry at indices {index} and {mirror}: {:?} != {:?}",
+                items[index], items[mirror]
+            )
+        })
+}
+
+/// A sequence that can be checked for being a palindrome.
+///
+/// Strings are compared by [`char`].
+pub trait Palindrome {
+    /// Describe the first pair of items that breaks the symmetry, if there is one.
+    fn asymmetry(&self) -> Option<String>;
+}
+
+impl<T: PartialEq + Debug> Palindrome for [T] {
+    fn asymmetry(&self) -> Option<String> {
+        first_asymmetry(self)
+    }
+}
+
+impl<T: PartialEq + Debug, const N: usize> Palindrome for [T; N] {
+    fn asymmetry(&self) -> Option<String> {
+        first_asymmetry(self)
+    }
+}
+
+impl<T: PartialEq + Debug> Palindrome for Vec<T> {
+    fn asymmetry(&self) -> Option<String> {
+        first_asymmetry(self)
+    }
+}
+
+impl Palindrome for str {
+    fn asymmetry(&self) -> Option<String> {
+        first_asymmetry(&self.chars().collect::<Vec<_>>())
+    }
+}
+
+impl Palindrome for String {
+    fn asymmetry(&self) -> Option<String> {
+        self.as_str().asymmetry()
+    }
+}
+
+impl<P: Palindrome + ?Sized> Palindrome for &P {
+    fn asymmetry(&self) -> Option<String> {
+        (**self).asymmetry()
+    }
+}
+
+/// Check that `value` reads the same forwards and backwards.
+///
+/// On failure, the first pair of items that differ is shown.
+pub fn palindrome_error<P: Palindrome + ?Sized>(value: &P) -> Result<(), String> {
+    value.asymmetry().map_or(Ok(()), Err)
+}
+
+/// A map, for comparing its values without the keys.
+pub trait MapValues {
+    /// The type of the values.
+    type Value;
+
+    /// The values of the map, in the iteration order of the map.
+    fn map_values(&self) -> Vec<&Self::Value>;
+}
+
+impl<K, V, S> MapValues for HashMap<K, V, S> {
+    type Value = V;
+
+    fn map_values(&self) -> Vec<&V> {
+        self.values().collect()
+    }
+}
+
+impl<K, V> MapValues for std::collections::BTreeMap<K, V> {
+    type Value = V;
+
+    fn map_values(&self) -> Vec<&V> {
+        self.values().collect()
+    }
+}
+
+impl<M: MapValues + ?Sized> MapValues for &M {
+    type Value = M::Value;
+
+    fn map_values(&self) -> Vec<&Self::Value> {
+        (**self).map_values()
+    }
+}
+
+/// A map, for comparing its keys without the values.
+pub trait MapKeys {
+    /// The type of the keys.
+    type Key;
+
+    /// The keys of the map, in the iteration order of the map.
+    fn map_keys(&self) -> Vec<&Self::Key>;
+
+    /// Returns `true` if the map contains `key`, using the lookup of the map itself.
+    fn contains_map_key(&self, key: &Self::Key) -> bool;
+}
+
+impl<K: Eq + Hash, V, S: BuildHasher> MapKeys for HashMap<K, V, S> {
+    type Key = K;
+
+    fn map_keys(&self) -> Vec<&K> {
+        self.keys().collect()
+    }
+
+    fn contains_map_key(&self, key: &K) -> bool {
+        self.contains_key(key)
+    }
+}
+
+impl<K: Ord, V> MapKeys for std::collections::BTreeMap<K, V> {
+    type Key = K;
+
+    fn map_keys(&self) -> Vec<&K> {
+        self.keys().collect()
+    }
+
+    fn contains_map_key(&self, key: &K) -> bool {
+        self.contains_key(key)
+    }
+}
+
+impl<M: MapKeys + ?Sized> MapKeys for &M {
+    type Key = M::Key;
+
+    fn map_keys(&self) -> Vec<&Self::Key> {
+        (**self).map_keys()
+    }
+
+    fn contains_map_key(&self, key: &Self::Key) -> bool {
+        (**self).contains_map_key(key)
+    }
+}
+
+/// The [`Debug`] output of the keys of `map` that are not in `other`, sorted so the report doesn't
+/// depend on the iteration order of a [`HashMap`].
+fn missing_keys<M, O>(map: &M, other: &O) -> Vec<String>
+where
+    M: MapKeys + ?Sized,
+    O: MapKeys<Key = M::Key> + ?Sized,
+    M::Key: Debug,
+{
+    let mut missing: Vec<String> = map
+        .map_keys()
+        .into_iter()
+        .filter(|key| !other.contains_map_key(key))
+        .map(|key| format!("{key:?}"))
+        .collect();
+    missing.sort_unstable();
+    missing
+}
+
+/// Compare the keys of two maps, ignoring their order.
+///
+/// Each map looks up the keys of the other, so a [`HashMap`] only needs [`Hash`] and [`Eq`] keys and a
+/// [`BTreeMap`](std::collections::BTreeMap) only needs [`Ord`] keys. On failure, the keys that are only
+/// in one of the maps are listed, sorted by their [`Debug`] output.
+pub fn keys_difference<L, R>(
+    left: &L,
+    right: &R,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    L: MapKeys + ?Sized,
+    R: MapKeys<Key = L::Key> + ?Sized,
+    L::Key: Debug,
+{
+    let only_left = missing_keys(left, right);
+    let only_right = missing_keys(right, left);
+    let mut details = Vec::new();
+    if !only_left.is_empty() {
+        details.push(format!("only in {left_ident}: [{}]", only_left.join(", ")));
+    }
+    if !only_right.is_empty() {
+        details.push(format!(
+            "only in {right_ident}: [{}]",
+            only_right.join(", ")
+        ));
+    }
+    if details.is_empty() {
+        Ok(())
+    } else {
+        Err(details.join("\n"))
+    }
+}
+
+/// A map, for checking its entries.
+pub trait MapEntries {
+    /// The type of the keys.
+    type Key;
+    /// The type of the values.
+    type Value;
+
+    /// The entries of the map, in the iteration order of the map.
+    fn map_entries(&self) -> Vec<(&Self::Key, &Self::Value)>;
+}
+
+impl<K, V, S> MapEntries for HashMap<K, V, S> {
+    type Key = K;
+    type Value = V;
+
+    fn map_entries(&self) -> Vec<(&K, &V)> {
+        self.iter().collect()
+    }
+}
+
+impl<K, V> MapEntries for std::collections::BTreeMap<K, V> {
+    type Key = K;
+    type Value = V;
+
+    fn map_entries(&self) -> Vec<(&K, &V)> {
+        self.iter().collect()
+    }
+}
+
+impl<M: MapEntries + ?Sized> MapEntries for &M {
+    type Key = M::Key;
+    type Value = M::Value;
+
+    fn map_entries(&self) -> Vec<(&Self::Key, &Self::Value)> {
+        (**self).map_entries()
+    }
+}
+
+/// Check that every character of `value` is in `allowed`.
+///
+/// On failure, the first disallowed character and its position are shown.
+pub fn charset_error<S: AsRef<str> + ?Sized>(
+    value: &S,
+    allowed: &str,
+    ident: &'static str,
+) -> Result<(), String> {
+    let value = value.as_ref();
+    value
+        .char_indices()
+        .enumerate()
+        .find(|(_, (_, c))| !allowed.contains(*c))
+        .map_or(Ok(()), |(position, (offset, c))| {
+            Err(format!(
+                "disallowed character {c:?} at position {position} (byte offset {offset})\n{ident}: {value:?}\nallowed: {allowed:?}"
+            ))
+        })
+}
+
+/// The number of items `test_iter_eq!` shows around the first difference if no context is given.
+pub const DEFAULT_CONTEXT: usize = 2;
+
+/// Describe an item that might be past the end of its iterator.
+fn describe_item<T: Debug>(item: Option<&T>) -> String {
+    item.map_or_else(|| String::from("<end>"), |item| format!("{item:?}"))
+}
+
+/// Compare the items of `left` and `right` one by one.
+///
+/// On failure, the first difference is shown with up to `context` items before and after it from
+/// both iterators.
+pub fn iter_difference_context<L, R>(
+    left: L,
+    right: R,
+    context: usize,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    L: IntoIterator,
+    R: IntoIterator,
+    L::Item: PartialEq<R::Item> + Debug,
+    R::Item: Debug,
+{
+    let (mut left, mut right) = (left.into_iter(), right.into_iter());
+    let mut left_window = std::collections::VecDeque::with_capacity(context + 1);
+    let mut right_window = std::collections::VecDeque::with_capacity(context + 1);
+    let mut index = 0;
+    let (left_item, right_item) = loop {
+        match (left.next(), right.next()) {
+            (None, None) => return Ok(()),
+            (Some(l), Some(r)) if l == r => {
+                if left_window.len() == context {
+                    left_window.pop_front();
+                    right_window.pop_front();
+                }
+                if context > 0 {
+                    left_window.push_back(l);
+                    right_window.push_back(r);
+                }
+                index += 1;
+            }
+            items => break items,
+        }
+    };
+    let first = format!(
+        "first difference at index {index}: {} != {}",
+        describe_item(left_item.as_ref()),
+        describe_item(right_item.as_ref())
+    );
+    let start = index - left_window.len();
+    let (left_ended, right_ended) = (left_item.is_none(), right_item.is_none());
+    left_window.extend(left_item);
+    right_window.extend(right_item);
+    if !left_ended {
+        left_window.extend(left.take(context));
+    }
+    if !right_ended {
+        right_window.extend(right.take(context));
+    }
+    Err(format!(
+        "{first}\n{left_ident}[{start}..{}]: {left_window:?}\n{right_ident}[{start}..{}]: {right_window:?}",
+        start + left_window.len(),
+        start + right_window.len()
+    ))
+}
+
+/// Find the first path where `left` and `right` differ, if there is one.
+///
+/// `path` is the path to `left` and `right`, like `$.users[0].name`.
+#[cfg(feature = "serde_json")]
+fn json_path_difference(
+    left: &serde_json::Value,
+    right: &serde_json::Value,
+    path: &str,
+) -> Option<(String, String, String)> {
+    use serde_json::Value;
+
+    /// Describe a value that might be missing.
+    fn describe(value: Option<&Value>) -> String {
+        value.map_or_else(|| String::from("<missing>"), ToString::to_string)
+    }
+
+    match (left, right) {
+        (Value::Object(left), Value::Object(right)) => left
+            .keys()
+            .chain(right.keys().filter(|key| !left.contains_key(*key)))
+            .find_map(|key| {
+                let path = format!("{path}.{key}");
+                match (left.get(key), right.get(key)) {
+                    (Some(l), Some(r)) => json_path_difference(l, r, &path),
+                    (l, r) => Some((path, describe(l), describe(r))),
+                }
+            }),
+        (Value::Array(left), Value::Array(right)) => {
+            (0..left.len().max(right.len())).find_map(|index| {
+                let path = format!("{path}[{index}]");
+                match (left.get(index), right.get(index)) {
+                    (Some(l), Some(r)) => json_path_difference(l, r, &path),
+                    (l, r) => Some((path, describe(l), describe(r))),
+                }
+            })
+        }
+        (left, right) if left == right => None,
+        (left, right) => Some((path.to_owned(), left.to_string(), right.to_string())),
+    }
+}
+
+/// Parse `left` and `right` as JSON and compare them.
+///
+/// On failure, the parse error or the first path where they differ is shown.
+#[cfg(feature = "serde_json")]
+pub fn json_str_difference(
+    left: &str,
+    right: &str,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String> {
+    let parse = |json: &str, ident: &str| {
+        serde_json::from_str::<serde_json::Value>(json)
+            .map_err(|error| format!("{ident} is not valid JSON: {error}"))
+    };
+    let left = parse(left, left_ident)?;
+    let right = parse(right, right_ident)?;
+    json_path_difference(&left, &right, "$").map_or(Ok(()), |(path, l, r)| {
+        Err(format!(
+            "first difference at {path}\n{left_ident}: {l}\n{right_ident}: {r}"
+        ))
+    })
+}
+
+/// Apply `via` to both `left` and `right`.
+///
+/// Passing the closure through this function lets the compiler infer the type of its argument.
+pub fn project<T: ?Sized, R>(left: &T, right: &T, via: impl Fn(&T) -> R) -> (R, R) {
+    (via(left), via(right))
+}
+
+/// Apply `via` to `value`.
+///
+/// Like [`project`], but for a single value, so the two sides can be of different types.
+pub fn project_one<T: ?Sized, R>(value: &T, via: impl FnOnce(&T) -> R) -> R {
+    via(value)
+}
+
+/// Call `compute` `runs` times and check that every result is equal to the first.
+///
+/// On failure, the index of the first run that differs is shown with both results.
+pub fn deterministic_error<T, F>(runs: usize, mut compute: F) -> Result<(), String>
+where
+    T: PartialEq + Debug,
+    F: FnMut() -> T,
+{
+    if runs == 0 {
+        return Ok(());
+    }
+    let first = compute();
+    for run in 1..runs {
+        let result = compute();
+        if result != first {
+            return Err(format!(
+                "run {run} differs from run 0\nrun 0: {first:?}\nrun {run}: {result:?}"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Compare `left` and `right` after replacing every run of whitespace with a single space and
+/// trimming both ends.
+///
+/// On failure, the original strings and the collapsed strings are shown.
+pub fn ws_collapsed_difference<L, R>(
+    left: &L,
+    right: &R,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    L: AsRef<str> + ?Sized,
+    R: AsRef<str> + ?Sized,
+{
+    let (left, right) = (left.as_ref(), right.as_ref());
+    let collapse = |s: &str| s.split_whitespace().collect::<Vec<_>>().join(" ");
+    let (left_collapsed, right_collapsed) = (collapse(left), collapse(right));
+    if left_collapsed == right_collapsed {
+        Ok(())
+    } else {
+        Err(format!(
+            "{left_ident}: {left:?}\n{right_ident}: {right:?}\ncollapsed {left_ident}: {left_collapsed:?}\ncollapsed {right_ident}: {right_collapsed:?}"
+        ))
+    }
+}
+
+/// List the files under `dir` recursively, relative to `root`.
+fn list_files(
+    root: &std::path::Path,
+    dir: &std::path::Path,
+    files: &mut Vec<std::path::PathBuf>,
+) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            list_files(root, &path, files)?;
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            files.push(relative.to_path_buf());
+        }
+    }
+    Ok(())
+}
+
+/// Compare the relative paths of the files in `left` and `right`, ignoring their contents.
+///
+/// On failure, the files that are only in one of the directories are listed.
+pub fn dir_difference<L, R>(
+    left: L,
+    right: R,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    L: AsRef<std::path::Path>,
+    R: AsRef<std::path::Path>,
+{
+    let list = |dir: &std::path::Path, ident: &str| {
+        let mut files = Vec::new();
+        list_files(dir, dir, &mut files)
+            .map_err(|error| format!("could not list {ident} ({}): {error}", dir.display()))?;
+        files.sort();
+        Ok::<_, String>(files)
+    };
+    let left = list(left.as_ref(), left_ident)?;
+    let right = list(right.as_ref(), right_ident)?;
+    multiset_difference(left, right, left_ident, right_ident)
+}
+
+/// Compare `left` and `right` by the Euclidean norm of their difference.
+///
+/// On failure, the length difference or the norm, the tolerance and both vectors are shown.
+pub fn norm_difference<L, R, T, Tol>(
+    left: &L,
+    right: &R,
+    tolerance: Tol,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    L: AsRef<[T]> + ?Sized,
+    R: AsRef<[T]> + ?Sized,
+    T: Into<f64> + Copy + Debug,
+    Tol: Into<f64> + Copy + Debug,
+{
+    let (left, right) = (left.as_ref(), right.as_ref());
+    if left.len() != right.len() {
+        return Err(format!(
+            "lengths differ: {left_ident} has {} elements, {right_ident} has {}",
+            left.len(),
+            right.len()
+        ));
+    }
+    let norm = left
+        .iter()
+        .zip(right)
+        .map(|(&l, &r)| (l.into() - r.into()).powi(2))
+        .sum::<f64>()
+        .sqrt();
+    // A NaN norm fails, as it isn't smaller than anything
+    if norm <= tolerance.into() {
+        return Ok(());
+    }
+    Err(format!(
+        "norm of difference: {norm} (allowed: {tolerance:?})\n{left_ident}: {left:?}\n{right_ident}: {right:?}"
+    ))
+}
+
+/// Check that [`PartialOrd::partial_cmp`] agrees with [`Ord::cmp`] for `left` and `right`.
+///
+/// On failure, both results and both values are shown.
+pub fn cmp_consistency<T: Ord + Debug + ?Sized>(
+    left: &T,
+    right: &T,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String> {
+    let ordering = left.cmp(right);
+    let partial_ordering = left.partial_cmp(right);
+    if partial_ordering == Some(ordering) {
+        Ok(())
+    } else {
+        Err(format!(
+            "partial_cmp returned {partial_ordering:?}, but cmp returned {ordering:?}\n{left_ident}: {left:?}\n{right_ident}: {right:?}"
+        ))
+    }
+}
+
+/// Parse `input` with [`FromStr`](std::str::FromStr) and compare it with `expected`.
+///
+/// On failure, the parse error or the parsed value and `expected` are shown.
+pub fn parse_difference<S, T>(
+    input: &S,
+    expected: &T,
+    expected_ident: &'static str,
+) -> Result<(), String>
+where
+    S: AsRef<str> + ?Sized,
+    T: std::str::FromStr + PartialEq + Debug,
+    T::Err: Debug,
+{
+    let input = input.as_ref();
+    match input.parse::<T>() {
+        Ok(parsed) if parsed == *expected => Ok(()),
+        Ok(parsed) => Err(format!(
+            "parsed: {parsed:?}\n{expected_ident}: {expected:?}\ninput: {input:?}"
+        )),
+        Err(error) => Err(format!(
+            "could not parse {input:?} as {}\nerror: {error:?}",
+            std::any::type_name::<T>()
+        )),
+    }
+}
+
+/// Check that every element of `slice` is equal to `value`.
+///
+/// On failure, the first element that differs and its index are shown.
+pub fn all_equal_error<L, T, V>(
+    slice: &L,
+    value: &V,
+    slice_ident: &'static str,
+    value_ident: &'static str,
+) -> Result<(), String>
+where
+    L: AsRef<[T]> + ?Sized,
+    T: PartialEq<V> + Debug,
+    V: Debug + ?Sized,
+{
+    slice
+        .as_ref()
+        .iter()
+        .enumerate()
+        .find(|(_, item)| *item != value)
+        .map_or(Ok(()), |(index, item)| {
+            Err(format!(
+                "first difference at index {index}\n{slice_ident}[{index}]: {item:?}\n{value_ident}: {value:?}"
+            ))
+        })
+}
+
+/// Round `value` to `decimals` decimal places, formatted as a string.
+///
+/// Negative zero is formatted without the sign, so `-0.0001` and `0.0001` round to the same string.
+fn round_to_string(value: f64, decimals: usize) -> String {
+    let rounded = format!("{value:.decimals$}");
+    match rounded.strip_prefix('-') {
+        Some(positive) if positive.chars().all(|c| c == '0' || c == '.') => positive.to_owned(),
+        _ => rounded,
+    }
+}
+
+/// Compare `left` and `right` after rounding both to `decimals` decimal places.
+///
+/// On failure, the original and rounded values are shown.
+pub fn rounded_difference<L, R>(
+    left: L,
+    right: R,
+    decimals: usize,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    L: Into<f64> + Copy + Debug,
+    R: Into<f64> + Copy + Debug,
+{
+    let left_rounded = round_to_string(left.into(), decimals);
+    let right_rounded = round_to_string(right.into(), decimals);
+    if left_rounded == right_rounded {
+        Ok(())
+    } else {
+        Err(format!(
+            "{left_ident}: {left:?} (rounded: {left_rounded})\n{right_ident}: {right:?} (rounded: {right_rounded})"
+        ))
+    }
+}
+
+/// Compare `left` and `right` in constant time.
+///
+/// On failure, only the lengths are shown, never the bytes.
+#[cfg(feature = "constant-time")]
+pub fn ct_difference<L, R>(
+    left: &L,
+    right: &R,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    L: AsRef<[u8]> + ?Sized,
+    R: AsRef<[u8]> + ?Sized,
+{
+    use subtle::ConstantTimeEq;
+
+    let (left, right) = (left.as_ref(), right.as_ref());
+    // `ct_eq` also returns false for different lengths, without comparing the bytes
+    if bool::from(left.ct_eq(right)) {
+        return Ok(());
+    }
+    let reason = if left.len() == right.len() {
+        "the contents differ"
+    } else {
+        "the lengths differ"
+    };
+    Err(format!(
+        "{reason}, the bytes are not shown\n{left_ident}: {} bytes\n{right_ident}: {} bytes",
+        left.len(),
+        right.len()
+    ))
+}
+
+/// Apply `predicate` to `value`.
+///
+/// Passing the closure through this function lets the compiler infer the type of its argument.
+pub fn satisfies<T: ?Sized>(value: &T, predicate: impl FnOnce(&T) -> bool) -> bool {
+    predicate(value)
+}
+
+/// Check that every pixel of `left` is within `tolerance` of the same pixel in `right`.
+///
+/// On failure, the first offending pixel is shown with both values, together with the number of
+/// offending pixels.
+pub fn image_difference<L, R>(
+    left: &L,
+    right: &R,
+    tolerance: u8,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    L: AsRef<[u8]> + ?Sized,
+    R: AsRef<[u8]> + ?Sized,
+{
+    let (left, right) = (left.as_ref(), right.as_ref());
+    if left.len() != right.len() {
+        return Err(format!(
+            "lengths differ: {left_ident} has {} pixels, {right_ident} has {}",
+            left.len(),
+            right.len()
+        ));
+    }
+    let mut offending = left
+        .iter()
+        .zip(right)
+        .enumerate()
+        .filter(|(_, (l, r))| l.abs_diff(**r) > tolerance);
+    let Some((index, (l, r))) = offending.next() else {
+        return Ok(());
+    };
+    let count = offending.count() + 1;
+    Err(format!(
+        "{count} of {} pixels differ by more than {tolerance}, the first at index {index}\n{left_ident}: {l}\n{right_ident}: {r}",
+        left.len()
+    ))
+}
+
+/// A writer that only counts the bytes written to it.
+#[cfg(feature = "serde")]
+#[derive(Default)]
+struct ByteCounter(usize);
+
+#[cfg(feature = "serde")]
+impl std::io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Serialize `left` and `right` as JSON and compare the number of bytes.
+///
+/// The JSON is not kept, only the bytes are counted. On failure, both lengths are shown.
+#[cfg(feature = "serde")]
+pub fn serialized_len_difference<L, R>(
+    left: &L,
+    right: &R,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    L: serde::Serialize + ?Sized,
+    R: serde::Serialize + ?Sized,
+{
+    /// Count the bytes of `value` serialized as JSON.
+    fn serialized_len<T: serde::Serialize + ?Sized>(
+        value: &T,
+        ident: &str,
+    ) -> Result<usize, String> {
+        let mut counter = ByteCounter::default();
+        serde_json::to_writer(&mut counter, value)
+            .map(|()| counter.0)
+            .map_err(|error| format!("{ident} could not be serialized: {error}"))
+    }
+
+    let left_len = serialized_len(left, left_ident)?;
+    let right_len = serialized_len(right, right_ident)?;
+    if left_len == right_len {
+        Ok(())
+    } else {
+        Err(format!(
+            "{left_ident}: {left_len} bytes\n{right_ident}: {right_len} bytes"
+        ))
+    }
+}
+
+/// The number of characters that have to be inserted, removed or replaced to turn `left` into `right`.
+pub fn edit_distance(left: &str, right: &str) -> usize {
+    let right: Vec<char> = right.chars().collect();
+    let mut previous: Vec<usize> = (0..=right.len()).collect();
+    let mut current = vec![0; right.len() + 1];
+    for (i, l) in left.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, r) in right.iter().enumerate() {
+            let replace = previous[j] + usize::from(l != *r);
+            current[j + 1] = replace.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[right.len()]
+}
+
+/// Check that `value` is one of the strings in `expected`.
+///
+/// On failure, the value and the allowed strings are shown, with a suggestion for the closest one.
+pub fn one_of_suggestion<'a, V, I>(
+    value: &V,
+    expected: &'a I,
+    value_ident: &'static str,
+) -> Result<(), String>
+where
+    V: AsRef<str> + ?Sized,
+    I: ?Sized,
+    &'a I: IntoIterator,
+    <&'a I as IntoIterator>::Item: AsRef<str>,
+{
+    let value = value.as_ref();
+    let expected: Vec<_> = expected.into_iter().collect();
+    if expected.iter().any(|e| e.as_ref() == value) {
+        return Ok(());
+    }
+    let allowed: Vec<&str> = expected.iter().map(AsRef::as_ref).collect();
+    let suggestion = allowed
+        .iter()
+        .min_by_key(|candidate| edit_distance(value, candidate))
+        .map_or_else(String::new, |closest| {
+            format!("\ndid you mean {closest:?}?")
+        });
+    Err(format!(
+        "{value_ident}: {value:?}\nexpected one of: {allowed:?}{suggestion}"
+    ))
+}
+
+/// The default factor for [`magnitude_difference`].
+pub const DEFAULT_MAGNITUDE_FACTOR: u32 = 10;
+
+/// Check that the larger of `left` and `right` is at most `factor` times the smaller one.
+///
+/// On failure, both durations are shown together with their ratio.
+pub fn magnitude_difference(
+    left: Duration,
+    right: Duration,
+    factor: u32,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String> {
+    let (smaller, larger) = if left <= right {
+        (left, right)
+    } else {
+        (right, left)
+    };
+    if smaller
+        .checked_mul(factor)
+        .map_or(true, |limit| larger <= limit)
+    {
+        return Ok(());
+    }
+    let ratio = if smaller.is_zero() {
+        String::from("infinite")
+    } else {
+        format!("{:.2}x", larger.as_secs_f64() / smaller.as_secs_f64())
+    };
+    Err(format!(
+        "ratio: {ratio} (maximum: {factor}x)\n{left_ident}: {left:?}\n{right_ident}: {right:?}"
+    ))
+}
+
+/// Compare the edges of `left` and `right` as sets.
+///
+/// If `directed` is false, every edge is stored with the smallest node first, so `(2, 1)` and
+/// `(1, 2)` are the same edge. On failure, the edges that are only in one side are listed.
+pub fn edges_difference<L, R>(
+    left: &L,
+    right: &R,
+    directed: bool,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    L: AsRef<[(u32, u32)]> + ?Sized,
+    R: AsRef<[(u32, u32)]> + ?Sized,
+{
+    let edges = |edges: &[(u32, u32)]| -> BTreeSet<(u32, u32)> {
+        edges
+            .iter()
+            .map(|&(from, to)| {
+                if directed || from <= to {
+                    (from, to)
+                } else {
+                    (to, from)
+                }
+            })
+            .collect()
+    };
+    multiset_difference(
+        edges(left.as_ref()),
+        edges(right.as_ref()),
+        left_ident,
+        right_ident,
+    )
+}
+
+/// A primitive integer, for checking the distance between two integers.
+pub trait Integer: Copy + Display {
+    /// The unsigned type of the distance between two integers.
+    type Distance: Copy + PartialOrd + Display;
+
+    /// The absolute difference between `self` and `other`, which can't overflow.
+    fn distance(self, other: Self) -> Self::Distance;
+
+    /// The integer after `self`, or [`None`] if `self` is the maximum.
+    fn successor(self) -> Option<Self>;
+}
+
+/// Implement [`Integer`] for the primitive integers, using `abs_diff` and `checked_add`.
+macro_rules! impl_integer {
+    ($($ty:ty => $distance:ty),+ $(,)?) => {
+        $(
+            impl Integer for $ty {
+                type Distance = $distance;
+
+                fn distance(self, other: Self) -> Self::Distance {
+                    self.abs_diff(other)
+                }
+
+                fn successor(self) -> Option<Self> {
+                    self.checked_add(1)
+                }
+            }
+        )+
+    };
+}
+
+impl_integer!(
+    u8 => u8, u16 => u16, u32 => u32, u64 => u64, u128 => u128, usize => usize,
+    i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize,
+);
+
+/// Check that `left` and `right` differ by at most `tolerance`.
+///
+/// On failure, both values and the difference between them are shown.
+pub fn near_int_difference<T: Integer>(
+    left: T,
+    right: T,
+    tolerance: T::Distance,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String> {
+    let distance = left.distance(right);
+    if distance <= tolerance {
+        Ok(())
+    } else {
+        Err(format!(
+            "difference: {distance} (maximum: {tolerance})\n{left_ident}: {left}\n{right_ident}: {right}"
+        ))
+    }
+}
+
+/// Apply `first` and `second` to the same `inputs` and compare their outputs one by one.
+///
+/// On failure, the first index where the outputs diverge is shown with the items around it.
+pub fn order_difference<T, F, G, L, R>(
+    inputs: &T,
+    first: F,
+    second: G,
+    first_ident: &'static str,
+    second_ident: &'static str,
+) -> Result<(), String>
+where
+    T: ?Sized,
+    F: FnOnce(&T) -> L,
+    G: FnOnce(&T) -> R,
+    L: IntoIterator,
+    R: IntoIterator,
+    L::Item: PartialEq<R::Item> + Debug,
+    R::Item: Debug,
+{
+    iter_difference_context(
+        first(inputs),
+        second(inputs),
+        DEFAULT_CONTEXT,
+        first_ident,
+        second_ident,
+    )
+}
+
+/// Describe the bits of `left` and `right` that differ, up to [`DEFAULT_DIFFERENCE_CAP`] bits.
+fn bit_differences(
+    len: usize,
+    left: impl Fn(usize) -> bool,
+    right: impl Fn(usize) -> bool,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String> {
+    let differences: Vec<usize> = (0..len).filter(|&bit| left(bit) != right(bit)).collect();
+    let Some(first) = differences.first() else {
+        return Ok(());
+    };
+    let mut lines = vec![
+        format!("first differing bit: {first}"),
+        format!(
+            "{} differing {} ({left_ident} != {right_ident}):",
+            differences.len(),
+            if differences.len() == 1 {
+                "bit"
+            } else {
+                "bits"
+            }
+        ),
+    ];
+    lines.extend(
+        differences
+            .iter()
+            .take(DEFAULT_DIFFERENCE_CAP)
+            .map(|&bit| format!("  [{bit}]: {} != {}", left(bit), right(bit))),
+    );
+    if differences.len() > DEFAULT_DIFFERENCE_CAP {
+        lines.push(format!(
+            "  ... and {} more",
+            differences.len() - DEFAULT_DIFFERENCE_CAP
+        ));
+    }
+    Err(lines.join("\n"))
+}
+
+/// Compare the bits in `left` and `right` one by one.
+///
+/// On failure, the length difference or the first differing bit and every differing bit are shown.
+pub fn bools_difference<L, R>(
+    left: &L,
+    right: &R,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    L: AsRef<[bool]> + ?Sized,
+    R: AsRef<[bool]> + ?Sized,
+{
+    let (left, right) = (left.as_ref(), right.as_ref());
+    if left.len() != right.len() {
+        return Err(format!(
+            "lengths differ: {left_ident} has {} bits, {right_ident} has {}",
+            left.len(),
+            right.len()
+        ));
+    }
+    bit_differences(
+        left.len(),
+        |bit| left[bit],
+        |bit| right[bit],
+        left_ident,
+        right_ident,
+    )
+}
+
+/// Compare the first `bits` bits of the packed bytes `left` and `right` one by one.
+///
+/// Bit `n` is bit `n % 8` of byte `n / 8`, counting from the least significant bit.
+/// On failure, the length difference or the first differing bit and every differing bit are shown.
+pub fn packed_bits_difference<L, R>(
+    left: &L,
+    right: &R,
+    bits: usize,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    L: AsRef<[u8]> + ?Sized,
+    R: AsRef<[u8]> + ?Sized,
+{
+    let (left, right) = (left.as_ref(), right.as_ref());
+    let bytes = bits.div_ceil(8);
+    if left.len() != bytes || right.len() != bytes {
+        return Err(format!(
+            "lengths differ: {bits} bits need {bytes} bytes, {left_ident} has {}, {right_ident} has {}",
+            left.len(),
+            right.len()
+        ));
+    }
+    let bit = |bytes: &[u8], n: usize| bytes[n / 8] >> (n % 8) & 1 == 1;
+    bit_differences(
+        bits,
+        |n| bit(left, n),
+        |n| bit(right, n),
+        left_ident,
+        right_ident,
+    )
+}
+
+/// Apply `implementation` and `reference` to `input` and compare the outputs.
+///
+/// On failure, the input and both outputs are shown.
+pub fn reference_difference<T, F, G, L, R>(
+    input: &T,
+    implementation: F,
+    reference: G,
+    input_ident: &'static str,
+    implementation_ident: &'static str,
+    reference_ident: &'static str,
+) -> Result<(), String>
+where
+    T: Debug + ?Sized,
+    F: FnOnce(&T) -> L,
+    G: FnOnce(&T) -> R,
+    L: PartialEq<R> + Debug,
+    R: Debug,
+{
+    let (actual, expected) = (implementation(input), reference(input));
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(format!(
+            "{input_ident}: {input:?}\n{implementation_ident}: {actual:?}\n{reference_ident}: {expected:?}"
+        ))
+    }
+}
+
+/// Compare the normalized values of `left` and `right`.
+///
+/// On failure, both the original and the normalized values are shown.
+pub fn normalized_difference<L, R, N>(
+    left: &L,
+    right: &R,
+    left_normalized: &N,
+    right_normalized: &N,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    L: Debug + ?Sized,
+    R: Debug + ?Sized,
+    N: PartialEq + Debug,
+{
+    if left_normalized == right_normalized {
+        Ok(())
+    } else {
+        Err(format!(
+            "{left_ident}: {left:?}\n{right_ident}: {right:?}\nnormalized {left_ident}: {left_normalized:?}\nnormalized {right_ident}: {right_normalized:?}"
+        ))
+    }
+}
+
+/// Check that `left` and `right` are equal, or differ by a single insertion or deletion.
+///
+/// On failure, the position of the first difference and why it is not a single insertion or deletion
+/// is shown.
+pub fn edit1_difference<L, R, T>(
+    left: &L,
+    right: &R,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    L: AsRef<[T]> + ?Sized,
+    R: AsRef<[T]> + ?Sized,
+    T: PartialEq + Debug,
+{
+    let (left, right) = (left.as_ref(), right.as_ref());
+    let Some(index) = first_difference(left, right) else {
+        return Ok(());
+    };
+    if left.len() == right.len() {
+        return Err(format!(
+            "substitution at index {index}, which is not an insertion or deletion\n{left_ident}[{index}]: {:?}\n{right_ident}[{index}]: {:?}",
+            left[index], right[index]
+        ));
+    }
+    let ((longer, longer_ident), (shorter, shorter_ident)) = if left.len() > right.len() {
+        ((left, left_ident), (right, right_ident))
+    } else {
+        ((right, right_ident), (left, left_ident))
+    };
+    if longer.len() - shorter.len() > 1 {
+        return Err(format!(
+            "lengths differ by more than one: {left_ident} has {} elements, {right_ident} has {}",
+            left.len(),
+            right.len()
+        ));
+    }
+    // `index` is the only candidate for the extra element, everything after it has to shift by one
+    match first_difference(&longer[index + 1..], &shorter[index..]) {
+        None => Ok(()),
+        Some(offset) => Err(format!(
+            "more than one edit: after removing {longer_ident}[{index}] = {:?}, {longer_ident}[{}] = {:?} differs from {shorter_ident}[{}] = {:?}",
+            longer[index],
+            index + 1 + offset,
+            longer[index + 1 + offset],
+            index + offset,
+            shorter[index + offset]
+        )),
+    }
+}
+
+/// The lines that differ between `left` and `right`, prefixed with `-` if they are only in `left` and
+/// with `+` if they are only in `right`.
+///
+/// The lines that both have in common are found with the longest common subsequence, so a changed
+/// line shows up as a removed and an added line. If the table for that would have more than
+/// `MAX_LCS_CELLS` cells, only the first differing line of both sides is shown.
+pub fn changed_lines(left: &str, right: &str) -> Vec<String> {
+    let left: Vec<&str> = left.lines().collect();
+    let right: Vec<&str> = right.lines().collect();
+    // The common prefix and suffix are never part of the difference, so leave them out of the table
+    let prefix = left.iter().zip(&right).take_while(|(l, r)| l == r).count();
+    let suffix = left[prefix..]
+        .iter()
+        .rev()
+        .zip(right[prefix..].iter().rev())
+        .take_while(|(l, r)| l == r)
+        .count();
+    let left = &left[prefix..left.len() - suffix];
+    let right = &right[prefix..right.len() - suffix];
+    if left.len().saturating_mul(right.len()) > MAX_LCS_CELLS {
+        let mut changed: Vec<String> = left
+            .first()
+            .map(|line| format!("-{line}"))
+            .into_iter()
+            .collect();
+        changed.extend(right.first().map(|line| format!("+{line}")));
+        changed.push(format!(
+            "(only the first differing line is shown, {} and {} lines are too many to compare)",
+            left.len(),
+            right.len()
+        ));
+        return changed;
+    }
+    // common[i][j] is the length of the longest common subsequence of left[i..] and right[j..]
+    let mut common = vec![vec![0_usize; right.len() + 1]; left.len() + 1];
+    for i in (0..left.len()).rev() {
+        for j in (0..right.len()).rev() {
+            common[i][j] = if left[i] == right[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut changed = Vec::new();
+    while i < left.len() || j < right.len() {
+        if i < left.len() && j < right.len() && left[i] == right[j] {
+            i += 1;
+            j += 1;
+        } else if j == right.len() || (i < left.len() && common[i + 1][j] >= common[i][j + 1]) {
+            changed.push(format!("-{}", left[i]));
+            i += 1;
+        } else {
+            changed.push(format!("+{}", right[j]));
+            j += 1;
+        }
+    }
+    changed
+}
+
+/// Compare the pretty [`Debug`] output of `left` and `right` line by line.
+///
+/// On failure, only the lines that differ are shown.
+pub fn debug_difference<L, R>(
+    left: &L,
+    right: &R,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    L: Debug + ?Sized,
+    R: Debug + ?Sized,
+{
+    let changed = changed_lines(&format!("{left:#?}"), &format!("{right:#?}"));
+    if changed.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "differing lines (-{left_ident}, +{right_ident}):\n{}",
+            changed.join("\n")
+        ))
+    }
+}
+
+/// Call `attempt` up to `attempts` times, until it succeeds.
+///
+/// On failure, the number of attempts and the failure of the last attempt are shown.
+pub fn eventually_error<F, E>(attempts: usize, mut attempt: F) -> Result<(), String>
+where
+    F: FnMut() -> Result<(), E>,
+    E: Display,
+{
+    let mut last = None;
+    for _ in 0..attempts {
+        match attempt() {
+            Ok(()) => return Ok(()),
+            Err(failure) => last = Some(failure),
+        }
+    }
+    last.map_or_else(
+        || Err(String::from("no attempts were made")),
+        |failure| {
+            // offset the failure by 3 spaces for clarity
+            let failure = failure.to_string().replace('\n', "\n   ");
+            Err(format!(
+                "all {attempts} attempts failed, the last with:\n   {failure}"
+            ))
+        },
+    )
+}
+
+/// Compare `left` and `right` as text if both are valid UTF-8, and as bytes otherwise.
+///
+/// On failure, the comparison that was used is shown, followed by the lines that differ or by a
+/// hex dump of the first difference. Text that only differs in its line endings is also shown as
+/// bytes, as there are no differing lines to show.
+pub fn bytes_text_difference<L, R>(
+    left: &L,
+    right: &R,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    L: AsRef<[u8]> + ?Sized,
+    R: AsRef<[u8]> + ?Sized,
+{
+    let (left, right) = (left.as_ref(), right.as_ref());
+    if left == right {
+        return Ok(());
+    }
+    match (std::str::from_utf8(left), std::str::from_utf8(right)) {
+        (Ok(left_text), Ok(right_text)) => {
+            let changed = changed_lines(left_text, right_text);
+            if changed.is_empty() {
+                // the lines are equal, so only the line endings or a trailing newline differ
+                return bytes_difference(left, right, left_ident, right_ident).map_err(|details| {
+                    format!("compared as bytes, only the line endings differ\n{details}")
+                });
+            }
+            Err(format!(
+                "compared as text\ndiffering lines (-{left_ident}, +{right_ident}):\n{}",
+                changed.join("\n")
+            ))
+        }
+        (left_text, _) => {
+            let invalid = if left_text.is_err() {
+                left_ident
+            } else {
+                right_ident
+            };
+            bytes_difference(left, right, left_ident, right_ident).map_err(|details| {
+                format!("compared as bytes, {invalid} is not valid UTF-8\n{details}")
+            })
+        }
+    }
+}
+
+/// The minimum, maximum and sum of a collection of numbers, or [`None`] if it is empty.
+///
+/// Returns an error naming the first index where the sum overflows.
+fn stats<'a, T, I>(items: I, ident: &'static str) -> Result<Option<(T, T, T)>, String>
+where
+    I: IntoIterator<Item = &'a T>,
+    T: Number + CheckedAdd + 'a,
+{
+    let items: Vec<T> = items.into_iter().copied().collect();
+    let Some((&first, rest)) = items.split_first() else {
+        return Ok(None);
+    };
+    let (mut min, mut max, mut sum) = (first, first, first);
+    for (index, &item) in rest.iter().enumerate() {
+        let index = index + 1;
+        if item < min {
+            min = item;
+        }
+        if item > max {
+            max = item;
+        }
+        sum = sum.checked_add(item).ok_or_else(|| {
+            format!(
+                "sum of {ident} overflows at index {index}: {sum:?} + {item:?}\n{ident}[{index}]: {item:?}"
+            )
+        })?;
+    }
+    Ok(Some((min, max, sum)))
+}
+
+/// Compare the minimum, maximum and sum of `left` and `right`, ignoring the order of the elements.
+///
+/// On failure, every statistic that differs is shown for both sides, or the index where a sum
+/// overflows.
+pub fn stats_difference<'a, L, R, T>(
+    left: L,
+    right: R,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    L: IntoIterator<Item = &'a T>,
+    R: IntoIterator<Item = &'a T>,
+    T: Number + CheckedAdd + 'a,
+{
+    let (left, right) = match (stats(left, left_ident)?, stats(right, right_ident)?) {
+        (Some(left), Some(right)) => (left, right),
+        (None, None) => return Ok(()),
+        (None, Some(_)) => return Err(format!("{left_ident} is empty, {right_ident} is not")),
+        (Some(_), None) => return Err(format!("{right_ident} is empty, {left_ident} is not")),
+    };
+    let details: Vec<String> = [
+        ("min", left.0, right.0),
+        ("max", left.1, right.1),
+        ("sum", left.2, right.2),
+    ]
+    .into_iter()
+    .filter(|(_, l, r)| l != r)
+    .map(|(name, l, r)| {
+        format!(
+            "{name} differs (delta: {})\n    {left_ident}: {l:?}\n    {right_ident}: {r:?}",
+            l.delta(r)
+        )
+    })
+    .collect();
+    if details.is_empty() {
+        Ok(())
+    } else {
+        Err(details.join("\n"))
+    }
+}
+
+/// Match `value` against the glob `pattern`.
+///
+/// A `*` does not match a `/`, while a `**` matches any number of directories.
+/// On failure, either the invalid pattern, or the value and the pattern are shown.
+#[cfg(feature = "glob")]
+pub fn glob_error<V>(value: &V, pattern: &str, value_ident: &'static str) -> Result<(), String>
+where
+    V: AsRef<str> + ?Sized,
+{
+    let value = value.as_ref();
+    let glob = glob::Pattern::new(pattern).map_err(|error| format!("invalid glob: {error}"))?;
+    let options = glob::MatchOptions {
+        require_literal_separator: true,
+        ..glob::MatchOptions::new()
+    };
+    if glob.matches_with(value, options) {
+        Ok(())
+    } else {
+        Err(format!("{value_ident}: {value:?}\npattern: {pattern:?}"))
+    }
+}
+
+/// Compare two optional floats, where two [`Some`]s may differ by at most `epsilon`.
+///
+/// On failure, either the [`Some`] and [`None`] mismatch, or both values and their difference are shown.
+pub fn opt_approx_difference<T, E>(
+    left: Option<T>,
+    right: Option<T>,
+    epsilon: E,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    T: Into<f64> + Copy + Debug,
+    E: Into<f64> + Copy + Debug,
+{
+    match (left, right) {
+        (None, None) => Ok(()),
+        (Some(left), Some(right)) => {
+            let difference = (left.into() - right.into()).abs();
+            // A NaN difference fails, as it isn't smaller than anything
+            if difference <= epsilon.into() {
+                Ok(())
+            } else {
+                Err(format!(
+                    "difference: {difference:?} (allowed: {epsilon:?})\n{left_ident}: {left:?}\n{right_ident}: {right:?}"
+                ))
+            }
+        }
+        (left, right) => Err(format!("{left_ident}: {left:?}\n{right_ident}: {right:?}")),
+    }
+}
+
+/// A weak reference, for checking whether the value it points to is still alive.
+pub trait WeakRef {
+    /// The number of strong references to the value, `0` if it has been dropped.
+    fn strong_count(&self) -> usize;
+}
+
+impl<T: ?Sized> WeakRef for std::rc::Weak<T> {
+    fn strong_count(&self) -> usize {
+        self.strong_count()
+    }
+}
+
+impl<T: ?Sized> WeakRef for std::sync::Weak<T> {
+    fn strong_count(&self) -> usize {
+        self.strong_count()
+    }
+}
+
+/// Check that the value behind `weak` is alive if `alive` is `true`, or dropped otherwise.
+///
+/// On failure, the number of strong references to the value is shown.
+pub fn weak_state_error<W: WeakRef + ?Sized>(
+    weak: &W,
+    alive: bool,
+    ident: &'static str,
+) -> Result<(), String> {
+    let strong = weak.strong_count();
+    if (strong > 0) == alive {
+        Ok(())
+    } else {
+        Err(format!("strong references to {ident}: {strong}"))
+    }
+}
+
+/// Compare `left` and `right` after clamping both into the range `low..=high`.
+///
+/// On failure, either the empty range, or both values and their clamped forms are shown.
+pub fn clamped_difference<T: PartialOrd + Copy + Debug>(
+    left: T,
+    right: T,
+    low: T,
+    high: T,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String> {
+    // A NaN bound makes the range empty, as it can't be compared
+    if !matches!(
+        low.partial_cmp(&high),
+        Some(std::cmp::Ordering::Less | std::cmp::Ordering::Equal)
+    ) {
+        return Err(format!("the range {low:?}..={high:?} is empty"));
+    }
+    let clamp = |value: T| {
+        if value < low {
+            low
+        } else if value > high {
+            high
+        } else {
+            value
+        }
+    };
+    let (clamped_left, clamped_right) = (clamp(left), clamp(right));
+    if clamped_left == clamped_right {
+        Ok(())
+    } else {
+        Err(format!(
+            "{left_ident}: {left:?} (clamped: {clamped_left:?})\n{right_ident}: {right:?} (clamped: {clamped_right:?})"
+        ))
+    }
+}
+
+/// The default seed for [`permutation_difference`].
+pub const DEFAULT_SHUFFLE_SEED: u64 = 0x5EED;
+
+/// Shuffle `items` with a Fisher-Yates shuffle, using a xorshift generator seeded with `seed`.
+///
+/// If the shuffle happens to leave every item in place, the first two items are swapped, so the
+/// order always changes when there is more than one item.
+fn shuffle<T>(items: &mut [T], seed: u64) {
+    // xorshift gets stuck on zero
+    let mut state = seed | 1;
+    let mut moved = false;
+    for i in (1..items.len()).rev() {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let bound = u64::try_from(i + 1).unwrap_or(u64::MAX);
+        let j = usize::try_from(state % bound).unwrap_or(i);
+        items.swap(i, j);
+        moved |= i != j;
+    }
+    if !moved && items.len() > 1 {
+        items.swap(0, 1);
+    }
+}
+
+/// Run `function` on `input` and on a shuffled copy of `input`, and compare the outputs.
+///
+/// On failure, both outputs and the seed of the shuffle are shown.
+pub fn permutation_difference<I, T, F, O>(
+    input: &I,
+    mut function: F,
+    seed: u64,
+    input_ident: &'static str,
+) -> Result<(), String>
+where
+    I: Clone + AsMut<[T]>,
+    F: FnMut(I) -> O,
+    O: PartialEq + Debug,
+{
+    let mut shuffled = input.clone();
+    shuffle(shuffled.as_mut(), seed);
+    let expected = function(input.clone());
+    let actual = function(shuffled);
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(format!(
+            "output for {input_ident}: {expected:?}\noutput for shuffled {input_ident} (seed {seed}): {actual:?}"
+        ))
+    }
+}
+
+/// A number that can be added without silently overflowing.
+pub trait CheckedAdd: Copy + Default + PartialOrd + Debug {
+    /// The sum of `self` and `other`, or [`None`] if it overflows.
+    fn checked_add(self, other: Self) -> Option<Self>;
+}
+
+/// Implement [`CheckedAdd`] for integers, using their own `checked_add`.
+macro_rules! impl_checked_add_integer {
+    ($($ty:ty),+ $(,)?) => {
+        $(
+            impl CheckedAdd for $ty {
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$ty>::checked_add(self, other)
+                }
+            }
+        )+
+    };
+}
+
+impl_checked_add_integer!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// Implement [`CheckedAdd`] for floats, which become infinite instead of overflowing.
+macro_rules! impl_checked_add_float {
+    ($($ty:ty),+ $(,)?) => {
+        $(
+            impl CheckedAdd for $ty {
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    Some(self + other)
+                }
+            }
+        )+
+    };
+}
+
+impl_checked_add_float!(f32, f64);
+
+/// Check that no running sum of `items` is below zero, where zero is [`Default::default`].
+///
+/// On failure, the first index where the running sum is negative is shown with the sum at that point,
+/// or the first index where the running sum overflows.
+pub fn prefix_sums_error<S, T>(items: &S, ident: &'static str) -> Result<(), String>
+where
+    S: AsRef<[T]> + ?Sized,
+    T: CheckedAdd,
+{
+    let zero = T::default();
+    let mut sum = zero;
+    for (index, &item) in items.as_ref().iter().enumerate() {
+        let Some(next) = sum.checked_add(item) else {
+            return Err(format!(
+                "running sum overflows at index {index}: {sum:?} + {item:?}\n{ident}[{index}]: {item:?}"
+            ));
+        };
+        sum = next;
+        if sum < zero {
+            return Err(format!(
+                "running sum at index {index}: {sum:?}\n{ident}[{index}]: {item:?}"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Run the tests in `body`, printing the failure to stderr if one of them fails.
+///
+/// Returns [`ExitCode::SUCCESS`](std::process::ExitCode::SUCCESS) if all tests passed, and
+/// [`ExitCode::FAILURE`](std::process::ExitCode::FAILURE) otherwise.
+pub fn run_main<F>(body: F) -> std::process::ExitCode
+where
+    F: FnOnce() -> Result<(), TestFailure>,
+{
+    match body() {
+        Ok(()) => std::process::ExitCode::SUCCESS,
+        Err(failure) => {
+            eprintln!("{failure}");
+            std::process::ExitCode::FAILURE
+        }
+    }
+}
+
+/// Remove the [`Default::default`] elements at the end of `slice`.
+fn trim_trailing_default<T: Default + PartialEq>(slice: &[T]) -> &[T] {
+    let default = T::default();
+    let end = slice
+        .iter()
+        .rposition(|item| item != &default)
+        .map_or(0, |index| index + 1);
+    &slice[..end]
+}
+
+/// Compare `left` and `right` after removing the trailing [`Default::default`] elements from both.
+///
+/// On failure, the trimmed slices and the first index where they differ are shown.
+pub fn trailing_default_difference<L, R, T>(
+    left: &L,
+    right: &R,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    L: AsRef<[T]> + ?Sized,
+    R: AsRef<[T]> + ?Sized,
+    T: Default + PartialEq + Debug,
+{
+    let (left, right) = (
+        trim_trailing_default(left.as_ref()),
+        trim_trailing_default(right.as_ref()),
+    );
+
+    match first_difference(left, right) {
+        None => Ok(()),
+        Some(index) => Err(format!(
+            "trimmed {left_ident}: {left:?}\ntrimmed {right_ident}: {right:?}\nfirst difference at index {index}: {} != {}",
+            describe_index(left, index),
+            describe_index(right, index),
+        )),
+    }
+}
+
+/// The default tolerance for [`scaled_difference`].
+pub const DEFAULT_SCALED_TOLERANCE: f64 = 1e-9;
+
+/// Check that `left` is within `tolerance` of `right * factor`.
+///
+/// On failure, both values, the scaled value and the difference are shown.
+pub fn scaled_difference<L, R, F, T>(
+    left: L,
+    right: R,
+    factor: F,
+    tolerance: T,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    L: Into<f64> + Copy + Debug,
+    R: Into<f64> + Copy + Debug,
+    F: Into<f64> + Copy + Debug,
+    T: Into<f64> + Copy + Debug,
+{
+    let scaled = right.into() * factor.into();
+    let difference = (left.into() - scaled).abs();
+    // A NaN difference fails, as it isn't smaller than anything
+    if difference <= tolerance.into() {
+        return Ok(());
+    }
+    Err(format!(
+        "{left_ident}: {left:?}\n{right_ident}: {right:?}\n{right_ident} * {factor:?}: {scaled:?}\ndifference: {difference:?} (allowed: {tolerance:?})"
+    ))
+}
+
+/// Check that the regular expressions `left` and `right` agree on whether they match each sample.
+///
+/// On failure, either the invalid pattern, or the first sample where the patterns disagree is shown.
+#[cfg(feature = "regex")]
+pub fn regex_equiv_difference<S, T>(
+    left: &str,
+    right: &str,
+    samples: &S,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    S: AsRef<[T]> + ?Sized,
+    T: AsRef<str>,
+{
+    let compile = |pattern, ident| {
+        regex::Regex::new(pattern).map_err(|error| format!("invalid regex {ident}: {error}"))
+    };
+    let (left_regex, right_regex) = (compile(left, left_ident)?, compile(right, right_ident)?);
+    let describe = |matches| if matches { "matches" } else { "does not match" };
+    for (index, sample) in samples.as_ref().iter().enumerate() {
+        let sample = sample.as_ref();
+        let (left_match, right_match) = (left_regex.is_match(sample), right_regex.is_match(sample));
+        if left_match != right_match {
+            return Err(format!(
+                "sample {index}: {sample:?}\n{left_ident} {}: {left:?}\n{right_ident} {}: {right:?}",
+                describe(left_match),
+                describe(right_match),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Measure how long `compare` takes, if the `timing` feature is enabled.
+///
+/// The macros only call this after a comparison failed, so `compare` repeats that comparison.
+#[cold]
+pub fn comparison_time(compare: impl FnOnce() -> bool) -> Option<Duration> {
+    if cfg!(feature = "timing") {
+        let start = Instant::now();
+        std::hint::black_box(compare());
+        Some(start.elapsed())
+    } else {
+        None
+    }
+}
+
+/// Check that every value of `map` is at most `threshold`.
+///
+/// On failure, every entry with a larger value is shown, sorted by the [`Debug`] output of the key
+/// so the order does not depend on the map.
+pub fn values_le_error<M, T>(map: &M, threshold: &T, ident: &'static str) -> Result<(), String>
+where
+    M: MapEntries + ?Sized,
+    M::Key: Debug,
+    M::Value: PartialOrd<T> + Debug,
+    T: Debug + ?Sized,
+{
+    let mut exceeding: Vec<(String, &M::Value)> = map
+        .map_entries()
+        .into_iter()
+        .filter(|(_, value)| {
+            !matches!(
+                (*value).partial_cmp(threshold),
+                Some(std::cmp::Ordering::Less | std::cmp::Ordering::Equal)
+            )
+        })
+        .map(|(key, value)| (format!("{key:?}"), value))
+        .collect();
+    if exceeding.is_empty() {
+        return Ok(());
+    }
+    exceeding.sort_by(|(left, _), (right, _)| left.cmp(right));
+    let entries: Vec<String> = exceeding
+        .into_iter()
+        .map(|(key, value)| format!("{ident}[{key}]: {value:?}"))
+        .collect();
+    Err(format!(
+        "{} of {} values exceed {threshold:?}\n{}",
+        entries.len(),
+        map.map_entries().len(),
+        entries.join("\n")
+    ))
+}
+
+/// The number of representable [`f64`] values between `left` and `right`, or [`None`] if one is NaN.
+fn ulp_distance(left: f64, right: f64) -> Option<u64> {
+    if left.is_nan() || right.is_nan() {
+        return None;
+    }
+    // Map the bits to integers that are ordered like the floats, with `0.0` and `-0.0` equal
+    let ordered = |value: f64| {
+        let magnitude = value.abs().to_bits();
+        if value.is_sign_negative() {
+            (1 << 63) - magnitude
+        } else {
+            (1 << 63) + magnitude
+        }
+    };
+    Some(ordered(left).abs_diff(ordered(right)))
+}
+
+/// Check that `left` and `right` are at most `ops` ULPs apart, allowing one ULP of error per operation.
+///
+/// On failure, both values, the distance in ULPs and the number of operations are shown.
+pub fn ulps_scaled_difference(
+    left: f64,
+    right: f64,
+    ops: u64,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String> {
+    match ulp_distance(left, right) {
+        Some(distance) if distance <= ops => Ok(()),
+        Some(distance) => Err(format!(
+            "distance: {distance} ULPs (allowed: {ops}, one per operation)\n{left_ident}: {left:?}\n{right_ident}: {right:?}"
+        )),
+        None => Err(format!(
+            "NaN has no distance\n{left_ident}: {left:?}\n{right_ident}: {right:?}"
+        )),
+    }
+}
+
+/// Find `target` in the sorted `slice` with a binary search.
+///
+/// On failure, the index where `target` would be inserted and the elements around it are shown.
+pub fn binary_search_error<S, T>(slice: &S, target: &T, ident: &'static str) -> Result<(), String>
+where
+    S: AsRef<[T]> + ?Sized,
+    T: Ord + Debug,
+{
+    let slice = slice.as_ref();
+    match slice.binary_search(target) {
+        Ok(_) => Ok(()),
+        Err(index) => {
+            let before = index.checked_sub(1).map_or_else(
+                || String::from("<start>"),
+                |before| describe_index(slice, before),
+            );
+            Err(format!(
+                "insertion point: {index}\n{ident}[{}..{}]: {before}, {}",
+                index.saturating_sub(1),
+                index + 1,
+                describe_index(slice, index)
+            ))
+        }
+    }
+}
+
+/// Compare `left` and `right` element by element, where a NaN on either side matches anything.
+///
+/// On failure, the length difference or the first index that differs is shown.
+pub fn nan_wildcard_difference<L, R>(
+    left: &L,
+    right: &R,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    L: AsRef<[f64]> + ?Sized,
+    R: AsRef<[f64]> + ?Sized,
+{
+    let (left, right) = (left.as_ref(), right.as_ref());
+    if left.len() != right.len() {
+        return Err(format!(
+            "lengths differ: {left_ident} has {} elements, {right_ident} has {}",
+            left.len(),
+            right.len()
+        ));
+    }
+    let mismatch = left
+        .iter()
+        .zip(right)
+        // A NaN can't be compared, so it never counts as a difference
+        .position(|(l, r)| l.partial_cmp(r).is_some_and(std::cmp::Ordering::is_ne));
+    match mismatch {
+        None => Ok(()),
+        Some(index) => Err(format!(
+            "first difference at index {index}: {:?} != {:?}",
+            left[index], right[index]
+        )),
+    }
+}
+
+/// Compare the `cached` value for `key` with a fresh computation of it.
+///
+/// On failure, either the missing key, or the key with both values is shown.
+pub fn cache_difference<K, V, F>(
+    cached: Option<&V>,
+    key: &K,
+    compute: F,
+    key_ident: &'static str,
+) -> Result<(), String>
+where
+    K: Debug + ?Sized,
+    V: PartialEq + Debug,
+    F: FnOnce(&K) -> V,
+{
+    let Some(cached) = cached else {
+        return Err(format!("the key is not cached\n{key_ident}: {key:?}"));
+    };
+    let computed = compute(key);
+    if *cached == computed {
+        Ok(())
+    } else {
+        Err(format!(
+            "{key_ident}: {key:?}\ncached: {cached:?}\ncomputed: {computed:?}"
+        ))
+    }
+}
+
+/// Collect every JSON pointer where `left` and `right` differ into `differences`.
+///
+/// `pointer` is the JSON pointer to `left` and `right`, like `/users/0/name`.
+#[cfg(feature = "serde_json")]
+fn json_pointer_differences(
+    left: &serde_json::Value,
+    right: &serde_json::Value,
+    pointer: &str,
+    differences: &mut Vec<(String, String, String)>,
+) {
+    use serde_json::Value;
+
+    /// Describe a value that might be missing.
+    fn describe(value: Option<&Value>) -> String {
+        value.map_or_else(|| String::from("<missing>"), ToString::to_string)
+    }
+
+    /// Compare the values at the same key or index of two objects or arrays.
+    fn compare(
+        left: Option<&Value>,
+        right: Option<&Value>,
+        pointer: String,
+        differences: &mut Vec<(String, String, String)>,
+    ) {
+        match (left, right) {
+            (Some(l), Some(r)) => json_pointer_differences(l, r, &pointer, differences),
+            (l, r) => differences.push((pointer, describe(l), describe(r))),
+        }
+    }
+
+    match (left, right) {
+        (Value::Object(left), Value::Object(right)) => {
+            for key in left
+                .keys()
+                .chain(right.keys().filter(|key| !left.contains_key(*key)))
+            {
+                // `~` and `/` are escaped in JSON pointers
+                let token = key.replace('~', "~0").replace('/', "~1");
+                let pointer = format!("{pointer}/{token}");
+                compare(left.get(key), right.get(key), pointer, differences);
+            }
+        }
+        (Value::Array(left), Value::Array(right)) => {
+            for index in 0..left.len().max(right.len()) {
+                let pointer = format!("{pointer}/{index}");
+                compare(left.get(index), right.get(index), pointer, differences);
+            }
+        }
+        (left, right) if left == right => {}
+        (left, right) => {
+            differences.push((pointer.to_owned(), left.to_string(), right.to_string()));
+        }
+    }
+}
+
+/// Compare two JSON values and list every JSON pointer where they differ, up to `cap` pointers.
+///
+/// On failure, every differing pointer is shown with both values.
+#[cfg(feature = "serde_json")]
+pub fn json_all_differences(
+    left: &serde_json::Value,
+    right: &serde_json::Value,
+    cap: usize,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String> {
+    let mut differences = Vec::new();
+    json_pointer_differences(left, right, "", &mut differences);
+    if differences.is_empty() {
+        return Ok(());
+    }
+    let mut lines = vec![format!(
+        "{} differing paths ({left_ident} != {right_ident}):",
+        differences.len()
+    )];
+    lines.extend(differences.iter().take(cap).map(|(pointer, l, r)| {
+        // The empty pointer is the whole document
+        let pointer = if pointer.is_empty() {
+            "<root>"
+        } else {
+            pointer
+        };
+        format!("  {pointer}: {l} != {r}")
+    }));
+    if differences.len() > cap {
+        lines.push(format!("  ... and {} more", differences.len() - cap));
+    }
+    Err(lines.join("\n"))
+}
+
+/// Check that no element of `slice` is equal to `forbidden`.
+///
+/// On failure, the first index where the element is equal to `forbidden` is shown.
+pub fn none_equal_error<L, T, V>(
+    slice: &L,
+    forbidden: &V,
+    slice_ident: &'static str,
+) -> Result<(), String>
+where
+    L: AsRef<[T]> + ?Sized,
+    T: PartialEq<V> + Debug,
+    V: ?Sized,
+{
+    slice
+        .as_ref()
+        .iter()
+        .enumerate()
+        .find(|(_, item)| *item == forbidden)
+        .map_or(Ok(()), |(index, item)| {
+            Err(format!(
+                "first match at index {index}\n{slice_ident}[{index}]: {item:?}"
+            ))
+        })
+}
+
+/// Format the code points of `string` like `U+0065 U+0301`.
+#[cfg(feature = "unicode")]
+fn code_points(string: &str) -> String {
+    string
+        .chars()
+        .map(|c| format!("U+{:04X}", u32::from(c)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Check that `left` and `right` are equal after normalizing both to Unicode Normalization Form C.
+///
+/// On failure, the code points of both strings are shown, before and after normalization.
+#[cfg(feature = "unicode")]
+pub fn nfc_difference<L, R>(
+    left: &L,
+    right: &R,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    L: AsRef<str> + ?Sized,
+    R: AsRef<str> + ?Sized,
+{
+    use unicode_normalization::UnicodeNormalization;
+
+    let (left, right) = (left.as_ref(), right.as_ref());
+    let (left_nfc, right_nfc) = (
+        left.nfc().collect::<String>(),
+        right.nfc().collect::<String>(),
+    );
+    if left_nfc == right_nfc {
+        return Ok(());
+    }
+    Err(format!(
+        "{left_ident}: {left:?}\n{left_ident} code points: {}\n{left_ident} in NFC: {}\n\
+         {right_ident}: {right:?}\n{right_ident} code points: {}\n{right_ident} in NFC: {}",
+        code_points(left),
+        code_points(&left_nfc),
+        code_points(right),
+        code_points(&right_nfc),
+    ))
+}
+
+/// Check that `output_len(size)` is equal to `expected(size)` for every size in `sizes`.
+///
+/// On failure, the first size where the lengths differ is shown, with both lengths.
+pub fn output_len_difference<S, F, E>(
+    sizes: &S,
+    mut output_len: F,
+    mut expected: E,
+    f_ident: &'static str,
+    expected_ident: &'static str,
+) -> Result<(), String>
+where
+    S: AsRef<[usize]> + ?Sized,
+    F: FnMut(usize) -> usize,
+    E: FnMut(usize) -> usize,
+{
+    for &size in sizes.as_ref() {
+        let (actual, expected) = (output_len(size), expected(size));
+        if actual != expected {
+            return Err(format!(
+                "first difference at size {size}\n{f_ident}({size}).len(): {actual}\n{expected_ident}({size}): {expected}"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Hash `value` with the [`DefaultHasher`](std::collections::hash_map::DefaultHasher).
+fn default_hash<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Check that `left` is equal to `right`.
+///
+/// On failure, both values are shown together with their hashes, so an inconsistency between [`Hash`]
+/// and [`PartialEq`] is easy to spot.
+pub fn hash_difference<T>(
+    left: &T,
+    right: &T,
+    left_ident: &'static str,
+    right_ident: &'static str,
+) -> Result<(), String>
+where
+    T: Hash + PartialEq + Debug + ?Sized,
+{
+    if left == right {
+        return Ok(());
+    }
+    let (left_hash, right_hash) = (default_hash(left), default_hash(right));
+    let mut details = format!(
+        "{left_ident}: {left:?}\n{right_ident}: {right:?}\nhash of {left_ident}: {left_hash:#018x}\nhash of {right_ident}: {right_hash:#018x}"
+    );
+    if left_hash == right_hash {
+        details.push_str("\nthe hashes are equal, although the values are not");
+    }
+    Err(details)
+}
+
+/// Prevents [`Float`] from being implemented outside this crate.
+mod sealed {
+    /// Implemented by [`f32`] and [`f64`].
+    pub trait Sealed {}
+
+    impl Sealed for f32 {}
+    impl Sealed for f64 {}
+}
+
+/// A float that can be compared approximately, with a default epsilon.
+pub trait Float: sealed::Sealed + Copy + Debug + PartialOrd + std::ops::Sub<Output = Self> {
+    /// The epsilon that is used when none is given, the machine epsilon of the type.
+    const EPSILON: Self;
+
+    /// The absolute value of `self`.
+    #[must_use]
+    fn abs(self) -> Self;
+}
+
+/// Implement [`Float`] for the primitive floats.
+macro_rules! impl_float {
+    ($($ty:ident),+ $(,)?) => {
+        $(
+            impl Float for $ty {
+                const EPSILON: Self = $ty::EPSILON;
+
+                fn abs(self) -> Self {
+                    self.abs()
+                }
+            }
+        )+
+    };
+}
+
+impl_float!(f32, f64);
+
+/// Check that `left` and `right` differ by at most `epsilon`, or [`Float::EPSILON`] if it is [`None`].
+///
+/// On failure, both values, their difference and the epsilon are shown. Values without an ident are
+/// literals, which are already in the message.
+pub fn approx_difference<T: Float>(
+    left: T,
+    right: T,
+    epsilon: Option<T>,
+    left_ident: Option<&'static str>,
+    right_ident: Option<&'static str>,
+) -> Result<(), String> {
+    // Equal infinities have a NaN difference, so check for equality first
+    if left == right {
+        return Ok(());
+    }
+    let epsilon = epsilon.unwrap_or(T::EPSILON);
+    let difference = (left - right).abs();
+    // A NaN difference fails, as it isn't smaller than anything
+    if difference <= epsilon {
+        return Ok(());
+    }
+    let values = [(left_ident, left), (right_ident, right)];
+    let mut lines: Vec<String> = values
+        .iter()
+        .filter_map(|(ident, value)| ident.map(|ident| format!("{ident}: {value:?}")))
+        .collect();
+    lines.push(format!("difference: {difference:?}"));
+    lines.push(format!("epsilon: {epsilon:?}"));
+    Err(lines.join("\n"))
+}
+
+/// Check that the sorted `values` contain every integer from the first to the last value.
+///
+/// Duplicates are allowed. On failure, the first gap is shown, or the first pair that is out of order.
+pub fn contiguous_error<'a, I, T>(values: I, ident: &'static str) -> Result<(), String>
+where
+    I: IntoIterator<Item = &'a T>,
+    T: Integer + Ord + 'a,
+{
+    let mut values = values.into_iter().copied().enumerate();
+    let Some((_, mut previous)) = values.next() else {
+        return Ok(());
+    };
+    for (index, value) in values {
+        if value < previous {
+            return Err(format!(
+                "{ident} is not sorted\n{ident}[{}]: {previous}\n{ident}[{index}]: {value}",
+                index - 1
+            ));
+        }
+        match previous.successor() {
+            Some(next) if value > next => {
+                return Err(format!(
+                    "first gap: {next} is missing between {previous} and {value}"
+                ));
+            }
+            _ => previous = value,
+        }
+    }
+    Ok(())
+}
+
+/// A wrapper that changes the arithmetic of an integer, like [`Wrapping`](std::num::Wrapping).
+pub trait IntegerWrapper {
+    /// The wrapped integer type.
+    type Inner: PartialEq + Debug;
+
+    /// Get a reference to the wrapped integer.
+    fn inner(&self) -> &Self::Inner;
+}
+
+impl<T: PartialEq + Debug> IntegerWrapper for std::num::Wrapping<T> {
+    type Inner = T;
+
+    fn inner(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: PartialEq + Debug> IntegerWrapper for std::num::Saturating<T> {
+    type Inner = T;
+
+    fn inner(&self) -> &T {
+        &self.0
+    }
+}
+
+/// Match `value` against `pattern` and compare capture group `group` with `expected`.
+///
+/// On failure, either the invalid pattern, the failed match, or the captured text is shown.
+#[cfg(feature = "regex")]
+pub fn capture_difference<V>(
+    value: &V,
+    pattern: &str,
+    group: usize,
+    expected: &str,
+    value_ident: &'static str,
+) -> Result<(), String>
+where
+    V: AsRef<str> + ?Sized,
+{
+    let value = value.as_ref();
+    let regex = regex::Regex::new(pattern).map_err(|error| format!("invalid regex: {error}"))?;
+    let Some(captures) = regex.captures(value) else {
+        return Err(format!(
+            "{value_ident} does not match\n{value_ident}: {value:?}"
+        ));
+    };
+    match captures.get(group) {
+        Some(capture) if capture.as_str() == expected => Ok(()),
+        Some(capture) => Err(format!(
+            "group {group}: {:?}\nexpected: {expected:?}\n{value_ident}: {value:?}",
+            capture.as_str()
+        )),
+        None => Err(format!(
+            "group {group} is not part of the match\n{value_ident}: {value:?}"
+        )),
+    }
+}
+
+/// A primitive number, for showing the difference between two numbers.
+pub trait Number: Copy {
+    /// Format the signed difference `self - other`.
+    fn delta(self, other: Self) -> String;
+}
+
+/// Implement [`Number`] for integers, using `abs_diff` so the difference can't overflow.
+macro_rules! impl_number_integer {
+    ($($ty:ty),+ $(,)?) => {
+        $(
+            impl Number for $ty {
+                fn delta(self, other: Self) -> String {
+                    let magnitude = self.abs_diff(other);
+                    if self >= other {
+                        format!("{magnitude}")
+                    } else {
+                        format!("-{magnitude}")
+                    }
+                }
+            }
+        )+
+    };
+}
+
+impl_number_integer!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// Implement [`Number`] for floats.
+macro_rules! impl_number_float {
+    ($($ty:ty),+ $(,)?) => {
+        $(
+            impl Number for $ty {
+                fn delta(self, other: Self) -> String {
+                    format!("{:?}", self - other)
+                }
+            }
+        )+
+    };
+}
+
+impl_number_float!(f32, f64);
+
+/// The operands of a failed comparison, for showing their difference.
+///
+/// The macros call `Delta(left, right).delta()`. If the operands are numbers of the same type this
+/// resolves to the inherent method, otherwise it falls through the [`Deref`](std::ops::Deref) to [`NoDelta`].
+pub struct Delta<'a, L: ?Sized, R: ?Sized>(pub &'a L, pub &'a R);
+
+impl<T: Number> Delta<'_, T, T> {
+    /// The signed difference between the operands, if the `delta` feature is enabled.
+    #[must_use]
+    pub fn delta(&self) -> Option<String> {
+        if cfg!(feature = "delta") {
+            Some(self.0.delta(*self.1))
+        } else {
+            None
+        }
+    }
+}
+
+impl<L: ?Sized, R: ?Sized> std::ops::Deref for Delta<'_, L, R> {
+    type Target = NoDelta;
+
+    fn deref(&self) -> &Self::Target {
+        &NoDelta
+    }
+}
+
+/// Fallback for [`Delta`], for operands that are not numbers.
+pub struct NoDelta;
+
+impl NoDelta {
+    /// There is no difference to show.
+    #[must_use]
+    pub const fn delta(&self) -> Option<String> {
+        None
+    }
+}
+
+/// The result of a builder, for unwrapping it if it is a [`Result`].
+///
+/// The macros call `Built(&value).kind().into_built(value)`. If the value is a [`Result`] this
+/// resolves to the inherent method returning [`BuiltResult`], otherwise it falls through the
+/// [`Deref`](std::ops::Deref) to [`NotResult`] and [`BuiltValue`].
+pub struct Built<'a, T: ?Sized>(pub &'a T);
+
+impl<T, E> Built<'_, Result<T, E>> {
+    /// The value is a [`Result`].
+    #[must_use]
+    pub const fn kind(&self) -> BuiltResult {
+        BuiltResult
+    }
+}
+
+impl<T: ?Sized> std::ops::Deref for Built<'_, T> {
+    type Target = NotResult;
+
+    fn deref(&self) -> &Self::Target {
+        &NotResult
+    }
+}
+
+/// Fallback for [`Built`], for values that are not a [`Result`].
+pub struct NotResult;
+
+impl NotResult {
+    /// The value is not a [`Result`].
+    #[must_use]
+    pub const fn kind(&self) -> BuiltValue {
+        BuiltValue
+    }
+}
+
+/// A builder that returned a [`Result`].
+pub struct BuiltResult;
+
+impl BuiltResult {
+    /// Return the result unchanged.
+    pub const fn into_built<T, E>(self, result: Result<T, E>) -> Result<T, E> {
+        result
+    }
+}
+
+/// A builder that returned the value directly.
+pub struct BuiltValue;
+
+impl BuiltValue {
+    /// Wrap the value in a [`Result`] that can't fail.
+    pub const fn into_built<T>(self, value: T) -> Result<T, std::convert::Infallible> {
+        Ok(value)
+    }
+}
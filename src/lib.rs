@@ -1,7 +1,14 @@
 #![doc = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/README.md"))]
 
+use std::borrow::Cow;
 use std::fmt::{Debug, Display, Formatter};
+use std::sync::OnceLock;
 
+#[cfg(feature = "alloc-counting")]
+#[doc(hidden)]
+pub mod alloc;
+#[doc(hidden)]
+pub mod helpers;
 mod macros;
 
 /// The line-info feature flag
@@ -12,6 +19,43 @@ mod macros;
 #[doc(hidden)]
 pub const __LINE_INFO: bool = cfg!(feature = "line-info");
 
+/// The prefix that the macros put in front of a failure message.
+const DEFAULT_PREFIX: &str = "Test failed: ";
+
+/// The custom prefix set with [`set_prefix`].
+static PREFIX: OnceLock<&'static str> = OnceLock::new();
+
+/// Replace the `"Test failed: "` prefix of all failure messages with `prefix`.
+///
+/// The prefix can only be set once.
+///
+/// # Errors
+/// Returns the rejected `prefix` if a prefix was already set.
+///
+/// # Examples
+/// ```
+/// use test_eq::{set_prefix, test_eq};
+/// set_prefix("Assertion failed: ").expect("Prefix was not set yet");
+/// let a = 3;
+/// let b = a * 2;
+/// println!("{:?}", test_eq!(a, b));
+/// // prints:
+/// // [src/main.rs:6:1]: Assertion failed: a != b
+/// // a: 3
+/// // b: 6
+/// ```
+pub fn set_prefix(prefix: &'static str) -> Result<(), &'static str> {
+    PREFIX.set(prefix)
+}
+
+/// Replace the default prefix in `message` with the prefix set by [`set_prefix`], if any.
+fn apply_prefix(message: &'static str) -> Cow<'static, str> {
+    match PREFIX.get() {
+        Some(prefix) => Cow::Owned(message.replacen(DEFAULT_PREFIX, prefix, 1)),
+        None => Cow::Borrowed(message),
+    }
+}
+
 /// An error returned when a test in one of the macros fails.
 ///
 /// The error message will display the expected value and the actual value. If the input was not
@@ -78,6 +122,7 @@ impl TestFailure {
         second_val: &dyn std::fmt::Debug,
         args: Option<std::fmt::Arguments<'_>>,
     ) -> Self {
+        let message = apply_prefix(message);
         let error = match args {
             Some(args) => format!(
                 "{message}: {args}\n{first_ident}: {first_val:?}\n{second_ident}: {second_val:?}"
@@ -90,6 +135,63 @@ impl TestFailure {
         Self { error }
     }
 
+    /// Create a failed test from the given `message` and optional `args`, comparing the values of `.*val`.
+    ///
+    /// With the `pretty_assertions` feature the values are shown as a diff, otherwise this is the
+    /// same as [`test_failed_two_idents`].
+    #[doc(hidden)]
+    #[inline(never)]
+    #[cold]
+    pub fn test_failed_comparison<T, U>(
+        message: &'static str,
+        first_ident: &'static str,
+        first_val: &T,
+        second_ident: &'static str,
+        second_val: &U,
+        args: Option<std::fmt::Arguments<'_>>,
+    ) -> Self
+    where
+        T: std::fmt::Debug + ?Sized,
+        U: std::fmt::Debug + ?Sized,
+    {
+        #[cfg(feature = "pretty_assertions")]
+        {
+            // the idents are already part of `message`
+            let _ = (first_ident, second_ident);
+            Self::test_failed_inner_comparison(message, &first_val, &second_val, args)
+        }
+        #[cfg(not(feature = "pretty_assertions"))]
+        {
+            Self::test_failed_inner_two_idents(
+                message,
+                first_ident,
+                &first_val,
+                second_ident,
+                &second_val,
+                args,
+            )
+        }
+    }
+
+    /// Non-generic version of [`test_failed_comparison`] to reduce code bloat.
+    #[doc(hidden)]
+    #[cfg(feature = "pretty_assertions")]
+    fn test_failed_inner_comparison(
+        message: &'static str,
+        first_val: &dyn std::fmt::Debug,
+        second_val: &dyn std::fmt::Debug,
+        args: Option<std::fmt::Arguments<'_>>,
+    ) -> Self {
+        let message = apply_prefix(message);
+        let comparison = pretty_assertions::Comparison::new(first_val, second_val);
+        let error = match args {
+            Some(args) => format!("{message}: {args}\n{comparison}"),
+            None => format!("{message}\n{comparison}"),
+        };
+
+        Self { error }
+    }
+
     /// Create a failed test from the given `message` and optional `args`, showing the value of `val`.
     ///
     /// `ident` is the name of `val`.
@@ -116,6 +218,7 @@ impl TestFailure {
         val: &dyn std::fmt::Debug,
         args: Option<std::fmt::Arguments<'_>>,
     ) -> Self {
+        let message = apply_prefix(message);
         let error = match args {
             Some(args) => format!("{message}: {args}\n{ident}: {val:?}"),
             None => format!("{message}\n{ident}: {val:?}"),
@@ -124,6 +227,66 @@ impl TestFailure {
         Self { error }
     }
 
+    /// Create a failed test from the given `message` and optional `args`, showing the error `err`
+    /// both with [`Debug`] and [`Display`].
+    ///
+    /// `ident` is the name of the expression that returned `err`.
+    #[doc(hidden)]
+    #[inline(never)]
+    #[cold]
+    pub fn test_failed_error<E>(
+        message: &'static str,
+        ident: &'static str,
+        err: &E,
+        args: Option<std::fmt::Arguments<'_>>,
+    ) -> Self
+    where
+        E: std::fmt::Debug + std::fmt::Display + ?Sized,
+    {
+        Self::test_failed_inner_error(message, ident, &err, &err, args)
+    }
+
+    /// Non-generic version of [`test_failed_error`] to reduce code bloat.
+    #[doc(hidden)]
+    fn test_failed_inner_error(
+        message: &'static str,
+        ident: &'static str,
+        err_debug: &dyn std::fmt::Debug,
+        err_display: &dyn std::fmt::Display,
+        args: Option<std::fmt::Arguments<'_>>,
+    ) -> Self {
+        let message = apply_prefix(message);
+        let error = match args {
+            Some(args) => {
+                format!("{message}: {args}\n{ident}: Err({err_debug:?})\nerror: {err_display}")
+            }
+            None => format!("{message}\n{ident}: Err({err_debug:?})\nerror: {err_display}"),
+        };
+
+        Self { error }
+    }
+
+    /// Create a failed test from the given `message` and optional `args`, followed by `details`.
+    ///
+    /// `details` describes why the test failed, for example which elements were different.
+    #[doc(hidden)]
+    #[inline(never)]
+    #[must_use]
+    #[cold]
+    pub fn test_failed_details(
+        message: &'static str,
+        details: &str,
+        args: Option<std::fmt::Arguments<'_>>,
+    ) -> Self {
+        let message = apply_prefix(message);
+        let error = match args {
+            Some(args) => format!("{message}: {args}\n{details}"),
+            None => format!("{message}\n{details}"),
+        };
+
+        Self { error }
+    }
+
     /// Create a failed test from the given `message` and optional `args`.
     #[doc(hidden)]
     #[inline(never)]
@@ -136,14 +299,40 @@ impl TestFailure {
     where
         T: std::fmt::Debug + ?Sized,
     {
+        let message = apply_prefix(message);
         let error = match args {
             Some(args) => format!("{message}: {args}"),
-            None => message.to_string(),
+            None => message.into_owned(),
         };
 
         Self { error }
     }
 
+    /// Add the difference between the compared values to the failure message, if there is one.
+    #[doc(hidden)]
+    #[inline(never)]
+    #[must_use]
+    #[cold]
+    pub fn with_delta(mut self, delta: Option<String>) -> Self {
+        if let Some(delta) = delta {
+            self.error.push_str("\ndelta: ");
+            self.error.push_str(&delta);
+        }
+        self
+    }
+
+    /// Add the duration of the comparison to the failure message, if it was measured.
+    #[doc(hidden)]
+    #[inline(never)]
+    #[must_use]
+    #[cold]
+    pub fn with_timing(mut self, duration: Option<std::time::Duration>) -> Self {
+        if let Some(duration) = duration {
+            self.error = format!("{}\ncomparison took: {duration:?}", self.error);
+        }
+        self
+    }
+
     /// Create a failed test from two failed test.
     #[doc(hidden)]
     #[inline(never)]
@@ -155,18 +344,8 @@ impl TestFailure {
         args: Option<std::fmt::Arguments<'_>>,
     ) -> Self {
         // offset the error messages by 3 spaces for clarity
-        let mut first = first.error;
-        let mut second = second.error;
-        let mut start_of_search = 0;
-        while let Some(position) = first[start_of_search..].find('\n') {
-            first.insert_str(start_of_search + position + 1, "   ");
-            start_of_search += position + 3;
-        }
-        let mut start_of_search = 0;
-        while let Some(position) = second[start_of_search..].find('\n') {
-            second.insert_str(start_of_search + position + 1, "   ");
-            start_of_search += position + 3;
-        }
+        let first = first.indented();
+        let second = second.indented();
         let error = if let Some(args) = args {
             format!("Both tests failed: {args}\n1: {first}\n2: {second}")
         } else {
@@ -182,12 +361,7 @@ impl TestFailure {
     #[cold]
     pub fn one_test_failed(failure: Self, args: Option<std::fmt::Arguments<'_>>) -> Self {
         // offset the error message by 3 spaces for clarity
-        let mut failure = failure.error;
-        let mut start_of_search = 0;
-        while let Some(position) = failure[start_of_search..].find('\n') {
-            failure.insert_str(start_of_search + position + 1, "   ");
-            start_of_search += position + 3;
-        }
+        let failure = failure.indented();
         let error = if let Some(args) = args {
             format!("One of the tests failed: {args}\n   {failure}")
         } else {
@@ -195,6 +369,111 @@ impl TestFailure {
         };
         Self { error }
     }
+
+    /// Create a failed test from several failed tests.
+    #[doc(hidden)]
+    #[inline(never)]
+    #[must_use]
+    #[cold]
+    pub fn tests_failed(mut failures: Vec<Self>, args: Option<std::fmt::Arguments<'_>>) -> Self {
+        if failures.len() == 1 {
+            return Self::one_test_failed(failures.remove(0), args);
+        }
+        let count = failures.len();
+        // offset the error messages by 3 spaces for clarity
+        let failures: Vec<String> = failures
+            .into_iter()
+            .enumerate()
+            .map(|(index, failure)| format!("{}: {}", index + 1, failure.indented()))
+            .collect();
+        let failures = failures.join("\n");
+        let error = if let Some(args) = args {
+            format!("{count} tests failed: {args}\n{failures}")
+        } else {
+            format!("{count} tests failed:\n{failures}")
+        };
+        Self { error }
+    }
+
+    /// The error message with every line after the first indented by 3 spaces.
+    fn indented(self) -> String {
+        let mut error = self.error;
+        let mut start_of_search = 0;
+        while let Some(position) = error[start_of_search..].find('\n') {
+            error.insert_str(start_of_search + position + 1, "   ");
+            start_of_search += position + 3;
+        }
+        error
+    }
+}
+
+/// A machine-readable description of why a test failed.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Diff<L, R> {
+    /// The compared values were not equal.
+    ValueMismatch {
+        /// The left operand of the comparison.
+        left: L,
+        /// The right operand of the comparison.
+        right: R,
+    },
+}
+
+/// A [`TestFailure`] that also carries a [`Diff`] of the failed test, for inspecting it in code.
+///
+/// It is displayed exactly like the [`TestFailure`], and can be converted into one with `?`.
+pub struct StructuredFailure<L, R> {
+    /// The failure, for displaying.
+    failure: TestFailure,
+    /// The values that caused the failure.
+    diff: Diff<L, R>,
+}
+
+impl<L, R> StructuredFailure<L, R> {
+    /// Combine a `failure` with the `diff` that caused it.
+    #[doc(hidden)]
+    #[inline(never)]
+    #[must_use]
+    #[cold]
+    pub const fn new(failure: TestFailure, diff: Diff<L, R>) -> Self {
+        Self { failure, diff }
+    }
+
+    /// The values that caused the failure.
+    pub const fn diff(&self) -> &Diff<L, R> {
+        &self.diff
+    }
+
+    /// Take the values that caused the failure.
+    pub fn into_diff(self) -> Diff<L, R> {
+        self.diff
+    }
+
+    /// Take the [`TestFailure`], dropping the values.
+    pub fn into_failure(self) -> TestFailure {
+        self.failure
+    }
+}
+
+impl<L, R> From<StructuredFailure<L, R>> for TestFailure {
+    fn from(failure: StructuredFailure<L, R>) -> Self {
+        failure.failure
+    }
+}
+
+impl<L, R> std::error::Error for StructuredFailure<L, R> {}
+
+impl<L, R> Display for StructuredFailure<L, R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        Display::fmt(&self.failure, f)
+    }
+}
+
+impl<L, R> Debug for StructuredFailure<L, R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.failure, f)
+    }
 }
 
 #[cfg(test)]
@@ -268,6 +547,28 @@ mod test {
         assert!(test_ge!(b, a).is_ok());
     }
 
+    #[test]
+    pub fn test_test_lt() {
+        let a = 5;
+        let b = 19;
+        assert!(test_lt!(a, b).is_ok());
+        assert!(test_lt!(b, a).is_err());
+        let a = 'a';
+        let b = 'b';
+        assert!(test_lt!(a, b).is_ok());
+        assert!(test_lt!(b, a).is_err());
+        let a = 42;
+        let b = 42;
+        assert!(test_lt!(a, b).is_err());
+        assert!(test_lt!(b, a).is_err());
+        assert!(test_lt!(a, 43).is_ok());
+        assert!(test_lt!(41, b).is_ok());
+        let failure = test_lt!(a, b, "and a is {}", a).unwrap_err().to_string();
+        assert!(failure.contains("a >= b: and a is 42"), "{failure}");
+        let failure = test_lt!(a, 42, "and a is {}", a).unwrap_err().to_string();
+        assert!(failure.contains("a >= 42: and a is 42"), "{failure}");
+    }
+
     #[test]
     pub fn test_test_or() {
         let a = 5;
@@ -282,8 +583,2708 @@ mod test {
     pub fn test_test_any() {
         let a = 3;
         let b = a * 2;
-        assert!(test_any!(a, [1, 3, 5, 7]).is_err());
+        assert!(test_any!(a, [1, 3, 5, 7]).is_ok());
         assert!(test_any!(b, [1, 3, 5, 7], "and a is {}", a).is_err());
         assert!(test_any!(b, [1, 3, 5, 7]).is_err());
     }
+
+    #[test]
+    pub fn test_test_ok() {
+        #[derive(Debug)]
+        struct CustomError(u8);
+        impl Display for CustomError {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                write!(f, "custom error with code {}", self.0)
+            }
+        }
+
+        let a: Result<(), CustomError> = Ok(());
+        assert!(test_ok!(a).is_ok());
+        let b: Result<(), CustomError> = Err(CustomError(42));
+        let failure = test_ok!(b).unwrap_err().to_string();
+        assert!(failure.contains("b: Err(CustomError(42))"), "{failure}");
+        assert!(failure.contains("custom error with code 42"), "{failure}");
+        let failure = test_ok!(b, "while parsing {}", "header")
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains("while parsing header"), "{failure}");
+        assert!(failure.contains("custom error with code 42"), "{failure}");
+    }
+
+    #[test]
+    pub fn test_test_iter_eq_unordered() {
+        let a = vec![1, 2, 3, 3];
+        let b = vec![3, 1, 3, 2];
+        assert!(test_iter_eq_unordered!(a.clone().into_iter(), b.into_iter()).is_ok());
+        let c = vec![1, 2, 4, 4];
+        let failure = test_iter_eq_unordered!(a.into_iter(), c.into_iter())
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("only in a.into_iter(): [3, 3]"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("only in c.into_iter(): [4, 4]"),
+            "{failure}"
+        );
+
+        let mut map = std::collections::HashMap::new();
+        map.insert(1, "one".to_string());
+        map.insert(2, "two".to_string());
+        map.insert(3, "two".to_string());
+        let expected = vec!["two".to_string(), "one".to_string(), "two".to_string()];
+        assert!(test_iter_eq_unordered!(map.values().cloned(), expected).is_ok());
+        let expected = vec!["one".to_string(), "two".to_string()];
+        let failure = test_iter_eq_unordered!(map.values().cloned(), expected, "map {:?}", map)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("only in map.values().cloned(): [\"two\"]"),
+            "{failure}"
+        );
+        assert!(!failure.contains("only in expected"), "{failure}");
+    }
+
+    #[test]
+    pub fn test_test_within_percent() {
+        let expected = 200.0;
+        assert!(test_within_percent!(204.0, expected, 5).is_ok());
+        assert!(test_within_percent!(196.0, expected, 5).is_ok());
+        assert!(test_within_percent!(210.0, expected, 5).is_ok());
+        assert!(test_within_percent!(190.0, expected, 5).is_ok());
+        assert!(test_within_percent!(105, 100, 5).is_ok());
+        let failure = test_within_percent!(212.0, expected, 5)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("212.0 is not within 5% of expected"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("difference: 6% (allowed: 5%)"),
+            "{failure}"
+        );
+        let actual = 80u8;
+        assert!(test_within_percent!(actual, 100, 5, "actual is {}", actual).is_err());
+    }
+
+    #[test]
+    pub fn test_test_tuple_eq() {
+        let a = 1;
+        let b = "two";
+        let c = 3.0;
+        assert!(test_tuple_eq!((a, b, c), (1, "two", 3.0)).is_ok());
+        let failure = test_tuple_eq!((a, b, c), (0, "two", 3.0))
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains("position 0: a != 0"), "{failure}");
+        assert!(!failure.contains("position 1"), "{failure}");
+        assert!(!failure.contains("position 2"), "{failure}");
+        let failure = test_tuple_eq!((a, b, c), (1, "three", 4.0), "c is {}", c)
+            .unwrap_err()
+            .to_string();
+        assert!(!failure.contains("position 0"), "{failure}");
+        assert!(failure.contains("position 1: b != \"three\""), "{failure}");
+        assert!(failure.contains("b: \"two\""), "{failure}");
+        assert!(failure.contains("position 2: c != 4.0"), "{failure}");
+        assert!(failure.contains("c: 3.0"), "{failure}");
+        assert!(failure.contains("c is 3"), "{failure}");
+        assert!(test_tuple_eq!((a, b), (1, "two")).is_ok());
+        assert!(test_tuple_eq!((a, b, c, 'd'), (1, "two", 3.0, 'e')).is_err());
+    }
+
+    #[test]
+    pub fn test_test_eq_sorted() {
+        let a = vec![3, 1, 2, 2];
+        let b = [2, 1, 2, 3];
+        assert!(test_eq_sorted!(a, b).is_ok());
+        assert!(test_eq_sorted!(a, &b[..]).is_ok());
+        let c = vec![2, 1, 4, 3];
+        let failure = test_eq_sorted!(a, c).unwrap_err().to_string();
+        assert!(failure.contains("sorted a: [1, 2, 2, 3]"), "{failure}");
+        assert!(failure.contains("sorted c: [1, 2, 3, 4]"), "{failure}");
+        assert!(
+            failure.contains("first difference at index 2: 2 != 3"),
+            "{failure}"
+        );
+        let d = [1, 2, 2];
+        let failure = test_eq_sorted!(a, d, "d is {:?}", d)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("first difference at index 3: 3 != <end, length 3>"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "pretty_assertions")]
+    pub fn test_test_eq_pretty_assertions() {
+        #[derive(Debug, PartialEq)]
+        struct Header {
+            magic: u32,
+            version: u8,
+        }
+
+        let a = Header {
+            magic: 0xDEAD_BEEF,
+            version: 1,
+        };
+        let b = Header {
+            magic: 0xDEAD_BEEF,
+            version: 2,
+        };
+        let failure = test_eq!(a, b).unwrap_err().to_string();
+        // the diff is colored, removed lines start with a red `<` and added lines with a green `>`
+        assert!(failure.contains("Diff"), "{failure}");
+        assert!(failure.contains("\u{1b}[31m<    version: "), "{failure}");
+        assert!(failure.contains("\u{1b}[32m>    version: "), "{failure}");
+        assert!(!failure.contains("a: Header"), "{failure}");
+        // equal values have no diff, so `test_ne!` shows both values instead
+        let c = Header {
+            magic: 0xDEAD_BEEF,
+            version: 1,
+        };
+        let failure = test_ne!(a, c).unwrap_err().to_string();
+        assert!(!failure.contains("Diff"), "{failure}");
+        assert!(failure.contains("a: Header"), "{failure}");
+        assert!(failure.contains("c: Header"), "{failure}");
+    }
+
+    #[test]
+    pub fn test_test_contains_all() {
+        let a = vec![1, 3, 5, 7];
+        assert!(test_contains_all!(a, [7, 1, 5]).is_ok());
+        assert!(test_contains_all!(a[..], vec![3]).is_ok());
+        let b: std::collections::HashSet<_> = a.iter().copied().collect();
+        assert!(test_contains_all!(b, [1, 3]).is_ok());
+        let failure = test_contains_all!(a, [1, 2, 3, 4]).unwrap_err().to_string();
+        assert!(failure.contains("missing: [2, 4]"), "{failure}");
+        let failure = test_contains_all!(b, [8, 1, 9], "b has {} items", b.len())
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains("missing: [8, 9]"), "{failure}");
+        assert!(failure.contains("b has 4 items"), "{failure}");
+    }
+
+    #[test]
+    pub fn test_test_hamming_le() {
+        let a = vec![1u8, 2, 3, 4, 5];
+        assert!(test_hamming_le!(a, a.clone(), 0).is_ok());
+        let b = [1u8, 0, 3, 0, 5];
+        assert!(test_hamming_le!(a, b, 2).is_ok());
+        let failure = test_hamming_le!(a, b, 1).unwrap_err().to_string();
+        assert!(
+            failure.contains("distance: 2 bytes (maximum: 1)"),
+            "{failure}"
+        );
+        assert!(failure.contains("differing offsets: [1, 3]"), "{failure}");
+        let c = [1u8, 2, 3];
+        let failure = test_hamming_le!(a, c, 5, "c is {:?}", c)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("a and c have different lengths: c is [1, 2, 3]"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("lengths differ: a has 5 bytes, c has 3 bytes"),
+            "{failure}"
+        );
+        assert!(!failure.contains("Hamming distance"), "{failure}");
+    }
+
+    #[test]
+    pub fn test_test_recv_eq() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let sender = std::thread::spawn(move || {
+            for i in [1, 2, 3, 4, 6] {
+                tx.send(i).expect("Receiver is alive");
+            }
+            tx
+        });
+        assert!(test_recv_eq!(rx, [1, 2, 3]).is_ok());
+        let failure = test_recv_eq!(rx, vec![4, 5]).unwrap_err().to_string();
+        assert!(failure.contains("first mismatch at index 1"), "{failure}");
+        assert!(failure.contains("received: 6"), "{failure}");
+        // keep the sender alive, so the channel times out instead of disconnecting
+        let tx = sender.join().expect("Sender did not panic");
+        let failure = test_recv_eq!(rx, [7], "sender is done")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("timed out after 1s waiting for index 0"),
+            "{failure}"
+        );
+        let failure = test_recv_eq!(rx, [7], timeout = std::time::Duration::from_millis(10))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("timed out after 10ms waiting for index 0"),
+            "{failure}"
+        );
+        drop(tx);
+        let failure = test_recv_eq!(rx, [7]).unwrap_err().to_string();
+        assert!(
+            failure.contains("channel disconnected before index 0"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_nonzero_eq() {
+        use std::num::NonZeroU32;
+
+        let a = NonZeroU32::new(42).expect("Not zero");
+        let b = NonZeroU32::new(42).expect("Not zero");
+        assert!(test_nonzero_eq!(a, b).is_ok());
+        let c = NonZeroU32::new(7).expect("Not zero");
+        let failure = test_nonzero_eq!(a, c).unwrap_err().to_string();
+        assert!(failure.contains("a: 42\nc: 7"), "{failure}");
+        assert!(!failure.contains("NonZero"), "{failure}");
+        assert!(test_nonzero_eq!(a, c, "c is {}", c).is_err());
+    }
+
+    #[test]
+    pub fn test_test_timestamps_monotonic() {
+        use std::time::{Duration, Instant};
+
+        let start = Instant::now();
+        let at = |millis| start + Duration::from_millis(millis);
+        let min = Duration::from_millis(5);
+        let max = Duration::from_millis(15);
+        let a = vec![at(0), at(10), at(20), at(25)];
+        assert!(test_timestamps_monotonic!(a, min, max).is_ok());
+        let b = [at(0), at(10), at(5)];
+        let failure = test_timestamps_monotonic!(b, min, max)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("timestamps 1 and 2 are out of order"),
+            "{failure}"
+        );
+        assert!(failure.contains("2 is 5ms before 1"), "{failure}");
+        let c = [at(0), at(10), at(20), at(40)];
+        let failure = test_timestamps_monotonic!(c, min, max, "limiter {}", 1)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("gap between timestamps 2 and 3 is 20ms"),
+            "{failure}"
+        );
+        assert!(failure.contains("allowed: 5ms..=15ms"), "{failure}");
+    }
+
+    #[test]
+    #[cfg(feature = "hex")]
+    pub fn test_test_bytes_eq_hex() {
+        let a = vec![0xDE, 0xAD, 0xBE, 0xEF];
+        assert!(test_bytes_eq_hex!(a, "deadbeef").is_ok());
+        assert!(test_bytes_eq_hex!(a, "DEADBEEF").is_ok());
+        let failure = test_bytes_eq_hex!(a, "deadbeee").unwrap_err().to_string();
+        assert!(
+            failure.contains("first difference at offset 0x3"),
+            "{failure}"
+        );
+        assert!(failure.contains("a: 00000000: de ad be ef"), "{failure}");
+        assert!(
+            failure.contains("\"deadbeee\": 00000000: de ad be ee"),
+            "{failure}"
+        );
+        let failure = test_bytes_eq_hex!(a, "deadbeefff", "a is {:?}", a)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("lengths differ: a has 4 bytes, \"deadbeefff\" has 5 bytes"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("first difference at offset 0x4"),
+            "{failure}"
+        );
+        let failure = test_bytes_eq_hex!(a, "deadbeeg").unwrap_err().to_string();
+        assert!(
+            failure.contains("\"deadbeeg\" is not valid hex: Invalid character 'g'"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "base64")]
+    pub fn test_test_bytes_eq_base64() {
+        let a = vec![0xDE, 0xAD, 0xBE, 0xEF];
+        assert!(test_bytes_eq_base64!(a, "3q2+7w==").is_ok());
+        let failure = test_bytes_eq_base64!(a, "3q2+7g==")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("first difference at offset 0x3"),
+            "{failure}"
+        );
+        assert!(failure.contains("a: 00000000: de ad be ef"), "{failure}");
+        let failure = test_bytes_eq_base64!(a, "3q2+7w=", "a is {:?}", a)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("\"3q2+7w=\" is not valid base64"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    pub fn test_test_pending() {
+        let mut a = std::future::pending::<u32>();
+        assert!(test_pending!(&mut a).is_ok());
+        assert!(test_pending!(a).is_ok());
+        let b = async { "done" };
+        let failure = test_pending!(b).unwrap_err().to_string();
+        assert!(failure.contains("b is ready"), "{failure}");
+        assert!(failure.contains("output: \"done\""), "{failure}");
+        let c = std::future::ready(5);
+        assert!(test_pending!(c, "c is {}", "ready").is_err());
+    }
+
+    #[test]
+    pub fn test_test_eq_range() {
+        let a = vec![0, 1, 2];
+        assert!(test_eq_range!(a, 0..3).is_ok());
+        assert!(test_eq_range!(a, vec![0, 1, 2]).is_ok());
+        let failure = test_eq_range!(a, 0..4).unwrap_err().to_string();
+        assert!(
+            failure.contains("lengths differ: a has 3 elements, 0..4 has more"),
+            "{failure}"
+        );
+        assert!(failure.contains("next item of 0..4: 3"), "{failure}");
+        let failure = test_eq_range!(a, 0..2).unwrap_err().to_string();
+        assert!(
+            failure.contains("lengths differ: a has 3 elements, 0..2 has 2"),
+            "{failure}"
+        );
+        let failure = test_eq_range!(a, 1..4, "a is {:?}", a)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("first difference at index 0: 0 != 1"),
+            "{failure}"
+        );
+        assert!(test_eq_range!(a, 0..).is_err());
+    }
+
+    #[test]
+    pub fn test_test_wrapping_eq() {
+        use std::num::{Saturating, Wrapping};
+
+        let a = Wrapping(200u8) + Wrapping(100);
+        let b = Wrapping(44u8);
+        assert!(test_wrapping_eq!(a, b).is_ok());
+        let c = Wrapping(45u8);
+        let failure = test_wrapping_eq!(a, c).unwrap_err().to_string();
+        assert!(failure.contains("a: 44\nc: 45"), "{failure}");
+        assert!(!failure.contains("Wrapping"), "{failure}");
+        let d = Saturating(200u8) + Saturating(100);
+        assert!(test_wrapping_eq!(d, Saturating(u8::MAX)).is_ok());
+        assert!(test_wrapping_eq!(d, Saturating(44), "d is {}", d).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    pub fn test_test_capture_eq() {
+        let a = "2024-06".to_string();
+        assert!(test_capture_eq!(a, r"(\d+)-(\d+)", 1, "2024").is_ok());
+        assert!(test_capture_eq!(a, r"(\d+)-(\d+)", 2, "06").is_ok());
+        let failure = test_capture_eq!(a, r"(\d+)-(\d+)", 1, "2025")
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains("group 1: \"2024\""), "{failure}");
+        assert!(failure.contains("expected: \"2025\""), "{failure}");
+        let b = "June 2024";
+        let failure = test_capture_eq!(b, r"(\d+)-(\d+)", 1, "2024", "b is {}", b)
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains("b does not match"), "{failure}");
+        let failure = test_capture_eq!(a, r"(\d+)-(\d+)|(x)", 3, "x")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("group 3 is not part of the match"),
+            "{failure}"
+        );
+        let failure = test_capture_eq!(a, r"(\d+", 1, "2024")
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains("invalid regex"), "{failure}");
+    }
+
+    #[test]
+    #[cfg(feature = "delta")]
+    pub fn test_test_eq_delta() {
+        let a = 100;
+        let b = 97;
+        let failure = test_eq!(a, b).unwrap_err().to_string();
+        assert!(failure.contains("delta: 3"), "{failure}");
+        let failure = test_eq!(b, 100u8).unwrap_err().to_string();
+        assert!(failure.contains("delta: -3"), "{failure}");
+        let failure = test_eq!(i64::MIN, i64::MAX, "extremes")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("delta: -18446744073709551615"),
+            "{failure}"
+        );
+        let failure = test_eq!("hello", "world").unwrap_err().to_string();
+        assert!(!failure.contains("delta"), "{failure}");
+    }
+
+    #[test]
+    pub fn test_test_grid_eq() {
+        let a = vec![vec![1, 2, 3], vec![4, 5, 6]];
+        assert!(test_grid_eq!(a, vec![vec![1, 2, 3], vec![4, 5, 6]]).is_ok());
+        assert!(test_grid_eq!(Vec::<Vec<u8>>::new(), Vec::<Vec<u8>>::new()).is_ok());
+        let b = vec![vec![1, 2, 3]];
+        let failure = test_grid_eq!(a, b).unwrap_err().to_string();
+        assert!(
+            failure.contains("row counts differ: a has 2 rows, b has 1"),
+            "{failure}"
+        );
+        let b = vec![vec![1, 2, 3], vec![4, 5]];
+        let failure = test_grid_eq!(a, b).unwrap_err().to_string();
+        assert!(
+            failure.contains("lengths of row 1 differ: a has 3 columns, b has 2"),
+            "{failure}"
+        );
+        let b = vec![vec![1, 2, 3], vec![4, 5, 7]];
+        let failure = test_grid_eq!(a, b, "step {}", 3).unwrap_err().to_string();
+        assert!(failure.contains("a != b: step 3"), "{failure}");
+        assert!(
+            failure.contains("first difference at (1, 2)\na: 6\nb: 7"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_cmp() {
+        use std::cmp::Ordering;
+        let a = 1;
+        let b = 2;
+        assert!(test_cmp!(a, b, Ordering::Less).is_ok());
+        assert!(test_cmp!(a, a, Ordering::Equal).is_ok());
+        assert!(test_cmp!(b, a, Ordering::Greater).is_ok());
+        let failure = test_cmp!(a, b, Ordering::Greater).unwrap_err().to_string();
+        assert!(
+            failure.contains("a.cmp(&b) != Ordering::Greater"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("a: 1\nb: 2\nexpected: Greater\nactual: Less"),
+            "{failure}"
+        );
+        let failure = test_cmp!(a, a, Ordering::Less, "a is {}", a)
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains(": a is 1"), "{failure}");
+        assert!(failure.contains("actual: Equal"), "{failure}");
+        let failure = test_cmp!(b, a, Ordering::Equal).unwrap_err().to_string();
+        assert!(failure.contains("actual: Greater"), "{failure}");
+    }
+
+    #[test]
+    pub fn test_test_eq_platform() {
+        let platform = if cfg!(windows) {
+            "windows"
+        } else if cfg!(unix) {
+            "unix"
+        } else {
+            "default"
+        };
+        assert!(test_eq_platform!(
+            platform,
+            windows = "windows",
+            unix = "unix",
+            default = "default"
+        )
+        .is_ok());
+        let a = 1;
+        let expected = if cfg!(windows) {
+            2
+        } else if cfg!(unix) {
+            3
+        } else {
+            4
+        };
+        let failure = test_eq_platform!(a, windows = 2, unix = 3, default = 4, "a is {}", a)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("a != expected value for this platform: a is 1"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains(&format!("a: 1\n{platform}: {expected}")),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_slice_eq_all() {
+        let a = vec![1, 2, 3, 4, 5];
+        assert!(test_slice_eq_all!(a, [1, 2, 3, 4, 5]).is_ok());
+        let b = vec![0, 2, 0, 4, 0];
+        let failure = test_slice_eq_all!(a, b).unwrap_err().to_string();
+        assert!(
+            failure.contains(
+                "3 differing indices (a != b):\n  [0]: 1 != 0\n  [2]: 3 != 0\n  [4]: 5 != 0"
+            ),
+            "{failure}"
+        );
+        assert!(!failure.contains("more"), "{failure}");
+        let failure = test_slice_eq_all!(a, b, cap = 1, "a is {:?}", a)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("a != b: a is [1, 2, 3, 4, 5]"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("  [0]: 1 != 0\n  ... and 2 more"),
+            "{failure}"
+        );
+        let failure = test_slice_eq_all!(a, [1, 2]).unwrap_err().to_string();
+        assert!(
+            failure.contains("lengths differ: a has 5 elements, [1, 2] has 2"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_valid_utf8() {
+        let a = "héllo".as_bytes().to_vec();
+        assert!(test_valid_utf8!(a).is_ok());
+        assert!(test_valid_utf8!(b"plain ascii").is_ok());
+        let b = [b'a', b'b', 0xc3, b'c'];
+        let failure = test_valid_utf8!(b).unwrap_err().to_string();
+        assert!(failure.contains("b is not valid UTF-8"), "{failure}");
+        assert!(
+            failure.contains("invalid sequence at offset 2: [c3]"),
+            "{failure}"
+        );
+        let failure = test_valid_utf8!(&a[..2], "a is {}", "cut")
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains("UTF-8: a is cut"), "{failure}");
+        assert!(failure.contains("offset 1"), "{failure}");
+    }
+
+    #[test]
+    pub fn test_test_eq_ignore_zst() {
+        use std::marker::PhantomData;
+
+        #[derive(Debug, PartialEq)]
+        struct Wrapper<T> {
+            value: u32,
+            _marker: PhantomData<T>,
+        }
+
+        #[derive(Debug, PartialEq)]
+        struct Marker<T>(PhantomData<T>);
+
+        #[derive(Debug, PartialEq)]
+        struct Named<T> {
+            name: &'static str,
+            quote: char,
+            _marker: PhantomData<T>,
+        }
+
+        let a = Wrapper::<fn() -> Vec<u8>> {
+            value: 1,
+            _marker: PhantomData,
+        };
+        let b = Wrapper {
+            value: 2,
+            _marker: PhantomData,
+        };
+        assert!(test_eq!(a, a).is_ok());
+        assert!(test_eq!(a, b).is_err());
+        assert!(test_eq_ignore_zst!(a, a).is_ok());
+        let failure = test_eq_ignore_zst!(a, b, "a is {}", a.value)
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains("a != b: a is 1"), "{failure}");
+        assert!(
+            failure.contains("a: Wrapper { value: 1 }\nb: Wrapper { value: 2 }"),
+            "{failure}"
+        );
+        let failure = test_eq_ignore_zst!(Some(Marker::<u8>(PhantomData)), None)
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains(": Some(Marker)\nNone: None"), "{failure}");
+
+        let c = Named::<Vec<Option<u8>>> {
+            name: "PhantomData<u8>, \"PhantomPinned",
+            quote: '"',
+            _marker: PhantomData,
+        };
+        let d = Named {
+            name: "PhantomData<u8>",
+            quote: '"',
+            _marker: PhantomData,
+        };
+        let failure = test_eq_ignore_zst!(c, d).unwrap_err().to_string();
+        assert!(
+            failure.contains(
+                "c: Named { name: \"PhantomData<u8>, \\\"PhantomPinned\", quote: '\"' }\n\
+                 d: Named { name: \"PhantomData<u8>\", quote: '\"' }"
+            ),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_power_of_two() {
+        let x = 0_u8;
+        let failure = test_power_of_two!(x).unwrap_err().to_string();
+        assert!(
+            failure.contains("Test failed: x is not a power of two"),
+            "{failure}"
+        );
+        assert!(failure.contains("x: 0 (0b0)"), "{failure}");
+        assert!(test_power_of_two!(1_u64).is_ok());
+        assert!(test_power_of_two!(64_usize).is_ok());
+        let failure = test_power_of_two!(65_u32, "alignment of {}", "buffer")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("65_u32 is not a power of two: alignment of buffer"),
+            "{failure}"
+        );
+        assert!(failure.contains("65_u32: 65 (0b1000001)"), "{failure}");
+    }
+
+    #[test]
+    pub fn test_test_io_err_kind() {
+        use std::io::{Error, ErrorKind};
+        let a: std::io::Result<u8> = Ok(5);
+        let failure = test_io_err_kind!(a, ErrorKind::NotFound)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("a is not an error of kind ErrorKind::NotFound"),
+            "{failure}"
+        );
+        assert!(failure.contains("a was Ok\na: Ok(5)"), "{failure}");
+        let b: std::io::Result<u8> = Err(Error::new(ErrorKind::PermissionDenied, "read-only"));
+        let failure = test_io_err_kind!(b, ErrorKind::NotFound, "opening {}", "file")
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains("NotFound: opening file"), "{failure}");
+        assert!(
+            failure.contains(
+                "expected kind: NotFound\nactual kind: PermissionDenied\nerror: read-only"
+            ),
+            "{failure}"
+        );
+        assert!(test_io_err_kind!(b, ErrorKind::PermissionDenied).is_ok());
+    }
+
+    #[test]
+    pub fn test_test_unchanged() {
+        let mut a = vec![3, 1, 2];
+        assert!(test_unchanged!(a, { a.iter().sum::<i32>() }).is_ok());
+        assert!(test_unchanged!(a, {
+            a.push(4);
+            a.pop();
+        })
+        .is_ok());
+        let failure = test_unchanged!(a, { a.sort_unstable() }, "sorting {} items", 3)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("a was changed: sorting 3 items"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("before: [3, 1, 2]\nafter: [1, 2, 3]"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_lines_eq() {
+        let a = vec!["one".to_string(), "two".to_string(), "three".to_string()];
+        assert!(test_lines_eq!(a, "one\ntwo\nthree").is_ok());
+        let failure = test_lines_eq!(a, "one\n2\nthree", "a is {:?}", a)
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains(": a is [\"one\""), "{failure}");
+        assert!(
+            failure.contains("first difference at line 1\na: \"two\"\n\"one\\n2\\nthree\": \"2\""),
+            "{failure}"
+        );
+        let expected = "one\ntwo";
+        let failure = test_lines_eq!(a, expected).unwrap_err().to_string();
+        assert!(
+            failure.contains(
+                "line counts differ: a has 3 lines, expected has 2\nfirst extra line: \"three\""
+            ),
+            "{failure}"
+        );
+        let failure = test_lines_eq!(a, "one\ntwo\nthree\n")
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains("first missing line: \"\""), "{failure}");
+    }
+
+    #[test]
+    pub fn test_test_aligned() {
+        let mut a = [0_u64; 2];
+        assert!(test_aligned!(&a, 8).is_ok());
+        assert!(test_aligned!(a.as_ptr(), 8).is_ok());
+        assert!(test_aligned!(&mut a, 8).is_ok());
+        assert!(test_aligned!(a.as_mut_ptr(), 1).is_ok());
+        let b = a.as_ptr().cast::<u8>().wrapping_add(1);
+        let failure = test_aligned!(b, 8, "buffer of {} bytes", 16)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("b is not aligned to 8 bytes: buffer of 16 bytes"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains(&format!("b: {b:p}\nrequired alignment: 8")),
+            "{failure}"
+        );
+        let failure = test_aligned!(&a, 3).unwrap_err().to_string();
+        assert!(
+            failure.contains("alignment 3 is not a power of two"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_eq_str_repr() {
+        #[derive(Debug)]
+        enum Level {
+            Low,
+            High,
+        }
+
+        impl std::fmt::Display for Level {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    Self::Low => f.write_str("low"),
+                    Self::High => f.write_str("high"),
+                }
+            }
+        }
+
+        let a = Level::Low;
+        let b = Level::High;
+        assert!(test_eq_str_repr!(a, Level::Low).is_ok());
+        assert!(test_eq_str_repr!(b, "high").is_ok());
+        let failure = test_eq_str_repr!(a, b, "level {}", 1)
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains("a != b: level 1"), "{failure}");
+        assert!(failure.contains("a: \"low\"\nb: \"high\""), "{failure}");
+    }
+
+    #[test]
+    pub fn test_test_palindrome() {
+        let a = vec![1, 2, 3, 2, 1];
+        assert!(test_palindrome!(a).is_ok());
+        assert!(test_palindrome!("été").is_ok());
+        assert!(test_palindrome!(String::from("abba")).is_ok());
+        assert!(test_palindrome!([0_u8; 0]).is_ok());
+        assert!(test_palindrome!(&a[..1]).is_ok());
+        assert!(test_palindrome!("").is_ok());
+        let b = [1, 2, 3, 4, 1];
+        let failure = test_palindrome!(b, "b has {} items", b.len())
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("b is not a palindrome: b has 5 items"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("first asymmetry at indices 1 and 3: 2 != 4"),
+            "{failure}"
+        );
+        let c = "héllo";
+        let failure = test_palindrome!(c).unwrap_err().to_string();
+        assert!(
+            failure.contains("first asymmetry at indices 0 and 4: 'h' != 'o'"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_values_eq_unordered() {
+        use std::collections::{BTreeMap, HashMap};
+        let a = HashMap::from([("a", 1), ("b", 2), ("c", 2)]);
+        let b = HashMap::from([("x", 2), ("y", 1), ("z", 2)]);
+        assert!(test_values_eq_unordered!(a, b).is_ok());
+        let c = BTreeMap::from([(1, "one"), (2, "two"), (3, "two")]);
+        let d = BTreeMap::from([(1, "two"), (2, "one"), (3, "three")]);
+        let failure = test_values_eq_unordered!(c, d, "c has {} entries", c.len())
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("c.values() != d.values() (unordered): c has 3 entries"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("only in c: [\"two\"]\nonly in d: [\"three\"]"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_charset() {
+        let hex = "abcdef0123456789";
+        assert!(test_charset!("deadbeef42", hex).is_ok());
+        assert!(test_charset!(String::new(), hex).is_ok());
+        let a = String::from("é0fg");
+        let failure = test_charset!(a, "é0123456789abcdef", "parsing {}", "hex")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("a contains characters outside of \"é0123456789abcdef\": parsing hex"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("disallowed character 'g' at position 3 (byte offset 4)\na: \"é0fg\""),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_iter_eq() {
+        let a = vec![1, 2, 3, 4, 5, 6];
+        assert!(test_iter_eq!(a.iter(), [1, 2, 3, 4, 5, 6].iter()).is_ok());
+        assert!(test_iter_eq!(a.clone(), 1..=6).is_ok());
+        let b = [1, 2, 3, 10, 4, 5, 6];
+        let failure = test_iter_eq!(a.iter(), b.iter()).unwrap_err().to_string();
+        assert!(
+            failure.contains("first difference at index 3: 4 != 10\na.iter()[1..6]: [2, 3, 4, 5, 6]\nb.iter()[1..6]: [2, 3, 10, 4, 5]"),
+            "{failure}"
+        );
+        let failure = test_iter_eq!(a.iter(), b.iter(), context = 0, "a has {} items", a.len())
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains(": a has 6 items"), "{failure}");
+        assert!(
+            failure.contains("a.iter()[3..4]: [4]\nb.iter()[3..4]: [10]"),
+            "{failure}"
+        );
+        let failure = test_iter_eq!(a.iter(), a.iter().take(5))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("first difference at index 5: 6 != <end>\na.iter()[3..6]: [4, 5, 6]\na.iter().take(5)[3..5]: [4, 5]"),
+            "{failure}"
+        );
+        assert!(test_iter_eq!(a, 1.., "endless").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "serde_json")]
+    pub fn test_test_json_str_eq() {
+        let a = r#"{"id": 1, "tags": ["a", "b"], "owner": {"name": "alice"}}"#;
+        let b =
+            "{\n  \"owner\": { \"name\": \"alice\" },\n  \"tags\": [\"a\", \"b\"],\n  \"id\": 1\n}";
+        assert!(test_json_str_eq!(a, b).is_ok());
+        let c = r#"{"id": 1, "tags": ["a", "b"], "owner": {"name": "bob"}}"#;
+        let failure = test_json_str_eq!(a, c, "id {}", 1).unwrap_err().to_string();
+        assert!(failure.contains("a != c: id 1"), "{failure}");
+        assert!(
+            failure.contains("first difference at $.owner.name\na: \"alice\"\nc: \"bob\""),
+            "{failure}"
+        );
+        let d = String::from(
+            r#"{"id": 1, "tags": ["a", "b"], "owner": {"name": "alice"}, "extra": null}"#,
+        );
+        let failure = test_json_str_eq!(a, d).unwrap_err().to_string();
+        assert!(
+            failure.contains("first difference at $.extra\na: <missing>\nd: null"),
+            "{failure}"
+        );
+        let failure = test_json_str_eq!(a, "{\"id\": }").unwrap_err().to_string();
+        assert!(
+            failure.contains("\"{\\\"id\\\": }\" is not valid JSON: expected value at line 1"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_eq_via() {
+        trait Keyed {
+            fn key(&self) -> String;
+        }
+
+        struct User(&'static str);
+
+        impl Keyed for User {
+            fn key(&self) -> String {
+                format!("user:{}", self.0)
+            }
+        }
+
+        struct Group(&'static str);
+
+        impl Keyed for Group {
+            fn key(&self) -> String {
+                format!("group:{}", self.0)
+            }
+        }
+
+        let a: Box<dyn Keyed> = Box::new(User("alice"));
+        let b: Box<dyn Keyed> = Box::new(Group("alice"));
+        let c: Box<dyn Keyed> = Box::new(Group("bob"));
+        let failure = test_eq_via!(a, b, |x| x.key()).unwrap_err().to_string();
+        assert!(
+            failure.contains("a: \"user:alice\"\nb: \"group:alice\""),
+            "{failure}"
+        );
+        // Compare the names, without the kind of the key
+        let name = |key: String| key.split_once(':').map(|(_, name)| name.to_owned());
+        assert!(test_eq_via!(a, b, |x| name(x.key())).is_ok());
+        let failure = test_eq_via!(a, c, |x| name(x.key()), "comparing {}", "names")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("a != c (via |x| name(x.key())): comparing names"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("a: Some(\"alice\")\nc: Some(\"bob\")"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_deterministic() {
+        let a = [3, 1, 2];
+        assert!(test_deterministic!(5, || a.iter().max().copied()).is_ok());
+        assert!(test_deterministic!(0, || unreachable!("never called")).is_ok());
+        let mut state = 0_u32;
+        let failure = test_deterministic!(
+            5,
+            || {
+                state += 1;
+                state / 3
+            },
+            "state is {}",
+            "shared"
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(
+            failure.contains("is not deterministic over 5 runs: state is shared"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("run 2 differs from run 0\nrun 0: 0\nrun 2: 1"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_eq_ws_collapsed() {
+        assert!(test_eq_ws_collapsed!("a   b", "a b").is_ok());
+        assert!(test_eq_ws_collapsed!(String::from("\t a\n\nb "), "a b").is_ok());
+        let a = "a b";
+        let failure = test_eq_ws_collapsed!(a, "a c", "line {}", 2)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("a != \"a c\" (whitespace collapsed): line 2"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("a: \"a b\"\n\"a c\": \"a c\""),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_dir_eq() {
+        let root = std::env::temp_dir().join(format!("test_eq_dir_eq_{}", std::process::id()));
+        let a = root.join("a");
+        let b = root.join("b");
+        for dir in [&a, &b] {
+            std::fs::create_dir_all(dir.join("nested")).unwrap();
+            std::fs::write(dir.join("top.txt"), "top").unwrap();
+            std::fs::write(dir.join("nested").join("inner.txt"), dir.to_str().unwrap()).unwrap();
+        }
+        std::fs::create_dir_all(b.join("empty")).unwrap();
+        let same = test_dir_eq!(a, b);
+        std::fs::write(a.join("only_a.txt"), "a").unwrap();
+        std::fs::write(b.join("nested").join("only_b.txt"), "b").unwrap();
+        let different = test_dir_eq!(a, b, "in {}", "temp");
+        let missing = test_dir_eq!(a, root.join("missing"));
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert!(same.is_ok(), "{same:?}");
+        let failure = different.unwrap_err().to_string();
+        assert!(failure.contains("a != b: in temp"), "{failure}");
+        assert!(failure.contains("only in a: [\"only_a.txt\"]"), "{failure}");
+        assert!(failure.contains("only in b: [\"nested"), "{failure}");
+        assert!(failure.contains("only_b.txt\"]"), "{failure}");
+        let failure = missing.unwrap_err().to_string();
+        assert!(
+            failure.contains("could not list root.join(\"missing\")"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_vec_norm_close() {
+        let a = vec![1.0_f32, 2.0, 3.0];
+        let b = vec![1.0_f32, 2.0, 3.25];
+        assert!(test_vec_norm_close!(a, b, 0.25).is_ok());
+        assert!(test_vec_norm_close!([0.0, 3.0], [4.0, 0.0], 5.0).is_ok());
+        let failure = test_vec_norm_close!([0.0, 3.0], [4.0, 0.0], 4.5, "step {}", 1)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("[0.0, 3.0] is not close to [4.0, 0.0]: step 1"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("norm of difference: 5 (allowed: 4.5)\n[0.0, 3.0]: [0.0, 3.0]\n[4.0, 0.0]: [4.0, 0.0]"),
+            "{failure}"
+        );
+        let failure = test_vec_norm_close!(a, b[..2], 1.0)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("lengths differ: a has 3 elements, b[..2] has 2"),
+            "{failure}"
+        );
+        assert!(test_vec_norm_close!([f64::NAN], [0.0], 1.0).is_err());
+    }
+
+    #[test]
+    pub fn test_test_cmp_consistent() {
+        use std::cmp::Ordering;
+
+        /// Orders by length with `cmp`, but alphabetically with `partial_cmp`.
+        #[derive(Debug, PartialEq, Eq)]
+        struct Word<T>(T);
+
+        impl<T: AsRef<str> + Ord> Ord for Word<T> {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.0.as_ref().len().cmp(&other.0.as_ref().len())
+            }
+        }
+
+        impl<T: PartialOrd> PartialOrd for Word<T> {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                self.0.partial_cmp(&other.0)
+            }
+        }
+
+        assert!(test_cmp_consistent!(1, 2).is_ok());
+        assert!(test_cmp_consistent!("b", "a").is_ok());
+        let a = Word("apple");
+        let b = Word("fig");
+        assert!(test_cmp_consistent!(a, a).is_ok());
+        let failure = test_cmp_consistent!(a, b, "comparing {}", "words")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("a.partial_cmp(&b) is inconsistent with a.cmp(&b): comparing words"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("partial_cmp returned Some(Less), but cmp returned Greater\na: Word(\"apple\")\nb: Word(\"fig\")"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_parse_eq() {
+        assert!(test_parse_eq!("2.5", 2.5_f64).is_ok());
+        assert!(test_parse_eq!(String::from("42"), 42_u8).is_ok());
+        let a = "41";
+        let failure = test_parse_eq!(a, 42_u8, "parsing {}", "bytes")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("a.parse() != 42_u8: parsing bytes"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("parsed: 41\n42_u8: 42\ninput: \"41\""),
+            "{failure}"
+        );
+        let failure = test_parse_eq!("256", 0_u8).unwrap_err().to_string();
+        assert!(
+            failure.contains(
+                "could not parse \"256\" as u8\nerror: ParseIntError { kind: PosOverflow }"
+            ),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_exhausted() {
+        let a = [String::from("x"), String::from("y")];
+        let mut iter = a.iter();
+        assert_eq!(iter.next(), Some(&String::from("x")), "first item");
+        let failure = test_exhausted!(&mut iter, "after {} item", 1)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("&mut iter is not exhausted: after 1 item\nnext item: \"y\""),
+            "{failure}"
+        );
+        assert!(test_exhausted!(iter).is_ok());
+        let mut empty = std::iter::empty::<u8>();
+        assert!(test_exhausted!(empty).is_ok());
+    }
+
+    #[test]
+    pub fn test_test_eq_cow() {
+        use std::borrow::Cow;
+
+        fn borrowed(s: &str) -> Cow<'_, str> {
+            Cow::Borrowed(s)
+        }
+
+        fn owned(s: &str) -> Cow<'static, str> {
+            Cow::Owned(s.to_uppercase())
+        }
+
+        assert!(test_eq!(borrowed("ABC"), owned("abc")).is_ok());
+        assert!(test_eq!(owned("abc"), "ABC").is_ok());
+        let failure = test_eq!(borrowed("abc"), owned("abc"), "case {}", "sensitive")
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains(": case sensitive"), "{failure}");
+        #[cfg(not(feature = "pretty_assertions"))]
+        assert!(
+            failure.contains("borrowed(\"abc\"): \"abc\"\nowned(\"abc\"): \"ABC\""),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_all_equal() {
+        let mut a = vec![0_u8; 16];
+        assert!(test_all_equal!(a, 0).is_ok());
+        assert!(test_all_equal!(Vec::<u8>::new(), 1).is_ok());
+        a[9] = 7;
+        let failure = test_all_equal!(a, 0, "buffer of {} bytes", a.len())
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("a is not all equal to 0: buffer of 16 bytes"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("first difference at index 9\na[9]: 7\n0: 0"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_eq_rounded() {
+        // Written as a division, as clippy sees 3.14159 as an approximation of PI
+        let a = 314_159.0_f64 / 100_000.0;
+        let b = 3.14160_f64;
+        assert!(test_eq_rounded!(a, b, 3).is_ok());
+        assert!(test_eq_rounded!(-0.0001_f32, 0.0001_f32, 3).is_ok());
+        let failure = test_eq_rounded!(a, b, 5, "price in {}", "euro")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("a != b (rounded to 5 decimals): price in euro"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("a: 3.14159 (rounded: 3.14159)\nb: 3.1416 (rounded: 3.14160)"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_increased() {
+        let mut counter = 0_u32;
+        assert!(test_increased!(counter, { counter += 1 }).is_ok());
+        let failure = test_increased!(counter, {}, "counter is {}", counter)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("counter did not increase: counter is 1\nbefore: 1\nafter: 1"),
+            "{failure}"
+        );
+        let failure = test_increased!(counter, { counter -= 1 })
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains("before: 1\nafter: 0"), "{failure}");
+        assert!(test_decreased!(counter, { counter += 1 }).is_err());
+        assert!(test_decreased!(counter, { counter -= 1 }).is_ok());
+        let failure = test_decreased!(counter, { counter *= 1 })
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains("counter did not decrease"), "{failure}");
+    }
+
+    #[test]
+    pub fn test_test_same_variant() {
+        use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+        let a = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let b = IpAddr::V4(Ipv4Addr::BROADCAST);
+        assert!(test_same_variant!(a, b).is_ok());
+        assert!(test_same_variant!(None::<u8>, None).is_ok());
+        let c = IpAddr::V6(Ipv6Addr::LOCALHOST);
+        let failure = test_same_variant!(a, c, "address {}", 2)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("a and c are different variants: address 2"),
+            "{failure}"
+        );
+        assert!(failure.contains("a: 127.0.0.1\nc: ::1"), "{failure}");
+    }
+
+    #[test]
+    #[cfg(feature = "constant-time")]
+    pub fn test_test_ct_eq() {
+        let a = vec![0xab_u8; 16];
+        assert!(test_ct_eq!(a, [0xab_u8; 16]).is_ok());
+        let mut b = a.clone();
+        b[15] = 0xcd;
+        let failure = test_ct_eq!(a, b, "mac {}", 1).unwrap_err().to_string();
+        assert!(failure.contains("a != b: mac 1"), "{failure}");
+        assert!(
+            failure
+                .contains("the contents differ, the bytes are not shown\na: 16 bytes\nb: 16 bytes"),
+            "{failure}"
+        );
+        // Skip the first line, its line info could contain a secret by accident
+        let (_, details) = failure.split_once('\n').unwrap_or_default();
+        for secret in ["171", "ab", "205", "cd"] {
+            assert!(!details.contains(secret), "{failure}");
+        }
+        let failure = test_ct_eq!(a, b[..8]).unwrap_err().to_string();
+        assert!(failure.contains("the lengths differ"), "{failure}");
+    }
+
+    #[test]
+    pub fn test_test_ok_satisfies() {
+        let ok: Result<i32, String> = Ok(5);
+        assert!(test_ok_satisfies!(ok, |v| *v > 0).is_ok());
+        let negative: Result<i32, String> = Ok(-3);
+        let failure = test_ok_satisfies!(negative, |v| *v > 0, "for input {}", 2)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("negative does not satisfy |v| *v > 0: for input 2"),
+            "{failure}"
+        );
+        assert!(failure.contains("value: -3"), "{failure}");
+        let err: Result<i32, String> = Err("invalid digit".to_owned());
+        let failure = test_ok_satisfies!(err, |v| *v > 0).unwrap_err().to_string();
+        assert!(failure.contains("err is not Ok"), "{failure}");
+        assert!(failure.contains("error: \"invalid digit\""), "{failure}");
+        assert!(!failure.contains("does not satisfy"), "{failure}");
+    }
+
+    #[test]
+    pub fn test_test_image_close() {
+        let a = [10_u8, 20, 30, 40, 250];
+        assert!(test_image_close!(a, a, 0).is_ok());
+        let b = [12_u8, 18, 30, 43, 255];
+        assert!(test_image_close!(a, b, 5).is_ok());
+        let c = [12_u8, 18, 30, 46, 255];
+        let failure = test_image_close!(a, c, 5, "frame {}", 3)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("a is not close to c (tolerance 5): frame 3"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains(
+                "1 of 5 pixels differ by more than 5, the first at index 3\na: 40\nc: 46"
+            ),
+            "{failure}"
+        );
+        let failure = test_image_close!(a, c[..4], 5).unwrap_err().to_string();
+        assert!(failure.contains("lengths differ"), "{failure}");
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    pub fn test_test_serialized_len_eq() {
+        #[derive(serde::Serialize)]
+        struct Point {
+            x: u32,
+            y: u32,
+        }
+        #[derive(serde::Serialize)]
+        struct Pixel {
+            r: u8,
+            g: u8,
+        }
+        let point = Point { x: 10, y: 20 };
+        let pixel = Pixel { r: 30, g: 40 };
+        assert!(test_serialized_len_eq!(point, pixel).is_ok());
+        let far = Point { x: 1000, y: 2000 };
+        let failure = test_serialized_len_eq!(point, far, "at zoom {}", 3)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("point and far have a different serialized length: at zoom 3"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("point: 15 bytes\nfar: 19 bytes"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "alloc-counting")]
+    pub fn test_test_no_alloc() {
+        let a = [3_u8, 1, 2];
+        assert!(test_no_alloc!({ a.iter().copied().max() }).is_ok());
+        let failure = test_no_alloc!(
+            {
+                let mut v = Vec::new();
+                for i in 0..4_u8 {
+                    v.push(std::hint::black_box(i));
+                }
+                std::hint::black_box(v)
+            },
+            "in round {}",
+            1
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(failure.contains("allocated: in round 1"), "{failure}");
+        assert!(failure.contains("allocations: "), "{failure}");
+        assert!(!failure.contains("allocations: 0"), "{failure}");
+    }
+
+    #[test]
+    pub fn test_test_one_of_suggest() {
+        let fruits = ["apple", "banana", "cherry"];
+        assert!(test_one_of_suggest!("cherry", fruits).is_ok());
+        let owned = vec![String::from("apple"), String::from("banana")];
+        assert!(test_one_of_suggest!(String::from("apple"), owned).is_ok());
+        let fruit = "bananna";
+        let failure = test_one_of_suggest!(fruit, fruits, "in basket {}", 2)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("fruit is not one of fruits: in basket 2"),
+            "{failure}"
+        );
+        assert!(failure.contains("fruit: \"bananna\""), "{failure}");
+        assert!(failure.contains("did you mean \"banana\"?"), "{failure}");
+        let failure = test_one_of_suggest!("chery", fruits)
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains("did you mean \"cherry\"?"), "{failure}");
+    }
+
+    #[test]
+    pub fn test_test_same_magnitude() {
+        use std::time::Duration;
+        let fast = Duration::from_millis(30);
+        let slow = Duration::from_millis(200);
+        assert!(test_same_magnitude!(fast, slow).is_ok());
+        assert!(test_same_magnitude!(slow, fast).is_ok());
+        let limit = Duration::from_millis(300);
+        assert!(test_same_magnitude!(fast, limit).is_ok());
+        assert!(test_same_magnitude!(fast, slow, factor = 5).is_err());
+        let failure = test_same_magnitude!(fast, Duration::from_millis(301), "run {}", 4)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains(
+                "fast and Duration::from_millis(301) do not have the same order of magnitude: run 4"
+            ),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("ratio: 10.03x (maximum: 10x)\nfast: 30ms"),
+            "{failure}"
+        );
+        let failure = test_same_magnitude!(Duration::ZERO, fast, factor = 2)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("ratio: infinite (maximum: 2x)"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_edges_eq_unordered() {
+        let triangle = [(1, 2), (2, 3), (3, 1)];
+        let reordered = [(3, 1), (1, 2), (2, 3), (1, 2)];
+        assert!(test_edges_eq_unordered!(triangle, reordered).is_ok());
+        let reversed = [(2, 1), (3, 2), (1, 3)];
+        assert!(test_edges_eq_unordered!(triangle, reversed, directed = false).is_ok());
+        let failure = test_edges_eq_unordered!(triangle, reversed, "graph {}", 1)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("triangle != reversed (unordered edges): graph 1"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("only in triangle: [(1, 2), (2, 3), (3, 1)]"),
+            "{failure}"
+        );
+        let missing = [(1, 2), (2, 3)];
+        let failure = test_edges_eq_unordered!(triangle, missing, directed = false)
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains("only in triangle: [(1, 3)]"), "{failure}");
+        assert!(!failure.contains("only in missing"), "{failure}");
+    }
+
+    #[test]
+    pub fn test_test_near_int() {
+        let a = -3_i32;
+        assert!(test_near_int!(a, -3, 1).is_ok());
+        assert!(test_near_int!(a, -2, 1).is_ok());
+        assert!(test_near_int!(a, -4, 1).is_ok());
+        let failure = test_near_int!(a, -1, 1, "frame {}", 9)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("a is not within 1 of -1: frame 9"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("difference: 2 (maximum: 1)\na: -3\n-1: -1"),
+            "{failure}"
+        );
+        let small = 1_u8;
+        assert!(test_near_int!(small, 2, 1).is_ok());
+        let failure = test_near_int!(small, 255, 1).unwrap_err().to_string();
+        assert!(failure.contains("difference: 254"), "{failure}");
+        assert!(test_near_int!(i64::MIN, i64::MAX, u64::MAX).is_ok());
+    }
+
+    #[test]
+    pub fn test_test_fields_match() {
+        let range = 3..7;
+        assert!(test_fields_match!(range, { start: 3, end: 7 }).is_ok());
+        assert!(test_fields_match!(range, { end: 7 }).is_ok());
+        let failure = test_fields_match!(range, { start: 3, end: 8 }, "for request {}", 7)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("One of the tests failed: for request 7"),
+            "{failure}"
+        );
+        assert!(failure.contains("range.end != 8"), "{failure}");
+        assert!(!failure.contains("range.start"), "{failure}");
+        let failure = test_fields_match!(range, { start: 4, end: 8 })
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains("2 tests failed:"), "{failure}");
+        assert!(failure.contains("1: "), "{failure}");
+        assert!(failure.contains("range.start != 4"), "{failure}");
+        assert!(failure.contains("2: "), "{failure}");
+        assert!(failure.contains("range.end != 8"), "{failure}");
+    }
+
+    #[test]
+    pub fn test_test_order_preserved() {
+        let events = vec![5, 3, 8, 1, 9];
+        let mapped = |events: &Vec<i32>| events.iter().map(|e| e + 1).collect::<Vec<_>>();
+        let streamed = |events: &Vec<i32>| {
+            let mut out = Vec::new();
+            for chunk in events.chunks(2) {
+                out.extend(chunk.iter().map(|e| e + 1));
+            }
+            out
+        };
+        assert!(test_order_preserved!(mapped, streamed, events).is_ok());
+        let reordered = |events: &Vec<i32>| {
+            let mut out = mapped(events);
+            out.swap(2, 3);
+            out
+        };
+        let failure = test_order_preserved!(mapped, reordered, events, "batch {}", 2)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("mapped and reordered have a different order for events: batch 2"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("first difference at index 2: 9 != 2"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_bits_eq_diff() {
+        let a = [true, false, true, true, false];
+        assert!(test_bits_eq_diff!(a, [true, false, true, true, false]).is_ok());
+        let b = [true, false, false, true, false];
+        let failure = test_bits_eq_diff!(a, b, "mask {}", 1)
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains("a != b: mask 1"), "{failure}");
+        assert!(
+            failure.contains(
+                "first differing bit: 2\n1 differing bit (a != b):\n  [2]: true != false"
+            ),
+            "{failure}"
+        );
+        let failure = test_bits_eq_diff!(a, b[..4]).unwrap_err().to_string();
+        assert!(
+            failure.contains("lengths differ: a has 5 bits, b[..4] has 4"),
+            "{failure}"
+        );
+        let c = [0b1000_0001_u8, 0b1111_0001];
+        let d = [0b1000_0001_u8, 0b0000_0101];
+        assert!(test_bits_eq_diff!(c, d, bits = 10).is_ok());
+        let failure = test_bits_eq_diff!(c, d, bits = 11).unwrap_err().to_string();
+        assert!(failure.contains("first differing bit: 10"), "{failure}");
+        let failure = test_bits_eq_diff!(c, d[..1], bits = 11)
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains("lengths differ"), "{failure}");
+    }
+
+    #[test]
+    pub fn test_test_matches_reference() {
+        let popcount = |n: &u32| n.count_ones();
+        let naive = |n: &u32| (0..32).map(|bit| n >> bit & 1).sum::<u32>();
+        assert!(test_matches_reference!(0xf0f0_u32, popcount, naive).is_ok());
+        let words = ["a", "bb", "ccc"];
+        let fast_len = |words: &[&str; 3]| words.iter().map(|w| w.len()).max();
+        let slow_len = |words: &[&str; 3]| words.iter().map(|w| w.chars().count()).min();
+        let failure = test_matches_reference!(words, fast_len, slow_len, "case {}", 2)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("fast_len does not match slow_len: case 2"),
+            "{failure}"
+        );
+        assert!(
+            failure
+                .contains("words: [\"a\", \"bb\", \"ccc\"]\nfast_len: Some(3)\nslow_len: Some(1)"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_eq_after_fn() {
+        /// Parse the numbers of a version, ignoring leading zeroes.
+        fn canonical_version(version: &str) -> Vec<u32> {
+            version
+                .split('.')
+                .map(|part| part.parse().unwrap_or(u32::MAX))
+                .collect()
+        }
+        let a = "1.02.0";
+        let b = String::from("1.2.00");
+        assert!(test_eq_after_fn!(a, b, canonical_version).is_ok());
+        let c = "1.3.0";
+        let failure = test_eq_after_fn!(a, c, canonical_version, "crate {}", "foo")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("a != c (after canonical_version): crate foo"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains(
+                "a: \"1.02.0\"\nc: \"1.3.0\"\nnormalized a: [1, 2, 0]\nnormalized c: [1, 3, 0]"
+            ),
+            "{failure}"
+        );
+        assert!(test_eq_after_fn!("ABC", "abc", str::to_lowercase).is_ok());
+    }
+
+    #[test]
+    pub fn test_test_keys_eq() {
+        use std::collections::{BTreeMap, HashMap};
+
+        #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+        struct Id(u8);
+
+        #[derive(Debug, PartialEq, Eq, Hash)]
+        struct Name(&'static str);
+
+        let a = HashMap::from([(1, "one"), (2, "two"), (3, "three")]);
+        let b = BTreeMap::from([(3, "drie"), (1, "een"), (2, "twee")]);
+        assert!(test_keys_eq!(a, b).is_ok());
+        let c = BTreeMap::from([(1, "un"), (2, "deux"), (4, "quatre")]);
+        let failure = test_keys_eq!(b, c, "language {}", "fr")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("b.keys() != c.keys() (unordered): language fr"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("only in b: [3]\nonly in c: [4]"),
+            "{failure}"
+        );
+
+        // The keys of a `BTreeMap` only need `Ord`
+        let ids = BTreeMap::from([(Id(1), 'a'), (Id(2), 'b')]);
+        let fewer_ids = BTreeMap::from([(Id(1), 'a')]);
+        assert!(test_keys_eq!(ids, ids).is_ok());
+        let failure = test_keys_eq!(ids, fewer_ids).unwrap_err().to_string();
+        assert!(failure.contains("only in ids: [Id(2)]"), "{failure}");
+
+        // The keys of a `HashMap` only need `Hash` and `Eq`
+        let names = HashMap::from([(Name("bob"), 1), (Name("alice"), 2), (Name("carol"), 3)]);
+        let fewer_names = HashMap::from([(Name("carol"), 4)]);
+        assert!(test_keys_eq!(names, names).is_ok());
+        let failure = test_keys_eq!(fewer_names, names).unwrap_err().to_string();
+        // Sorted by the `Debug` output, not in the iteration order of the map
+        assert!(
+            failure.contains("only in names: [Name(\"alice\"), Name(\"bob\")]"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_eq_edit1() {
+        let a = [1, 2, 3, 4];
+        assert!(test_eq_edit1!(a, [1, 2, 3, 4]).is_ok());
+        assert!(test_eq_edit1!(a, [1, 2, 9, 3, 4]).is_ok());
+        assert!(test_eq_edit1!(a, [1, 2, 3, 4, 5]).is_ok());
+        assert!(test_eq_edit1!(a, [2, 3, 4]).is_ok());
+        let substituted = [1, 2, 9, 4];
+        let failure = test_eq_edit1!(a, substituted, "read {}", 3)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains(
+                "a and substituted differ by more than one insertion or deletion: read 3"
+            ),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("substitution at index 2, which is not an insertion or deletion"),
+            "{failure}"
+        );
+        let two_edits = [1, 9, 2, 3, 5];
+        let failure = test_eq_edit1!(a, two_edits).unwrap_err().to_string();
+        assert!(
+            failure.contains("more than one edit: after removing two_edits[1] = 9, two_edits[4] = 5 differs from a[3] = 4"),
+            "{failure}"
+        );
+        let failure = test_eq_edit1!(a, [1, 2]).unwrap_err().to_string();
+        assert!(
+            failure.contains("lengths differ by more than one"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_is_permutation_of() {
+        let shuffled = vec![4_usize, 0, 3, 1, 2];
+        assert!(test_is_permutation_of!(shuffled, 0..5).is_ok());
+        let duplicate = vec![4_usize, 0, 3, 3, 2];
+        let failure = test_is_permutation_of!(duplicate, 0..5, "seed {}", 7)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("duplicate is not a permutation of 0..5: seed 7"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("only in duplicate: [3]\nonly in 0..5: [1]"),
+            "{failure}"
+        );
+        let missing = vec![4_usize, 0, 1, 2];
+        let failure = test_is_permutation_of!(missing, 0..5)
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains("only in 0..5: [3]"), "{failure}");
+        assert!(!failure.contains("only in missing"), "{failure}");
+    }
+
+    #[test]
+    pub fn test_test_debug_diff() {
+        let a = std::ops::Range { start: 1, end: 10 };
+        assert!(test_debug_diff!(a, 1..10).is_ok());
+        let b = std::ops::Range { start: 1, end: 12 };
+        let failure = test_debug_diff!(a, b, "slice {}", 2)
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains("a != b (Debug): slice 2"), "{failure}");
+        let error = std::io::Error::new(std::io::ErrorKind::NotFound, "config");
+        let other = std::io::Error::new(std::io::ErrorKind::NotFound, "cache");
+        let failure = test_debug_diff!(error, other).unwrap_err().to_string();
+        assert!(
+            failure.ends_with(
+                "differing lines (-error, +other):\n-    error: \"config\",\n+    error: \"cache\","
+            ),
+            "{failure}"
+        );
+        assert!(!failure.contains("NotFound"), "{failure}");
+        // Too many lines to compare, only the first differing line is shown
+        let long: Vec<u32> = (0..1500).collect();
+        let mut changed = long.clone();
+        changed[0] = 7;
+        changed[1499] = 7;
+        let failure = test_debug_diff!(long, changed).unwrap_err().to_string();
+        assert!(
+            failure.ends_with(
+                "differing lines (-long, +changed):\n-    0,\n+    7,\n\
+                 (only the first differing line is shown, 1500 and 1500 lines are too many to compare)"
+            ),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_eventually() {
+        let mut attempts = 0;
+        let result = test_eventually!(5, || {
+            attempts += 1;
+            test_ge!(attempts, 3)
+        });
+        assert!(result.is_ok());
+        assert_eq!(
+            attempts, 3,
+            "The closure should stop being called once it passes"
+        );
+        let queue: Vec<u32> = Vec::new();
+        let failure = test_eventually!(4, || test_eq!(queue.len(), 1), "job {}", 2)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("did not pass within 4 attempts: job 2"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("all 4 attempts failed, the last with:\n   "),
+            "{failure}"
+        );
+        assert!(failure.contains("queue.len() != 1"), "{failure}");
+    }
+
+    #[test]
+    pub fn test_test_eq_labeled() {
+        let totals = [3, 4, 5];
+        assert!(test_eq_labeled!("expected" => 12, "sum" => totals.iter().sum::<i32>()).is_ok());
+        let failure = test_eq_labeled!(
+            "expected" => 13,
+            "sum" => totals.iter().sum::<i32>(),
+            "for {} totals",
+            totals.len()
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(
+            failure.contains("Test failed: expected != sum: for 3 totals"),
+            "{failure}"
+        );
+        #[cfg(not(feature = "pretty_assertions"))]
+        assert!(failure.contains("expected: 13\nsum: 12"), "{failure}");
+        assert!(!failure.contains("totals.iter()"), "{failure}");
+    }
+
+    #[test]
+    pub fn test_test_bytes_text_eq() {
+        let a = b"line one\nline two\n".to_vec();
+        assert!(test_bytes_text_eq!(a, b"line one\nline two\n").is_ok());
+        let b = b"line one\nline tw0\n".to_vec();
+        let failure = test_bytes_text_eq!(a, b, "frame {}", 5)
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains("a != b: frame 5"), "{failure}");
+        assert!(
+            failure.contains("compared as text\ndiffering lines (-a, +b):\n-line two\n+line tw0"),
+            "{failure}"
+        );
+        let c = [b'l', b'i', 0xff, b'e'];
+        let failure = test_bytes_text_eq!(a, c).unwrap_err().to_string();
+        assert!(
+            failure.contains("compared as bytes, c is not valid UTF-8"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("first difference at offset 0x2"),
+            "{failure}"
+        );
+        let failure = test_bytes_text_eq!(b"a\n", b"a").unwrap_err().to_string();
+        assert!(
+            failure.contains("compared as bytes, only the line endings differ"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("lengths differ: b\"a\\n\" has 2 bytes, b\"a\" has 1 bytes"),
+            "{failure}"
+        );
+        let d = b"line one\r\nline two\r\n".to_vec();
+        let failure = test_bytes_text_eq!(a, d).unwrap_err().to_string();
+        assert!(
+            failure.contains("compared as bytes, only the line endings differ"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("first difference at offset 0x8"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_display_eq() {
+        let address = std::net::Ipv4Addr::new(10, 0, 0, 1);
+        assert!(test_display_eq!(address, "10.0.0.1").is_ok());
+        assert!(test_display_eq!(address, String::from("10.0.0.1")).is_ok());
+        let failure = test_display_eq!(address, "10.0.0.2")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("address is not displayed as \"10.0.0.2\""),
+            "{failure}"
+        );
+        #[cfg(not(feature = "pretty_assertions"))]
+        assert!(failure.contains("address: \"10.0.0.1\""), "{failure}");
+        let failure = test_display_eq!(address, "10.0.0.2", "for host {}", 2)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("address is not displayed as \"10.0.0.2\": for host 2"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_build_eq() {
+        use std::collections::BTreeSet;
+        let parsed = "8080".parse::<u16>();
+        assert!(test_build_eq!(parsed.clone(), 8080).is_ok());
+        let built: BTreeSet<u8> = [3, 1, 2].into_iter().collect();
+        assert!(test_build_eq!(built.clone(), BTreeSet::from([1, 2, 3])).is_ok());
+        let failure = test_build_eq!(built, BTreeSet::from([1, 2]), "set {}", 1)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("built != BTreeSet::from([1, 2]): set 1"),
+            "{failure}"
+        );
+        let failure = test_build_eq!(parsed, 80).unwrap_err().to_string();
+        assert!(failure.contains("parsed != 80"), "{failure}");
+        let failure = test_build_eq!("80a".parse::<u16>(), 80, "port {}", "http")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("\"80a\".parse::<u16>() failed to build: port http"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("error: ParseIntError { kind: InvalidDigit }"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_stats_eq() {
+        let a = vec![5, -2, 7, 0];
+        let b = [0, 7, 5, -2];
+        assert!(test_stats_eq!(a, b).is_ok());
+        let empty: [u8; 0] = [];
+        assert!(test_stats_eq!(empty, empty).is_ok());
+        let c = [0, 7, 6, -2];
+        let failure = test_stats_eq!(a, c, "batch {}", 3).unwrap_err().to_string();
+        assert!(
+            failure.contains("a and c have different statistics: batch 3"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("sum differs (delta: -1)\n    a: 10\n    c: 11"),
+            "{failure}"
+        );
+        assert!(!failure.contains("min differs"), "{failure}");
+        assert!(!failure.contains("max differs"), "{failure}");
+        let failure = test_stats_eq!(empty, [1]).unwrap_err().to_string();
+        assert!(failure.contains("empty is empty, [1] is not"), "{failure}");
+        let failure = test_stats_eq!([200u8, 100], [44u8])
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("sum of [200u8, 100] overflows at index 1: 200 + 100"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "glob")]
+    pub fn test_test_glob_match() {
+        let a = String::from("src/lib.rs");
+        assert!(test_glob_match!(a, "src/*.rs").is_ok());
+        let b = "src/helpers/text/lines.rs";
+        assert!(test_glob_match!(b, "src/**/*.rs").is_ok());
+        assert!(test_glob_match!(a, "src/**/*.rs").is_ok());
+        let failure = test_glob_match!(b, "src/*.rs", "b is {}", "nested")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("b does not match \"src/*.rs\": b is nested"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("b: \"src/helpers/text/lines.rs\"\npattern: \"src/*.rs\""),
+            "{failure}"
+        );
+        let failure = test_glob_match!(a, "src/*.toml").unwrap_err().to_string();
+        assert!(failure.contains("a does not match"), "{failure}");
+        let failure = test_glob_match!(a, "src/[a").unwrap_err().to_string();
+        assert!(failure.contains("invalid glob"), "{failure}");
+    }
+
+    #[test]
+    pub fn test_test_opt_approx_eq() {
+        let none: Option<f64> = None;
+        assert!(test_opt_approx_eq!(none, None, 0.1).is_ok());
+        let a = Some(1.0_f32);
+        assert!(test_opt_approx_eq!(a, Some(1.05), 0.1).is_ok());
+        let failure = test_opt_approx_eq!(a, None, 0.1, "a is {:?}", a)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("a is not within 0.1 of None: a is Some(1.0)"),
+            "{failure}"
+        );
+        assert!(failure.contains("a: Some(1.0)\nNone: None"), "{failure}");
+        let b = Some(2.0);
+        let failure = test_opt_approx_eq!(Some(2.5), b, 0.1)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("difference: 0.5 (allowed: 0.1)\nSome(2.5): 2.5\nb: 2.0"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_weak_alive() {
+        use std::rc::Rc;
+        use std::sync::Arc;
+        let a = Rc::new(String::from("cached"));
+        let weak_a = Rc::downgrade(&a);
+        assert!(test_weak_alive!(weak_a).is_ok());
+        let failure = test_weak_dead!(weak_a).unwrap_err().to_string();
+        assert!(
+            failure.contains("Test failed: weak reference is alive"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("strong references to weak_a: 1"),
+            "{failure}"
+        );
+        drop(a);
+        let failure = test_weak_alive!(weak_a, "cache {}", 3)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("Test failed: weak reference is dead: cache 3"),
+            "{failure}"
+        );
+        assert!(test_weak_dead!(weak_a).is_ok());
+        let b = Arc::new(5);
+        let weak_b = Arc::downgrade(&b);
+        assert!(test_weak_alive!(weak_b).is_ok());
+        drop(b);
+        assert!(test_weak_alive!(weak_b).is_err());
+        assert!(test_weak_dead!(weak_b).is_ok());
+    }
+
+    #[test]
+    pub fn test_test_eq_clamped() {
+        let a = -40;
+        let b = -7;
+        assert!(test_eq_clamped!(a, b, 0, 100).is_ok());
+        assert!(test_eq_clamped!(a, -80, -50, 50).is_err());
+        assert!(test_eq_clamped!(1.5, 9.0, 0.0, 1.0).is_ok());
+        let failure = test_eq_clamped!(a, 120, 0, 100, "a is {}", a)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("a != 120 when clamped to 0..=100: a is -40"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("a: -40 (clamped: 0)\n120: 120 (clamped: 100)"),
+            "{failure}"
+        );
+        let failure = test_eq_clamped!(a, b, 10, 0).unwrap_err().to_string();
+        assert!(failure.contains("the range 10..=0 is empty"), "{failure}");
+    }
+
+    #[test]
+    pub fn test_test_permutation_invariant() {
+        let a = vec![4, 8, 15, 16, 23, 42];
+        assert!(
+            test_permutation_invariant!(|items: Vec<i32>| items.iter().sum::<i32>(), a).is_ok()
+        );
+        let max = |items: [u8; 3]| items.iter().max().copied();
+        assert!(test_permutation_invariant!(max, [2, 9, 4], seed = 3).is_ok());
+        let first = |items: Vec<i32>| items[0];
+        let failure = test_permutation_invariant!(first, a, "a has {} items", a.len())
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("first depends on the order of a: a has 6 items"),
+            "{failure}"
+        );
+        assert!(failure.contains("output for a: 4\n"), "{failure}");
+        assert!(
+            failure.contains("output for shuffled a (seed 24301): "),
+            "{failure}"
+        );
+        // Two items are always swapped, whatever the seed
+        for seed in 0..16 {
+            assert!(test_permutation_invariant!(first, vec![1, 2], seed = seed).is_err());
+        }
+    }
+
+    #[test]
+    pub fn test_test_eq_mapped() {
+        #[derive(Debug, PartialEq)]
+        enum Status {
+            Active,
+            Closed,
+        }
+
+        enum LegacyStatus {
+            Open,
+            Resolved,
+        }
+
+        enum TicketState {
+            InProgress,
+            Done,
+        }
+
+        trait Canonical {
+            fn canonical(&self) -> Status;
+        }
+
+        impl Canonical for LegacyStatus {
+            fn canonical(&self) -> Status {
+                match self {
+                    Self::Open => Status::Active,
+                    Self::Resolved => Status::Closed,
+                }
+            }
+        }
+
+        impl Canonical for TicketState {
+            fn canonical(&self) -> Status {
+                match self {
+                    Self::InProgress => Status::Active,
+                    Self::Done => Status::Closed,
+                }
+            }
+        }
+
+        let a = LegacyStatus::Resolved;
+        let b = TicketState::Done;
+        assert!(test_eq_mapped!(a, b, Canonical::canonical).is_ok());
+        assert!(
+            test_eq_mapped!(LegacyStatus::Open, TicketState::InProgress, |x| x
+                .canonical()
+                == Status::Active)
+            .is_ok()
+        );
+        let failure = test_eq_mapped!(
+            a,
+            TicketState::InProgress,
+            Canonical::canonical,
+            "ticket {}",
+            12
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(
+            failure.contains(
+                "a != TicketState::InProgress (mapped with Canonical::canonical): ticket 12"
+            ),
+            "{failure}"
+        );
+        #[cfg(not(feature = "pretty_assertions"))]
+        assert!(
+            failure.contains("a: Closed\nTicketState::InProgress: Active"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_prefix_sums_nonneg() {
+        let a = vec![2, -1, 3, -4, 0];
+        assert!(test_prefix_sums_nonneg!(a).is_ok());
+        let empty: [i64; 0] = [];
+        assert!(test_prefix_sums_nonneg!(empty).is_ok());
+        assert!(test_prefix_sums_nonneg!([0.5, -0.25, -0.25]).is_ok());
+        let b = [3_i8, -1, -5, 10];
+        let failure = test_prefix_sums_nonneg!(b, "stack of {}", "frames")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("the running sum of b is negative: stack of frames"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("running sum at index 2: -3\nb[2]: -5"),
+            "{failure}"
+        );
+        let c = [100_i8, 100];
+        let failure = test_prefix_sums_nonneg!(c).unwrap_err().to_string();
+        assert!(
+            failure.contains("running sum overflows at index 1: 100 + 100\nc[1]: 100"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_eq_ignore_trailing_default() {
+        let a = vec![1, 2, 0, 0];
+        let b = [1, 2];
+        assert!(test_eq_ignore_trailing_default!(a, b).is_ok());
+        assert!(test_eq_ignore_trailing_default!([0, 0], Vec::<u8>::new()).is_ok());
+        assert!(test_eq_ignore_trailing_default!(["x", "", ""], ["x"]).is_ok());
+        let c = [1, 2, 0];
+        let d = [1, 3];
+        let failure = test_eq_ignore_trailing_default!(c, d, "buffer {}", 4)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("c != d ignoring trailing defaults: buffer 4"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains(
+                "trimmed c: [1, 2]\ntrimmed d: [1, 3]\nfirst difference at index 1: 2 != 3"
+            ),
+            "{failure}"
+        );
+        // Zeros in the middle are not trimmed
+        let failure = test_eq_ignore_trailing_default!([1, 0, 2], [1, 2])
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("first difference at index 1: 0 != 2"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_eq_scaled() {
+        let meters = 1.0;
+        let millimeters = 1000.0;
+        assert!(test_eq_scaled!(meters, millimeters, 0.001).is_ok());
+        let grams = 2500;
+        assert!(test_eq_scaled!(grams, 2.5, 1000).is_ok());
+        assert!(test_eq_scaled!(meters, 1004.0, 0.001, tolerance = 0.005).is_ok());
+        let failure = test_eq_scaled!(meters, 1250.0, 0.001, "for rod {}", 2)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("meters != 1250.0 * 0.001: for rod 2"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("meters: 1.0\n1250.0: 1250.0\n1250.0 * 0.001: 1.25\ndifference: 0.25 (allowed: 1e-9)"),
+            "{failure}"
+        );
+        let failure = test_eq_scaled!(meters, 1010.0, 0.001, tolerance = 0.005)
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains("(allowed: 0.005)"), "{failure}");
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    pub fn test_test_regex_equiv() {
+        let a = r"^(ab)+$";
+        let b = r"^ab(ab)*$";
+        let samples = ["ab", "abab", "", "aba", "ba"];
+        assert!(test_regex_equiv!(a, b, samples).is_ok());
+        let samples = vec![String::from("ab"), String::new()];
+        let c = r"^(ab)*$";
+        let failure = test_regex_equiv!(a, c, samples, "for {}", "pairs")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("a and c do not match the same samples: for pairs"),
+            "{failure}"
+        );
+        assert!(
+            failure
+                .contains("sample 1: \"\"\na does not match: \"^(ab)+$\"\nc matches: \"^(ab)*$\""),
+            "{failure}"
+        );
+        let failure = test_regex_equiv!(a, "(ab", ["ab"]).unwrap_err().to_string();
+        assert!(failure.contains("invalid regex \"(ab\""), "{failure}");
+    }
+
+    #[test]
+    #[cfg(feature = "timing")]
+    pub fn test_test_eq_timing() {
+        let a = vec![1u8; 4096];
+        let mut b = a.clone();
+        b[4095] = 2;
+        let failure = test_eq!(a, b).unwrap_err().to_string();
+        assert!(failure.contains("\ncomparison took: "), "{failure}");
+        let failure = test_eq!(a.len(), 4, "for {}", "a").unwrap_err().to_string();
+        assert!(failure.contains("\ncomparison took: "), "{failure}");
+        assert!(test_eq!(a, a.clone()).is_ok());
+    }
+
+    #[test]
+    pub fn test_test_all_values_le() {
+        use std::collections::{BTreeMap, HashMap};
+        let a = HashMap::from([("db", 3), ("http", 8), ("queue", 5)]);
+        assert!(test_all_values_le!(a, 8).is_ok());
+        assert!(test_all_values_le!(HashMap::<u8, f64>::new(), 0.0).is_ok());
+        let b = HashMap::from([("db", 3), ("http", 12), ("queue", 5), ("cron", 9)]);
+        let failure = test_all_values_le!(b, 8, "{} connections", "open")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("a value of b exceeds 8: open connections"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("2 of 4 values exceed 8\nb[\"cron\"]: 9\nb[\"http\"]: 12"),
+            "{failure}"
+        );
+        let c = BTreeMap::from([(1, f64::NAN)]);
+        let failure = test_all_values_le!(c, 1.0).unwrap_err().to_string();
+        assert!(failure.contains("c[1]: NaN"), "{failure}");
+    }
+
+    #[test]
+    pub fn test_test_eq_structured() {
+        #[derive(Debug, Clone, PartialEq)]
+        struct Point {
+            x: i32,
+            y: i32,
+        }
+
+        let a = Point { x: 1, y: 2 };
+        assert!(test_eq_structured!(a, Point { x: 1, y: 2 }).is_ok());
+        let b = Point { x: 1, y: 5 };
+        let failure = test_eq_structured!(a, b, "after {} steps", 3).unwrap_err();
+        assert_eq!(
+            failure.diff(),
+            &Diff::ValueMismatch {
+                left: a.clone(),
+                right: b.clone()
+            },
+            "The diff holds clones of both values"
+        );
+        let message = failure.to_string();
+        assert!(message.contains("a != b: after 3 steps"), "{message}");
+        let plain = test_eq!(a, b, "after {} steps", 3).unwrap_err().to_string();
+        // Skip the line info and the duration of the comparison, which differ
+        let values = |message: &str| {
+            message
+                .lines()
+                .skip(1)
+                .filter(|line| !line.starts_with("comparison took"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        assert_eq!(
+            values(&message),
+            values(&plain),
+            "The values are shown like test_eq!"
+        );
+        let Diff::ValueMismatch { left, right } = failure.into_diff();
+        assert_eq!((left.y, right.y), (2, 5), "The values can be taken out");
+        let converted: TestFailure = test_eq_structured!(a.x, 4).unwrap_err().into();
+        assert!(converted.to_string().contains("a.x != 4"), "{converted}");
+    }
+
+    #[test]
+    pub fn test_test_eq_ulps_scaled() {
+        let values = [0.1_f64; 10];
+        let ops = 9;
+        let sum: f64 = values.iter().sum();
+        assert!(test_eq_ulps_scaled!(sum, 1.0, ops).is_ok());
+        assert!(test_eq_ulps_scaled!(0.0, -0.0, 0).is_ok());
+        let next = f64::from_bits(1.0_f64.to_bits() + 3);
+        assert!(test_eq_ulps_scaled!(next, 1.0, 3).is_ok());
+        let failure = test_eq_ulps_scaled!(next, 1.0, 2, "after {} steps", 2)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("next is not within 2 ULPs of 1.0: after 2 steps"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("distance: 3 ULPs (allowed: 2, one per operation)"),
+            "{failure}"
+        );
+        // The distance crosses zero
+        let tiny = f64::from_bits(1);
+        let failure = test_eq_ulps_scaled!(tiny, -tiny, 1)
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains("distance: 2 ULPs"), "{failure}");
+        let failure = test_eq_ulps_scaled!(f64::NAN, 1.0, 100)
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains("NaN has no distance"), "{failure}");
+    }
+
+    #[test]
+    #[cfg(feature = "diff")]
+    pub fn test_test_slice_eq_all_lcs() {
+        let a = [1, 2, 3, 4, 5, 6, 7, 8];
+        let b = [0, 1, 2, 3, 9, 5, 6, 7, 8];
+        let failure = test_slice_eq_all!(a, b).unwrap_err().to_string();
+        assert!(failure.contains("lengths differ"), "{failure}");
+        assert!(
+            failure.contains("\nlongest common subsequence: 7 elements"),
+            "{failure}"
+        );
+        let c = [8, 2, 3, 4, 5, 6, 7, 1];
+        let failure = test_slice_eq_all!(a, c).unwrap_err().to_string();
+        assert!(
+            failure.contains("\nlongest common subsequence: 6 elements"),
+            "{failure}"
+        );
+        let long = vec![0u8; 2000];
+        let failure = test_slice_eq_all!(long, vec![1u8; 1000])
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("longest common subsequence: too long to compute"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_binary_search() {
+        let a = vec!["apple", "cherry", "grape", "melon"];
+        assert!(test_binary_search!(a, "grape").is_ok());
+        let failure = test_binary_search!(a, "fig", "in {} fruits", a.len())
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("\"fig\" is not in a: in 4 fruits"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("insertion point: 2\na[1..3]: \"cherry\", \"grape\""),
+            "{failure}"
+        );
+        let failure = test_binary_search!(a, "aardvark").unwrap_err().to_string();
+        assert!(
+            failure.contains("insertion point: 0\na[0..1]: <start>, \"apple\""),
+            "{failure}"
+        );
+        let failure = test_binary_search!(a, "pear").unwrap_err().to_string();
+        assert!(
+            failure.contains("insertion point: 4\na[3..5]: \"melon\", <end, length 4>"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_eq_nan_wildcard() {
+        let a = vec![0.5, f64::NAN, 2.0, -1.0];
+        let b = [0.5, 7.0, f64::NAN, -1.0];
+        assert!(test_eq_nan_wildcard!(a, b).is_ok());
+        assert!(test_eq_nan_wildcard!([0.0], [-0.0]).is_ok());
+        let c = [f64::NAN, 7.0, 2.0, -1.5];
+        let failure = test_eq_nan_wildcard!(a, c, "sample {}", 3)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("a != c ignoring NaN: sample 3"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("first difference at index 3: -1.0 != -1.5"),
+            "{failure}"
+        );
+        let failure = test_eq_nan_wildcard!(a, [f64::NAN; 3])
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains("lengths differ"), "{failure}");
+    }
+
+    #[test]
+    pub fn test_test_cache_consistent() {
+        use std::collections::HashMap;
+        let greet = |name: &String| format!("Hello, {name}!");
+        let mut a = HashMap::new();
+        a.insert(String::from("Ann"), String::from("Hello, Ann!"));
+        a.insert(String::from("Bob"), String::from("Hi, Bob!"));
+        assert!(test_cache_consistent!(a, String::from("Ann"), greet).is_ok());
+        let bob = String::from("Bob");
+        let failure = test_cache_consistent!(a, bob, greet, "greeting {}", 2)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("a is not consistent for bob: greeting 2"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("bob: \"Bob\"\ncached: \"Hi, Bob!\"\ncomputed: \"Hello, Bob!\""),
+            "{failure}"
+        );
+        let cat = String::from("Cat");
+        let failure = test_cache_consistent!(a, cat, greet)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("the key is not cached\ncat: \"Cat\""),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde_json")]
+    pub fn test_test_json_eq_all() {
+        use serde_json::json;
+        let a = json!({
+            "items": [{"name": "pen", "price": 2}, {"name": "ink", "price": 5}],
+            "owner": {"id": 7, "tags/labels": ["x"]},
+        });
+        let b = json!({
+            "owner": {"tags/labels": ["x"], "id": 7},
+            "items": [{"price": 2, "name": "pen"}, {"name": "ink", "price": 5}],
+        });
+        assert!(test_json_eq_all!(a, b).is_ok());
+        let c = json!({
+            "items": [{"name": "pen", "price": 3}, {"name": "nib", "price": 5}],
+            "owner": {"id": 7, "tags/labels": ["x", "y"]},
+        });
+        let failure = test_json_eq_all!(a, c, "order {}", 12)
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains("a != c: order 12"), "{failure}");
+        assert!(failure.contains("3 differing paths (a != c):"), "{failure}");
+        assert!(failure.contains("  /items/0/price: 2 != 3"), "{failure}");
+        assert!(
+            failure.contains("  /items/1/name: \"ink\" != \"nib\""),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("  /owner/tags~1labels/1: <missing> != \"y\""),
+            "{failure}"
+        );
+        let failure = test_json_eq_all!(a, c, cap = 1).unwrap_err().to_string();
+        assert!(failure.contains("  ... and 2 more"), "{failure}");
+        let failure = test_json_eq_all!(json!(1), json!("1"))
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains("  <root>: 1 != \"1\""), "{failure}");
+    }
+
+    #[test]
+    pub fn test_test_none_equal() {
+        let a = ["read", "write", "exec"];
+        assert!(test_none_equal!(a, "delete").is_ok());
+        let empty: Vec<u8> = Vec::new();
+        assert!(test_none_equal!(empty, 0).is_ok());
+        let b = vec![Some(1), None, Some(3), None];
+        let failure = test_none_equal!(b, None, "in {} slots", b.len())
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("an element of b is equal to None: in 4 slots"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("first match at index 1\nb[1]: None"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "unicode")]
+    pub fn test_test_eq_nfc() {
+        let a = "\u{e9}";
+        let b = String::from("e\u{301}");
+        assert!(test_eq_nfc!(a, b).is_ok());
+        assert!(test_eq_nfc!(b, a).is_ok());
+        let c = "e";
+        let failure = test_eq_nfc!(b, c, "in {}", "french")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("b and c are not equal in NFC: in french"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("b code points: U+0065 U+0301\nb in NFC: U+00E9"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("c code points: U+0065\nc in NFC: U+0065"),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_output_len_eq() {
+        fn square(n: usize) -> usize {
+            n * n
+        }
+        let sizes = vec![0, 1, 2, 5, 10];
+        let grid = |n: usize| vec![0_u8; n * n];
+        assert!(test_output_len_eq!(sizes, grid, square).is_ok());
+        let truncated = |n: usize| vec![0_u8; (n * n).min(20)];
+        let failure = test_output_len_eq!(sizes, truncated, square, "for {}", "grids")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("the output length of truncated does not match square: for grids"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("first difference at size 5\ntruncated(5).len(): 20\nsquare(5): 25"),
+            "{failure}"
+        );
+        assert!(test_output_len_eq!([], truncated, square).is_ok());
+    }
+
+    #[test]
+    pub fn test_test_matches() {
+        #[derive(Debug)]
+        enum Shape {
+            Circle(u32),
+            Square(u32),
+        }
+
+        let a = Shape::Circle(5);
+        assert!(test_matches!(a, Shape::Circle(_)).is_ok());
+        assert!(test_matches!(a, Shape::Circle(r) if r > 3).is_ok());
+        assert!(test_matches!(a, Shape::Square(_) | Shape::Circle(5)).is_ok());
+        let c = Shape::Square(2);
+        assert!(test_matches!(c, Shape::Square(side) | Shape::Circle(side) if side < 3).is_ok());
+        let failure = test_matches!(a, Shape::Circle(r) if r > 10)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("a does not match Shape::Circle(r) if r > 10"),
+            "{failure}"
+        );
+        assert!(failure.contains("a: Circle(5)"), "{failure}");
+        let b = Some(String::from("hello"));
+        let failure = test_matches!(b, None, "for user {}", 3)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("b does not match None: for user 3"),
+            "{failure}"
+        );
+        assert!(test_matches!(b, Some(ref s) if s.len() == 5).is_ok());
+    }
+
+    #[test]
+    pub fn test_test_eq_with_hash() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        fn hash(value: &str) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let a = "key";
+        let b = String::from("key");
+        assert!(test_eq_with_hash!(a, b.as_str()).is_ok());
+        let c = "Key";
+        let failure = test_eq_with_hash!(a, c, "in bucket {}", 3)
+            .unwrap_err()
+            .to_string();
+        assert!(failure.contains("a != c: in bucket 3"), "{failure}");
+        assert!(failure.contains("a: \"key\"\nc: \"Key\""), "{failure}");
+        assert!(
+            failure.contains(&format!("hash of a: {:#018x}", hash(a))),
+            "{failure}"
+        );
+        assert!(
+            failure.contains(&format!("hash of c: {:#018x}", hash(c))),
+            "{failure}"
+        );
+        assert!(!failure.contains("the hashes are equal"), "{failure}");
+    }
+
+    #[test]
+    pub fn test_test_approx_eq() {
+        let a = 0.1 + 0.2;
+        let b = 0.3;
+        assert!(test_approx_eq!(a, b).is_ok());
+        assert!(test_approx_eq!(a, 0.3).is_ok());
+        assert!(test_approx_eq!(0.3, a).is_ok());
+        let c = 0.1_f32 + 0.2;
+        assert!(test_approx_eq!(c, 0.3).is_ok());
+        assert!(test_approx_eq!(a, 0.31).is_err());
+        assert!(test_approx_eq!(a, 0.31, 0.05).is_ok());
+        assert!(test_approx_eq!(a, f64::NAN, 1.0).is_err());
+        assert!(test_approx_eq!(f64::INFINITY, f64::INFINITY).is_ok());
+        assert!(test_approx_eq!(f32::NEG_INFINITY, f32::NEG_INFINITY, 0.1).is_ok());
+        assert!(test_approx_eq!(f64::INFINITY, f64::NEG_INFINITY).is_err());
+        assert!(test_approx_eq!(f64::INFINITY, f64::MAX, 1.0).is_err());
+        let failure = test_approx_eq!(a, b * 2.0, 0.001, "for step {}", 3)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("|a - b * 2.0| > 0.001: for step 3"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("a: 0.30000000000000004\nb * 2.0: 0.6\n"),
+            "{failure}"
+        );
+        assert!(failure.contains("epsilon: 0.001"), "{failure}");
+        let failure = test_approx_eq!(c, 0.4).unwrap_err().to_string();
+        assert!(failure.contains("|c - 0.4| > EPSILON"), "{failure}");
+        assert!(!failure.contains("0.4: "), "{failure}");
+        assert!(
+            failure.contains(&format!("epsilon: {:?}", f32::EPSILON)),
+            "{failure}"
+        );
+    }
+
+    #[test]
+    pub fn test_test_contiguous() {
+        use std::collections::BTreeSet;
+
+        let a = BTreeSet::from([7_u32, 5, 6, 8]);
+        assert!(test_contiguous!(a).is_ok());
+        let b = BTreeSet::from([1_u32, 2, 3, 6, 7]);
+        let failure = test_contiguous!(b, "for pool {}", 2)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            failure.contains("b is not contiguous: for pool 2"),
+            "{failure}"
+        );
+        assert!(
+            failure.contains("first gap: 4 is missing between 3 and 6"),
+            "{failure}"
+        );
+        let c: BTreeSet<u32> = BTreeSet::new();
+        assert!(test_contiguous!(c).is_ok());
+        assert!(test_contiguous!([-1, 0, 0, 1]).is_ok());
+        assert!(test_contiguous!(vec![u8::MAX - 1, u8::MAX]).is_ok());
+        let failure = test_contiguous!([1, 2, 1]).unwrap_err().to_string();
+        assert!(
+            failure.contains("[1, 2, 1] is not sorted\n[1, 2, 1][1]: 2\n[1, 2, 1][2]: 1"),
+            "{failure}"
+        );
+    }
 }
@@ -4,11 +4,17 @@
 
 // import TestFailure so we can reference it in the docs
 #[cfg(doc)]
-use super::TestFailure;
+use super::{Diff, StructuredFailure, TestFailure};
 
 /// Tests that two expressions are equal to each other (using [`PartialEq`]).
 ///
-/// This macro returns a [`Result`]`<(), `[`TestFailure`]`>` and hints the compiler that the failure
+/// With the `delta` feature, the failure message also shows the difference `left - right` if both
+/// expressions are the same primitive number type.
+///
+/// With the `timing` feature, the failure message also shows how long the comparison took. To keep
+/// the passing case fast, this is measured by repeating the comparison after it failed.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
 /// case is unlikely to happen.
 ///
 /// A custom message can be added, with [`std::fmt`] support.
@@ -43,7 +49,7 @@ macro_rules! test_eq {
                     // The reborrows below are intentional. Without them, the stack slot for the
                     // borrow is initialized even before the values are compared, leading to a
                     // noticeable slow down.
-                    ::std::result::Result::Err($crate::TestFailure::test_failed_one_ident(message, ::std::stringify!($left), &*left_val, ::std::option::Option::None))
+                    ::std::result::Result::Err($crate::TestFailure::test_failed_one_ident(message, ::std::stringify!($left), &*left_val, ::std::option::Option::None).with_delta($crate::helpers::Delta(&*left_val, &*right_val).delta()).with_timing($crate::helpers::comparison_time(|| left_val == right_val)))
                 } else {
                     ::std::result::Result::Ok(())
                 }
@@ -65,7 +71,7 @@ macro_rules! test_eq {
                     // The reborrows below are intentional. Without them, the stack slot for the
                     // borrow is initialized even before the values are compared, leading to a
                     // noticeable slow down.
-                    ::std::result::Result::Err($crate::TestFailure::test_failed_one_ident(message, ::std::stringify!($right), &*right_val, ::std::option::Option::None))
+                    ::std::result::Result::Err($crate::TestFailure::test_failed_one_ident(message, ::std::stringify!($right), &*right_val, ::std::option::Option::None).with_delta($crate::helpers::Delta(&*left_val, &*right_val).delta()).with_timing($crate::helpers::comparison_time(|| left_val == right_val)))
                 } else {
                     ::std::result::Result::Ok(())
                 }
@@ -87,7 +93,7 @@ macro_rules! test_eq {
                     // The reborrows below are intentional. Without them, the stack slot for the
                     // borrow is initialized even before the values are compared, leading to a
                     // noticeable slow down.
-                    ::std::result::Result::Err($crate::TestFailure::test_failed_two_idents(message, ::std::stringify!($left), &*left_val, ::std::stringify!($right), &*right_val, ::std::option::Option::None))
+                    ::std::result::Result::Err($crate::TestFailure::test_failed_comparison(message, ::std::stringify!($left), &*left_val, ::std::stringify!($right), &*right_val, ::std::option::Option::None).with_delta($crate::helpers::Delta(&*left_val, &*right_val).delta()).with_timing($crate::helpers::comparison_time(|| left_val == right_val)))
                 } else {
                     ::std::result::Result::Ok(())
                 }
@@ -109,7 +115,7 @@ macro_rules! test_eq {
                     // The reborrows below are intentional. Without them, the stack slot for the
                     // borrow is initialized even before the values are compared, leading to a
                     // noticeable slow down.
-                    ::std::result::Result::Err($crate::TestFailure::test_failed_one_ident(message, ::std::stringify!($right), &*right_val, ::std::option::Option::Some(::std::format_args!($($arg)+))))
+                    ::std::result::Result::Err($crate::TestFailure::test_failed_one_ident(message, ::std::stringify!($right), &*right_val, ::std::option::Option::Some(::std::format_args!($($arg)+))).with_delta($crate::helpers::Delta(&*left_val, &*right_val).delta()).with_timing($crate::helpers::comparison_time(|| left_val == right_val)))
                 } else {
                     ::std::result::Result::Ok(())
                 }
@@ -131,7 +137,7 @@ macro_rules! test_eq {
                     // The reborrows below are intentional. Without them, the stack slot for the
                     // borrow is initialized even before the values are compared, leading to a
                     // noticeable slow down.
-                    ::std::result::Result::Err($crate::TestFailure::test_failed_one_ident(message, ::std::stringify!($left), &*left_val, ::std::option::Option::Some(::std::format_args!($($arg)+))))
+                    ::std::result::Result::Err($crate::TestFailure::test_failed_one_ident(message, ::std::stringify!($left), &*left_val, ::std::option::Option::Some(::std::format_args!($($arg)+))).with_delta($crate::helpers::Delta(&*left_val, &*right_val).delta()).with_timing($crate::helpers::comparison_time(|| left_val == right_val)))
                 } else {
                     ::std::result::Result::Ok(())
                 }
@@ -153,7 +159,7 @@ macro_rules! test_eq {
                     // The reborrows below are intentional. Without them, the stack slot for the
                     // borrow is initialized even before the values are compared, leading to a
                     // noticeable slow down.
-                    ::std::result::Result::Err($crate::TestFailure::test_failed_two_idents(message, ::std::stringify!($left), &*left_val, ::std::stringify!($right), &*right_val, ::std::option::Option::Some(::std::format_args!($($arg)+))))
+                    ::std::result::Result::Err($crate::TestFailure::test_failed_comparison(message, ::std::stringify!($left), &*left_val, ::std::stringify!($right), &*right_val, ::std::option::Option::Some(::std::format_args!($($arg)+))).with_delta($crate::helpers::Delta(&*left_val, &*right_val).delta()).with_timing($crate::helpers::comparison_time(|| left_val == right_val)))
                 } else {
                     ::std::result::Result::Ok(())
                 }
@@ -164,7 +170,7 @@ macro_rules! test_eq {
 
 /// Tests that two expressions are not equal to each other (using [`PartialEq`]).
 ///
-/// This macro returns a [`Result`]`<(), `[`TestFailure`]`>` and hints the compiler that the failure
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
 /// case is unlikely to happen.
 ///
 /// A custom message can be added, with [`std::fmt`] support.
@@ -323,7 +329,7 @@ macro_rules! test_ne {
 /// The right expression can be anything with a `.contains(&T)` function.
 /// For example, [`slice`], [`Vec`], [`range`][std::ops::Range], ….
 ///
-/// This macro returns a [`Result`]`<(), `[`TestFailure`]`>` and hints the compiler that the failure
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
 /// case is unlikely to happen.
 ///
 /// A custom message can be added, with [`std::fmt`] support.
@@ -480,7 +486,7 @@ macro_rules! test_any {
 /// The right expression can be anything with a `.contains(&T)` function.
 /// For example, [`slice`], [`Vec`], [`range`][std::ops::Range], ….
 ///
-/// This macro returns a [`Result`]`<(), `[`TestFailure`]`>` and hints the compiler that the failure
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
 /// case is unlikely to happen.
 ///
 /// A custom message can be added, with [`std::fmt`] support.
@@ -635,7 +641,7 @@ macro_rules! test_not_any {
 
 /// Tests that the left expression is smaller or equal to the right expression (using [`PartialOrd`]).
 ///
-/// This macro returns a [`Result`]`<(), `[`TestFailure`]`>` and hints the compiler that the failure
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
 /// case is unlikely to happen.
 ///
 /// A custom message can be added, with [`std::fmt`] support.
@@ -789,9 +795,165 @@ macro_rules! test_le {
     }};
 }
 
+/// Tests that the left expression is strictly smaller than the right expression (using [`PartialOrd`]).
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_lt;
+/// let a = 3;
+/// let b = 3;
+/// let c = b * 2;
+/// test_lt!(a, c).expect("This is true");
+/// println!("{:?}", test_lt!(a, b, "and c is {}", c));
+/// // prints:
+/// // [src/main.rs:5:1]: Test failed: a >= b: and c is 6
+/// // a: 3
+/// // b: 3
+/// ```
+#[macro_export]
+macro_rules! test_lt {
+    ($left:expr, $right:literal $(,)?) => {{
+        match (&$left, &$right) {
+            (left_val, right_val) => {
+                if !(left_val < right_val) {
+                    let message = if $crate::__LINE_INFO {
+                        // "[src/main:2:5]: Test failed: a * 2 >= b * 5"
+                        ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " >= ", ::std::stringify!($right))
+                    } else {
+                        // "Test failed: a * 2 >= b * 5"
+                        ::std::concat!("Test failed: ", ::std::stringify!($left), " >= ", ::std::stringify!($right))
+                    };
+
+                    // The reborrows below are intentional. Without them, the stack slot for the
+                    // borrow is initialized even before the values are compared, leading to a
+                    // noticeable slow down.
+                    ::std::result::Result::Err($crate::TestFailure::test_failed_one_ident(message, ::std::stringify!($left), &*left_val, ::std::option::Option::None))
+                } else {
+                    ::std::result::Result::Ok(())
+                }
+            }
+        }
+    }};
+    ($left:literal, $right:expr $(,)?) => {{
+        match (&$left, &$right) {
+            (left_val, right_val) => {
+                if !(left_val < right_val) {
+                    let message = if $crate::__LINE_INFO {
+                        // "[src/main:2:5]: Test failed: a * 2 >= b * 5"
+                        ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " >= ", ::std::stringify!($right))
+                    } else {
+                        // "Test failed: a * 2 >= b * 5"
+                        ::std::concat!("Test failed: ", ::std::stringify!($left), " >= ", ::std::stringify!($right))
+                    };
+
+                    // The reborrows below are intentional. Without them, the stack slot for the
+                    // borrow is initialized even before the values are compared, leading to a
+                    // noticeable slow down.
+                    ::std::result::Result::Err($crate::TestFailure::test_failed_one_ident(message, ::std::stringify!($right), &*right_val, ::std::option::Option::None))
+                } else {
+                    ::std::result::Result::Ok(())
+                }
+            }
+        }
+    }};
+    ($left:expr, $right:expr $(,)?) => {{
+        match (&$left, &$right) {
+            (left_val, right_val) => {
+                if !(left_val < right_val) {
+                    let message = if $crate::__LINE_INFO {
+                        // "[src/main:2:5]: Test failed: a * 2 >= b * 5"
+                        ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " >= ", ::std::stringify!($right))
+                    } else {
+                        // "Test failed: a * 2 >= b * 5"
+                        ::std::concat!("Test failed: ", ::std::stringify!($left), " >= ", ::std::stringify!($right))
+                    };
+
+                    // The reborrows below are intentional. Without them, the stack slot for the
+                    // borrow is initialized even before the values are compared, leading to a
+                    // noticeable slow down.
+                    ::std::result::Result::Err($crate::TestFailure::test_failed_two_idents(message, ::std::stringify!($left), &*left_val, ::std::stringify!($right), &*right_val, ::std::option::Option::None))
+                } else {
+                    ::std::result::Result::Ok(())
+                }
+            }
+        }
+    }};
+    ($left:literal, $right:expr, $($arg:tt)+) => {{
+        match (&$left, &$right) {
+            (left_val, right_val) => {
+                if !(left_val < right_val) {
+                    let message = if $crate::__LINE_INFO {
+                        // "[src/main:2:5]: Test failed: a * 2 >= b * 5"
+                        ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " >= ", ::std::stringify!($right))
+                    } else {
+                        // "Test failed: a * 2 >= b * 5"
+                        ::std::concat!("Test failed: ", ::std::stringify!($left), " >= ", ::std::stringify!($right))
+                    };
+
+                    // The reborrows below are intentional. Without them, the stack slot for the
+                    // borrow is initialized even before the values are compared, leading to a
+                    // noticeable slow down.
+                    ::std::result::Result::Err($crate::TestFailure::test_failed_one_ident(message, ::std::stringify!($right), &*right_val, ::std::option::Option::Some(::std::format_args!($($arg)+))))
+                } else {
+                    ::std::result::Result::Ok(())
+                }
+            }
+        }
+    }};
+    ($left:expr, $right:literal, $($arg:tt)+) => {{
+        match (&$left, &$right) {
+            (left_val, right_val) => {
+                if !(left_val < right_val) {
+                    let message = if $crate::__LINE_INFO {
+                        // "[src/main:2:5]: Test failed: a * 2 >= b * 5"
+                        ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " >= ", ::std::stringify!($right))
+                    } else {
+                        // "Test failed: a * 2 >= b * 5"
+                        ::std::concat!("Test failed: ", ::std::stringify!($left), " >= ", ::std::stringify!($right))
+                    };
+
+                    // The reborrows below are intentional. Without them, the stack slot for the
+                    // borrow is initialized even before the values are compared, leading to a
+                    // noticeable slow down.
+                    ::std::result::Result::Err($crate::TestFailure::test_failed_one_ident(message, ::std::stringify!($left), &*left_val, ::std::option::Option::Some(::std::format_args!($($arg)+))))
+                } else {
+                    ::std::result::Result::Ok(())
+                }
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {{
+        match (&$left, &$right) {
+            (left_val, right_val) => {
+                if !(left_val < right_val) {
+                    let message = if $crate::__LINE_INFO {
+                        // "[src/main:2:5]: Test failed: a * 2 >= b * 5"
+                        ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " >= ", ::std::stringify!($right))
+                    } else {
+                        // "Test failed: a * 2 >= b * 5"
+                        ::std::concat!("Test failed: ", ::std::stringify!($left), " >= ", ::std::stringify!($right))
+                    };
+
+                    // The reborrows below are intentional. Without them, the stack slot for the
+                    // borrow is initialized even before the values are compared, leading to a
+                    // noticeable slow down.
+                    ::std::result::Result::Err($crate::TestFailure::test_failed_two_idents(message, ::std::stringify!($left), &*left_val, ::std::stringify!($right), &*right_val, ::std::option::Option::Some(::std::format_args!($($arg)+))))
+                } else {
+                    ::std::result::Result::Ok(())
+                }
+            }
+        }
+    }};
+}
+
 /// Tests that the left expression is greater or equal to the right expression (using [`PartialOrd`]).
 ///
-/// This macro returns a [`Result`]`<(), `[`TestFailure`]`>` and hints the compiler that the failure
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
 /// case is unlikely to happen.
 ///
 /// A custom message can be added, with [`std::fmt`] support.
@@ -947,10 +1109,10 @@ macro_rules! test_ge {
 
 /// Tests that both tests pass.
 ///
-/// As input this takes two expressions that resolve to the type [`Result`]`<(), `[`TestFailure`]`>`.
+/// As input this takes two expressions that resolve to the type <code>[Result]<(), [TestFailure]></code>.
 /// This means this type is composable with itself, and all the other `test_*!` macros.
 ///
-/// This macro returns a [`Result`]`<(), `[`TestFailure`]`>` and hints the compiler that the failure
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
 /// case is unlikely to happen.
 ///
 /// A custom message can be added, with [`std::fmt`] support.
@@ -992,10 +1154,10 @@ macro_rules! test_and {
 
 /// Tests that at least one test passes.
 ///
-/// As input this takes two expressions that resolve to the type [`Result`]`<(), `[`TestFailure`]`>`.
+/// As input this takes two expressions that resolve to the type <code>[Result]<(), [TestFailure]></code>.
 /// This means this type is composable with itself, and all the other `test_*!` macros.
 ///
-/// This macro returns a [`Result`]`<(), `[`TestFailure`]`>` and hints the compiler that the failure
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
 /// case is unlikely to happen.
 ///
 /// A custom message can be added, with [`std::fmt`] support.
@@ -1045,3 +1207,5271 @@ macro_rules! test_or {
         }
     }};
 }
+
+/// Tests that a [`Result`] is [`Ok`].
+///
+/// On failure the error is shown using both [`Debug`] and [`Display`](std::fmt::Display), so
+/// errors with a readable message stay readable.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_ok;
+/// let a: Result<(), std::fmt::Error> = Ok(());
+/// let b: Result<(), std::fmt::Error> = Err(std::fmt::Error);
+/// test_ok!(a).expect("This is true");
+/// println!("{:?}", test_ok!(b, "while writing {}", "header"));
+/// // prints:
+/// // [src/main.rs:5:1]: Test failed: b is not Ok: while writing header
+/// // b: Err(Error)
+/// // error: an error occurred when formatting an argument
+/// ```
+#[macro_export]
+macro_rules! test_ok {
+    ($result:expr $(,)?) => {{
+        match &$result {
+            ::std::result::Result::Ok(_) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(err) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: write(a) is not Ok"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($result), " is not Ok")
+                } else {
+                    // "Test failed: write(a) is not Ok"
+                    ::std::concat!("Test failed: ", ::std::stringify!($result), " is not Ok")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_error(message, ::std::stringify!($result), &*err, ::std::option::Option::None))
+            }
+        }
+    }};
+    ($result:expr, $($arg:tt)+) => {{
+        match &$result {
+            ::std::result::Result::Ok(_) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(err) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: write(a) is not Ok"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($result), " is not Ok")
+                } else {
+                    // "Test failed: write(a) is not Ok"
+                    ::std::concat!("Test failed: ", ::std::stringify!($result), " is not Ok")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_error(message, ::std::stringify!($result), &*err, ::std::option::Option::Some(::std::format_args!($($arg)+))))
+            }
+        }
+    }};
+}
+
+/// Tests that two iterators yield the same elements, ignoring the order.
+///
+/// Both expressions can be anything that implements [`IntoIterator`], and the items must implement
+/// [`Eq`], [`Hash`](std::hash::Hash) and [`Debug`]. The items are counted as they are yielded, without
+/// collecting the iterators first, so duplicates matter.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_iter_eq_unordered;
+/// let a = vec![1, 2, 3, 3];
+/// let b = vec![3, 1, 3, 2];
+/// let c = vec![1, 2, 4];
+/// test_iter_eq_unordered!(a.iter(), b.iter()).expect("This is true");
+/// println!("{:?}", test_iter_eq_unordered!(a.iter(), c.iter(), "for input {}", 5));
+/// // prints:
+/// // [src/main.rs:6:1]: Test failed: a.iter() != c.iter() (unordered): for input 5
+/// // only in a.iter(): [3, 3]
+/// // only in c.iter(): [4]
+/// ```
+#[macro_export]
+macro_rules! test_iter_eq_unordered {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::test_iter_eq_unordered!(@check $left, $right, ::std::option::Option::None)
+    };
+    (@check $left:expr, $right:expr, $args:expr) => {{
+        match $crate::helpers::multiset_difference($left, $right, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a.iter() != b.iter() (unordered)"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right), " (unordered)")
+                } else {
+                    // "Test failed: a.iter() != b.iter() (unordered)"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right), " (unordered)")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::test_iter_eq_unordered!(@check $left, $right, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that the left expression is within a percentage of the right expression.
+///
+/// The test passes when `|left - right| <= percent / 100 * |right|`. All operands must implement
+/// <code>[Into]<[f64]></code> and [`Copy`], which includes [`f32`], [`f64`] and the integers up to 32 bits.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_within_percent;
+/// let a = 103;
+/// let b = 107;
+/// test_within_percent!(a, 100, 5).expect("This is true");
+/// println!("{:?}", test_within_percent!(b, 100, 5, "and a is {}", a));
+/// // prints:
+/// // [src/main.rs:5:1]: Test failed: b is not within 5% of 100: and a is 103
+/// // b: 107
+/// // 100: 100
+/// // difference: 7% (allowed: 5%)
+/// ```
+#[macro_export]
+macro_rules! test_within_percent {
+    ($left:expr, $right:expr, $percent:expr $(,)?) => {{
+        match $crate::helpers::within_percent($left, $right, $percent, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a is not within 5% of b"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " is not within ", ::std::stringify!($percent), "% of ", ::std::stringify!($right))
+                } else {
+                    // "Test failed: a is not within 5% of b"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " is not within ", ::std::stringify!($percent), "% of ", ::std::stringify!($right))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, ::std::option::Option::None))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $percent:expr, $($arg:tt)+) => {{
+        match $crate::helpers::within_percent($left, $right, $percent, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a is not within 5% of b"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " is not within ", ::std::stringify!($percent), "% of ", ::std::stringify!($right))
+                } else {
+                    // "Test failed: a is not within 5% of b"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " is not within ", ::std::stringify!($percent), "% of ", ::std::stringify!($right))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, ::std::option::Option::Some(::std::format_args!($($arg)+))))
+            }
+        }
+    }};
+}
+
+/// Tests that two tuples are equal to each other, comparing them element by element (using [`PartialEq`]).
+///
+/// Both tuples must be written out, for example `test_tuple_eq!((a, b), (c, d))`. Tuples with 2 to 4
+/// elements are supported. On failure only the positions that differ are shown.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_tuple_eq;
+/// let a = 3;
+/// let b = "hello";
+/// let c = 4;
+/// test_tuple_eq!((a, b), (3, "hello")).expect("This is true");
+/// println!("{:?}", test_tuple_eq!((a, b, c), (3, "world", 5), "and a is {}", a));
+/// // prints:
+/// // [src/main.rs:6:1]: Test failed: (a, b, c) != (3, "world", 5): and a is 3
+/// // position 1: b != "world"
+/// //    b: "hello"
+/// //    "world": "world"
+/// // position 2: c != 5
+/// //    c: 4
+/// //    5: 5
+/// ```
+#[macro_export]
+macro_rules! test_tuple_eq {
+    (@compare [$(($index:tt, $left:expr, $right:expr)),+], $args:expr) => {{
+        let mut details = ::std::vec::Vec::new();
+        $(
+            match (&$left, &$right) {
+                (left_val, right_val) => {
+                    if !(left_val == right_val) {
+                        details.push(::std::format!(
+                            ::std::concat!("position ", $index, ": ", ::std::stringify!($left), " != ", ::std::stringify!($right), "\n   ", ::std::stringify!($left), ": {:?}\n   ", ::std::stringify!($right), ": {:?}"),
+                            &*left_val,
+                            &*right_val,
+                        ));
+                    }
+                }
+            }
+        )+
+        if details.is_empty() {
+            ::std::result::Result::Ok(())
+        } else {
+            let message = if $crate::__LINE_INFO {
+                // "[src/main:2:5]: Test failed: (a, b) != (c, d)"
+                ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: (", ::std::stringify!($($left),+), ") != (", ::std::stringify!($($right),+), ')')
+            } else {
+                // "Test failed: (a, b) != (c, d)"
+                ::std::concat!("Test failed: (", ::std::stringify!($($left),+), ") != (", ::std::stringify!($($right),+), ')')
+            };
+
+            ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details.join("\n"), $args))
+        }
+    }};
+    (($l0:expr, $l1:expr $(,)?), ($r0:expr, $r1:expr $(,)?) $(,)?) => {
+        $crate::test_tuple_eq!(@compare [(0, $l0, $r0), (1, $l1, $r1)], ::std::option::Option::None)
+    };
+    (($l0:expr, $l1:expr, $l2:expr $(,)?), ($r0:expr, $r1:expr, $r2:expr $(,)?) $(,)?) => {
+        $crate::test_tuple_eq!(@compare [(0, $l0, $r0), (1, $l1, $r1), (2, $l2, $r2)], ::std::option::Option::None)
+    };
+    (($l0:expr, $l1:expr, $l2:expr, $l3:expr $(,)?), ($r0:expr, $r1:expr, $r2:expr, $r3:expr $(,)?) $(,)?) => {
+        $crate::test_tuple_eq!(@compare [(0, $l0, $r0), (1, $l1, $r1), (2, $l2, $r2), (3, $l3, $r3)], ::std::option::Option::None)
+    };
+    (($l0:expr, $l1:expr $(,)?), ($r0:expr, $r1:expr $(,)?), $($arg:tt)+) => {
+        $crate::test_tuple_eq!(@compare [(0, $l0, $r0), (1, $l1, $r1)], ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+    (($l0:expr, $l1:expr, $l2:expr $(,)?), ($r0:expr, $r1:expr, $r2:expr $(,)?), $($arg:tt)+) => {
+        $crate::test_tuple_eq!(@compare [(0, $l0, $r0), (1, $l1, $r1), (2, $l2, $r2)], ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+    (($l0:expr, $l1:expr, $l2:expr, $l3:expr $(,)?), ($r0:expr, $r1:expr, $r2:expr, $r3:expr $(,)?), $($arg:tt)+) => {
+        $crate::test_tuple_eq!(@compare [(0, $l0, $r0), (1, $l1, $r1), (2, $l2, $r2), (3, $l3, $r3)], ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two collections contain the same elements, by comparing sorted copies of them.
+///
+/// Both expressions must implement <code>[AsRef]<\[T\]></code>, for example [`slice`], [`Vec`] or an array.
+/// The elements must implement [`Ord`], [`Clone`] and [`Debug`]. For small collections this is cheaper
+/// than [`test_iter_eq_unordered!`].
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_eq_sorted;
+/// let a = vec![3, 1, 2];
+/// let b = [1, 2, 3];
+/// let c = [1, 4, 3];
+/// test_eq_sorted!(a, b).expect("This is true");
+/// println!("{:?}", test_eq_sorted!(a, c, "and b is {:?}", b));
+/// // prints:
+/// // [src/main.rs:6:1]: Test failed: a != c (sorted): and b is [1, 2, 3]
+/// // sorted a: [1, 2, 3]
+/// // sorted c: [1, 3, 4]
+/// // first difference at index 1: 2 != 3
+/// ```
+#[macro_export]
+macro_rules! test_eq_sorted {
+    ($left:expr, $right:expr $(,)?) => {{
+        match $crate::helpers::sorted_difference(&$left, &$right, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a != b (sorted)"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right), " (sorted)")
+                } else {
+                    // "Test failed: a != b (sorted)"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right), " (sorted)")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, ::std::option::Option::None))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {{
+        match $crate::helpers::sorted_difference(&$left, &$right, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a != b (sorted)"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right), " (sorted)")
+                } else {
+                    // "Test failed: a != b (sorted)"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right), " (sorted)")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, ::std::option::Option::Some(::std::format_args!($($arg)+))))
+            }
+        }
+    }};
+}
+
+/// Tests that the left expression contains all the values in the right expression.
+///
+/// The left expression can be anything with a `.contains(&T)` function.
+/// For example, [`slice`], [`Vec`], [`HashSet`](std::collections::HashSet), ….
+/// The right expression can be anything with an `.iter()` function, like an array or a [`slice`].
+/// All missing values are reported, not just the first one.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_contains_all;
+/// let a = vec![1, 3, 5, 7];
+/// let b = 9;
+/// test_contains_all!(a, [3, 7]).expect("This is true");
+/// println!("{:?}", test_contains_all!(a, [1, 2, 4], "and b is {}", b));
+/// // prints:
+/// // [src/main.rs:5:1]: Test failed: a does not contain all of [1, 2, 4]: and b is 9
+/// // missing: [2, 4]
+/// // a: [1, 3, 5, 7]
+/// ```
+#[macro_export]
+macro_rules! test_contains_all {
+    ($left:expr, $right:expr $(,)?) => {{
+        match (&$left, &$right) {
+            (left_val, right_val) => {
+                let missing: ::std::vec::Vec<_> = right_val.iter().filter(|item| !left_val.contains(*item)).collect();
+                if !missing.is_empty() {
+                    let message = if $crate::__LINE_INFO {
+                        // "[src/main:2:5]: Test failed: a does not contain all of [5, 10, 15]"
+                        ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " does not contain all of ", ::std::stringify!($right))
+                    } else {
+                        // "Test failed: a does not contain all of [5, 10, 15]"
+                        ::std::concat!("Test failed: ", ::std::stringify!($left), " does not contain all of ", ::std::stringify!($right))
+                    };
+
+                    ::std::result::Result::Err($crate::TestFailure::test_failed_two_idents(message, "missing", &missing, ::std::stringify!($left), &*left_val, ::std::option::Option::None))
+                } else {
+                    ::std::result::Result::Ok(())
+                }
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {{
+        match (&$left, &$right) {
+            (left_val, right_val) => {
+                let missing: ::std::vec::Vec<_> = right_val.iter().filter(|item| !left_val.contains(*item)).collect();
+                if !missing.is_empty() {
+                    let message = if $crate::__LINE_INFO {
+                        // "[src/main:2:5]: Test failed: a does not contain all of [5, 10, 15]"
+                        ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " does not contain all of ", ::std::stringify!($right))
+                    } else {
+                        // "Test failed: a does not contain all of [5, 10, 15]"
+                        ::std::concat!("Test failed: ", ::std::stringify!($left), " does not contain all of ", ::std::stringify!($right))
+                    };
+
+                    ::std::result::Result::Err($crate::TestFailure::test_failed_two_idents(message, "missing", &missing, ::std::stringify!($left), &*left_val, ::std::option::Option::Some(::std::format_args!($($arg)+))))
+                } else {
+                    ::std::result::Result::Ok(())
+                }
+            }
+        }
+    }};
+}
+
+/// Tests that the Hamming distance between two byte buffers is at most the maximum.
+///
+/// The distance is the number of *bytes* that differ, not the number of bits. Both buffers must implement
+/// <code>[AsRef]<\[[u8]\]></code> and have the same length, buffers with different lengths fail the test
+/// without a distance.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_hamming_le;
+/// let a = [0xDE, 0xAD, 0xBE, 0xEF];
+/// let b = [0xDE, 0xAD, 0xBE, 0xEE];
+/// let c = [0x00, 0xAD, 0xBE, 0xEE];
+/// test_hamming_le!(a, b, 1).expect("This is true");
+/// println!("{:?}", test_hamming_le!(a, c, 1, "for block {}", 3));
+/// // prints:
+/// // [src/main.rs:6:1]: Test failed: Hamming distance between a and c is greater than 1: for block 3
+/// // distance: 2 bytes (maximum: 1)
+/// // differing offsets: [0, 3]
+/// ```
+#[macro_export]
+macro_rules! test_hamming_le {
+    ($left:expr, $right:expr, $max:expr $(,)?) => {
+        $crate::test_hamming_le!(@compare $left, $right, $max, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $max:expr, $args:expr) => {{
+        if let ::std::result::Result::Err(details) = $crate::helpers::byte_lengths_error(&$left, &$right, ::std::stringify!($left), ::std::stringify!($right)) {
+            let message = if $crate::__LINE_INFO {
+                // "[src/main:2:5]: Test failed: a and b have different lengths"
+                ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " and ", ::std::stringify!($right), " have different lengths")
+            } else {
+                // "Test failed: a and b have different lengths"
+                ::std::concat!("Test failed: ", ::std::stringify!($left), " and ", ::std::stringify!($right), " have different lengths")
+            };
+
+            ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+        } else if let ::std::result::Result::Err(details) = $crate::helpers::hamming_distance_le(&$left, &$right, $max) {
+            let message = if $crate::__LINE_INFO {
+                // "[src/main:2:5]: Test failed: Hamming distance between a and b is greater than 2"
+                ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: Hamming distance between ", ::std::stringify!($left), " and ", ::std::stringify!($right), " is greater than ", ::std::stringify!($max))
+            } else {
+                // "Test failed: Hamming distance between a and b is greater than 2"
+                ::std::concat!("Test failed: Hamming distance between ", ::std::stringify!($left), " and ", ::std::stringify!($right), " is greater than ", ::std::stringify!($max))
+            };
+
+            ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+        } else {
+            ::std::result::Result::Ok(())
+        }
+    }};
+    ($left:expr, $right:expr, $max:expr, $($arg:tt)+) => {
+        $crate::test_hamming_le!(@compare $left, $right, $max, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that a channel receives the expected values, in order.
+///
+/// The left expression must be a [`Receiver`](std::sync::mpsc::Receiver) and the right expression must
+/// implement <code>[AsRef]<\[T\]></code>. Exactly as many values as expected are received, waiting
+/// at most one second for each value. The timeout can be changed with `timeout = d`, where `d` is a
+/// [`Duration`](std::time::Duration).
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use test_eq::test_recv_eq;
+/// let (tx, rx) = std::sync::mpsc::channel();
+/// for i in [1, 2, 3, 5] {
+///     tx.send(i).unwrap();
+/// }
+/// test_recv_eq!(rx, [1, 2]).expect("This is true");
+/// println!("{:?}", test_recv_eq!(rx, [3, 4], timeout = Duration::from_secs(5), "from worker {}", 1));
+/// // prints:
+/// // [src/main.rs:8:1]: Test failed: rx did not receive [3, 4]: from worker 1
+/// // first mismatch at index 1
+/// // received: 5
+/// // expected: 4
+/// ```
+#[macro_export]
+macro_rules! test_recv_eq {
+    ($receiver:expr, $expected:expr $(,)?) => {
+        $crate::test_recv_eq!(@compare $receiver, $expected, $crate::helpers::DEFAULT_RECV_TIMEOUT, ::std::option::Option::None)
+    };
+    ($receiver:expr, $expected:expr, timeout = $timeout:expr $(,)?) => {
+        $crate::test_recv_eq!(@compare $receiver, $expected, $timeout, ::std::option::Option::None)
+    };
+    ($receiver:expr, $expected:expr, timeout = $timeout:expr, $($arg:tt)+) => {
+        $crate::test_recv_eq!(@compare $receiver, $expected, $timeout, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+    (@compare $receiver:expr, $expected:expr, $timeout:expr, $args:expr) => {{
+        match $crate::helpers::recv_sequence(&$receiver, &$expected, $timeout) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: rx did not receive [1, 2, 3]"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($receiver), " did not receive ", ::std::stringify!($expected))
+                } else {
+                    // "Test failed: rx did not receive [1, 2, 3]"
+                    ::std::concat!("Test failed: ", ::std::stringify!($receiver), " did not receive ", ::std::stringify!($expected))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($receiver:expr, $expected:expr, $($arg:tt)+) => {
+        $crate::test_recv_eq!(@compare $receiver, $expected, $crate::helpers::DEFAULT_RECV_TIMEOUT, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two `NonZero*` integers are equal to each other, showing the plain integers on failure.
+///
+/// Both expressions must be one of the `NonZero*` types, like [`NonZeroU32`](std::num::NonZeroU32).
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use std::num::NonZeroU32;
+/// use test_eq::test_nonzero_eq;
+/// let a = NonZeroU32::new(3).unwrap();
+/// let b = NonZeroU32::new(1 + 2).unwrap();
+/// let c = NonZeroU32::new(6).unwrap();
+/// test_nonzero_eq!(a, b).expect("This is true");
+/// println!("{:?}", test_nonzero_eq!(a, c, "and b is {}", b));
+/// // prints:
+/// // [src/main.rs:7:1]: Test failed: a != c: and b is 3
+/// // a: 3
+/// // c: 6
+/// ```
+#[macro_export]
+macro_rules! test_nonzero_eq {
+    ($left:expr, $right:expr $(,)?) => {{
+        match ($crate::helpers::NonZeroInteger::get($left), $crate::helpers::NonZeroInteger::get($right)) {
+            (left_val, right_val) => {
+                if !(left_val == right_val) {
+                    let message = if $crate::__LINE_INFO {
+                        // "[src/main:2:5]: Test failed: a != b"
+                        ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                    } else {
+                        // "Test failed: a != b"
+                        ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                    };
+
+                    ::std::result::Result::Err($crate::TestFailure::test_failed_two_idents(message, ::std::stringify!($left), &left_val, ::std::stringify!($right), &right_val, ::std::option::Option::None))
+                } else {
+                    ::std::result::Result::Ok(())
+                }
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {{
+        match ($crate::helpers::NonZeroInteger::get($left), $crate::helpers::NonZeroInteger::get($right)) {
+            (left_val, right_val) => {
+                if !(left_val == right_val) {
+                    let message = if $crate::__LINE_INFO {
+                        // "[src/main:2:5]: Test failed: a != b"
+                        ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                    } else {
+                        // "Test failed: a != b"
+                        ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                    };
+
+                    ::std::result::Result::Err($crate::TestFailure::test_failed_two_idents(message, ::std::stringify!($left), &left_val, ::std::stringify!($right), &right_val, ::std::option::Option::Some(::std::format_args!($($arg)+))))
+                } else {
+                    ::std::result::Result::Ok(())
+                }
+            }
+        }
+    }};
+}
+
+/// Tests that timestamps never decrease and that the gaps between them are within bounds.
+///
+/// The first expression must implement <code>[AsRef]<\[[Instant](std::time::Instant)\]></code>, the
+/// bounds must be a [`Duration`](std::time::Duration). Every gap between two consecutive timestamps
+/// must be at least `min_gap` and at most `max_gap`.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use std::time::{Duration, Instant};
+/// use test_eq::test_timestamps_monotonic;
+/// let start = Instant::now();
+/// let a = [start, start + Duration::from_millis(10), start + Duration::from_millis(20)];
+/// let b = [start, start + Duration::from_millis(10), start + Duration::from_millis(50)];
+/// let min = Duration::from_millis(5);
+/// let max = Duration::from_millis(15);
+/// test_timestamps_monotonic!(a, min, max).expect("This is true");
+/// println!("{:?}", test_timestamps_monotonic!(b, min, max, "for limiter {}", 2));
+/// // prints:
+/// // [src/main.rs:9:1]: Test failed: b is not monotonic within min..=max: for limiter 2
+/// // gap between timestamps 1 and 2 is 40ms
+/// // allowed: 5ms..=15ms
+/// ```
+#[macro_export]
+macro_rules! test_timestamps_monotonic {
+    ($timestamps:expr, $min_gap:expr, $max_gap:expr $(,)?) => {{
+        match $crate::helpers::timestamps_monotonic(&$timestamps, $min_gap, $max_gap) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: times is not monotonic within min..=max"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($timestamps), " is not monotonic within ", ::std::stringify!($min_gap), "..=", ::std::stringify!($max_gap))
+                } else {
+                    // "Test failed: times is not monotonic within min..=max"
+                    ::std::concat!("Test failed: ", ::std::stringify!($timestamps), " is not monotonic within ", ::std::stringify!($min_gap), "..=", ::std::stringify!($max_gap))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, ::std::option::Option::None))
+            }
+        }
+    }};
+    ($timestamps:expr, $min_gap:expr, $max_gap:expr, $($arg:tt)+) => {{
+        match $crate::helpers::timestamps_monotonic(&$timestamps, $min_gap, $max_gap) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: times is not monotonic within min..=max"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($timestamps), " is not monotonic within ", ::std::stringify!($min_gap), "..=", ::std::stringify!($max_gap))
+                } else {
+                    // "Test failed: times is not monotonic within min..=max"
+                    ::std::concat!("Test failed: ", ::std::stringify!($timestamps), " is not monotonic within ", ::std::stringify!($min_gap), "..=", ::std::stringify!($max_gap))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, ::std::option::Option::Some(::std::format_args!($($arg)+))))
+            }
+        }
+    }};
+}
+
+/// Tests that bytes are equal to a hex encoded string.
+///
+/// The left expression must implement <code>[AsRef]<\[[u8]\]></code>, the right expression must be a
+/// [`&str`](str). On failure, a hex dump of the first difference is shown. If the string is not valid
+/// hex, the test fails with the decoding error.
+///
+/// This macro is only available with the `hex` feature.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_bytes_eq_hex;
+/// let a = [0xDE, 0xAD, 0xBE, 0xEF];
+/// test_bytes_eq_hex!(a, "deadbeef").expect("This is true");
+/// println!("{:?}", test_bytes_eq_hex!(a, "deadbeee", "for block {}", 3));
+/// // prints:
+/// // [src/main.rs:4:1]: Test failed: a != hex "deadbeee": for block 3
+/// // first difference at offset 0x3
+/// // a: 00000000: de ad be ef
+/// // "deadbeee": 00000000: de ad be ee
+/// ```
+#[cfg(feature = "hex")]
+#[macro_export]
+macro_rules! test_bytes_eq_hex {
+    ($actual:expr, $expected:expr $(,)?) => {{
+        match $crate::helpers::hex_difference(&$actual, $expected, ::std::stringify!($actual), ::std::stringify!($expected)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a != hex "deadbeef""
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($actual), " != hex ", ::std::stringify!($expected))
+                } else {
+                    // "Test failed: a != hex "deadbeef""
+                    ::std::concat!("Test failed: ", ::std::stringify!($actual), " != hex ", ::std::stringify!($expected))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, ::std::option::Option::None))
+            }
+        }
+    }};
+    ($actual:expr, $expected:expr, $($arg:tt)+) => {{
+        match $crate::helpers::hex_difference(&$actual, $expected, ::std::stringify!($actual), ::std::stringify!($expected)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a != hex "deadbeef""
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($actual), " != hex ", ::std::stringify!($expected))
+                } else {
+                    // "Test failed: a != hex "deadbeef""
+                    ::std::concat!("Test failed: ", ::std::stringify!($actual), " != hex ", ::std::stringify!($expected))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, ::std::option::Option::Some(::std::format_args!($($arg)+))))
+            }
+        }
+    }};
+}
+
+/// Tests that bytes are equal to a base64 encoded string.
+///
+/// The left expression must implement <code>[AsRef]<\[[u8]\]></code>, the right expression must be a
+/// [`&str`](str). On failure, a hex dump of the first difference is shown. If the string is not valid
+/// base64, the test fails with the decoding error.
+///
+/// This macro is only available with the `base64` feature.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_bytes_eq_base64;
+/// let a = [0xDE, 0xAD, 0xBE, 0xEF];
+/// test_bytes_eq_base64!(a, "3q2+7w==").expect("This is true");
+/// println!("{:?}", test_bytes_eq_base64!(a, "3q2+7g==", "for block {}", 3));
+/// // prints:
+/// // [src/main.rs:4:1]: Test failed: a != base64 "3q2+7g==": for block 3
+/// // first difference at offset 0x3
+/// // a: 00000000: de ad be ef
+/// // "3q2+7g==": 00000000: de ad be ee
+/// ```
+#[cfg(feature = "base64")]
+#[macro_export]
+macro_rules! test_bytes_eq_base64 {
+    ($actual:expr, $expected:expr $(,)?) => {{
+        match $crate::helpers::base64_difference(&$actual, $expected, ::std::stringify!($actual), ::std::stringify!($expected)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a != base64 "3q2+7w==""
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($actual), " != base64 ", ::std::stringify!($expected))
+                } else {
+                    // "Test failed: a != base64 "3q2+7w==""
+                    ::std::concat!("Test failed: ", ::std::stringify!($actual), " != base64 ", ::std::stringify!($expected))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, ::std::option::Option::None))
+            }
+        }
+    }};
+    ($actual:expr, $expected:expr, $($arg:tt)+) => {{
+        match $crate::helpers::base64_difference(&$actual, $expected, ::std::stringify!($actual), ::std::stringify!($expected)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a != base64 "3q2+7w==""
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($actual), " != base64 ", ::std::stringify!($expected))
+                } else {
+                    // "Test failed: a != base64 "3q2+7w==""
+                    ::std::concat!("Test failed: ", ::std::stringify!($actual), " != base64 ", ::std::stringify!($expected))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, ::std::option::Option::Some(::std::format_args!($($arg)+))))
+            }
+        }
+    }};
+}
+
+/// Tests that a future is still pending.
+///
+/// The future is polled exactly once, with a waker that does nothing. The future is consumed, so
+/// to keep using it pass a `&mut` to it (if it is [`Unpin`]) or a [`Pin<&mut F>`](std::pin::Pin).
+/// On failure, the output of the future is shown.
+///
+/// This macro is only available with the `async` feature.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_pending;
+/// let a = std::future::pending::<u32>();
+/// let b = std::future::ready(5);
+/// test_pending!(a).expect("This is true");
+/// println!("{:?}", test_pending!(b, "in state {}", "Idle"));
+/// // prints:
+/// // [src/main.rs:5:1]: Test failed: b is ready: in state Idle
+/// // output: 5
+/// ```
+#[cfg(feature = "async")]
+#[macro_export]
+macro_rules! test_pending {
+    ($future:expr $(,)?) => {{
+        match $crate::helpers::poll_once($future) {
+            ::std::task::Poll::Pending => ::std::result::Result::Ok(()),
+            ::std::task::Poll::Ready(output) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: fut is ready"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($future), " is ready")
+                } else {
+                    // "Test failed: fut is ready"
+                    ::std::concat!("Test failed: ", ::std::stringify!($future), " is ready")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_one_ident(message, "output", &output, ::std::option::Option::None))
+            }
+        }
+    }};
+    ($future:expr, $($arg:tt)+) => {{
+        match $crate::helpers::poll_once($future) {
+            ::std::task::Poll::Pending => ::std::result::Result::Ok(()),
+            ::std::task::Poll::Ready(output) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: fut is ready"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($future), " is ready")
+                } else {
+                    // "Test failed: fut is ready"
+                    ::std::concat!("Test failed: ", ::std::stringify!($future), " is ready")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_one_ident(message, "output", &output, ::std::option::Option::Some(::std::format_args!($($arg)+))))
+            }
+        }
+    }};
+}
+
+/// Tests that a collection is equal to the items of an iterator, like a range.
+///
+/// The left expression must implement <code>[AsRef]<\[T\]></code>, the right expression can be anything
+/// that implements [`IntoIterator`]. The items are compared one by one, so the right expression is
+/// never collected.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_eq_range;
+/// let a = vec![0, 1, 2];
+/// let b = vec![0, 1, 3];
+/// test_eq_range!(a, 0..3).expect("This is true");
+/// println!("{:?}", test_eq_range!(b, 0..3, "and a is {:?}", a));
+/// // prints:
+/// // [src/main.rs:5:1]: Test failed: b != 0..3: and a is [0, 1, 2]
+/// // first difference at index 2: 3 != 2
+/// ```
+#[macro_export]
+macro_rules! test_eq_range {
+    ($left:expr, $right:expr $(,)?) => {{
+        match $crate::helpers::iter_difference(&$left, $right, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a != 0..n"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                } else {
+                    // "Test failed: a != 0..n"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, ::std::option::Option::None))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {{
+        match $crate::helpers::iter_difference(&$left, $right, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a != 0..n"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                } else {
+                    // "Test failed: a != 0..n"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, ::std::option::Option::Some(::std::format_args!($($arg)+))))
+            }
+        }
+    }};
+}
+
+/// Tests that two [`Wrapping`](std::num::Wrapping) or [`Saturating`](std::num::Saturating) integers
+/// are equal to each other, showing the plain integers on failure.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use std::num::Wrapping;
+/// use test_eq::test_wrapping_eq;
+/// let a = Wrapping(250u8) + Wrapping(10);
+/// let b = Wrapping(4u8);
+/// let c = Wrapping(5u8);
+/// test_wrapping_eq!(a, b).expect("This is true");
+/// println!("{:?}", test_wrapping_eq!(a, c, "and b is {}", b));
+/// // prints:
+/// // [src/main.rs:7:1]: Test failed: a != c: and b is 4
+/// // a: 4
+/// // c: 5
+/// ```
+#[macro_export]
+macro_rules! test_wrapping_eq {
+    ($left:expr, $right:expr $(,)?) => {{
+        match ($crate::helpers::IntegerWrapper::inner(&$left), $crate::helpers::IntegerWrapper::inner(&$right)) {
+            (left_val, right_val) => {
+                if !(left_val == right_val) {
+                    let message = if $crate::__LINE_INFO {
+                        // "[src/main:2:5]: Test failed: a != b"
+                        ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                    } else {
+                        // "Test failed: a != b"
+                        ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                    };
+
+                    // The reborrows below are intentional. Without them, the stack slot for the
+                    // borrow is initialized even before the values are compared, leading to a
+                    // noticeable slow down.
+                    ::std::result::Result::Err($crate::TestFailure::test_failed_two_idents(message, ::std::stringify!($left), &*left_val, ::std::stringify!($right), &*right_val, ::std::option::Option::None))
+                } else {
+                    ::std::result::Result::Ok(())
+                }
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {{
+        match ($crate::helpers::IntegerWrapper::inner(&$left), $crate::helpers::IntegerWrapper::inner(&$right)) {
+            (left_val, right_val) => {
+                if !(left_val == right_val) {
+                    let message = if $crate::__LINE_INFO {
+                        // "[src/main:2:5]: Test failed: a != b"
+                        ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                    } else {
+                        // "Test failed: a != b"
+                        ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                    };
+
+                    // The reborrows below are intentional. Without them, the stack slot for the
+                    // borrow is initialized even before the values are compared, leading to a
+                    // noticeable slow down.
+                    ::std::result::Result::Err($crate::TestFailure::test_failed_two_idents(message, ::std::stringify!($left), &*left_val, ::std::stringify!($right), &*right_val, ::std::option::Option::Some(::std::format_args!($($arg)+))))
+                } else {
+                    ::std::result::Result::Ok(())
+                }
+            }
+        }
+    }};
+}
+
+/// Tests that a capture group of a regular expression is equal to the expected string.
+///
+/// The first expression must implement <code>[AsRef]<[str]></code>. The pattern uses the syntax of the
+/// [`regex`](https://docs.rs/regex) crate and is compiled every time the test runs. Group `0` is the
+/// whole match. The test fails if the pattern is invalid, does not match, or the group is not part of the match.
+///
+/// This macro is only available with the `regex` feature.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_capture_eq;
+/// let a = "2024-06";
+/// test_capture_eq!(a, r"(\d+)-(\d+)", 1, "2024").expect("This is true");
+/// println!("{:?}", test_capture_eq!(a, r"(\d+)-(\d+)", 2, "07", "for year {}", 2024));
+/// // prints:
+/// // [src/main.rs:4:1]: Test failed: group 2 of a != "07": for year 2024
+/// // group 2: "06"
+/// // expected: "07"
+/// // a: "2024-06"
+/// ```
+#[cfg(feature = "regex")]
+#[macro_export]
+macro_rules! test_capture_eq {
+    ($value:expr, $pattern:expr, $group:expr, $expected:expr $(,)?) => {{
+        match $crate::helpers::capture_difference(&$value, $pattern, $group, $expected, ::std::stringify!($value)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: group 1 of a != "2024""
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: group ", ::std::stringify!($group), " of ", ::std::stringify!($value), " != ", ::std::stringify!($expected))
+                } else {
+                    // "Test failed: group 1 of a != "2024""
+                    ::std::concat!("Test failed: group ", ::std::stringify!($group), " of ", ::std::stringify!($value), " != ", ::std::stringify!($expected))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, ::std::option::Option::None))
+            }
+        }
+    }};
+    ($value:expr, $pattern:expr, $group:expr, $expected:expr, $($arg:tt)+) => {{
+        match $crate::helpers::capture_difference(&$value, $pattern, $group, $expected, ::std::stringify!($value)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: group 1 of a != "2024""
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: group ", ::std::stringify!($group), " of ", ::std::stringify!($value), " != ", ::std::stringify!($expected))
+                } else {
+                    // "Test failed: group 1 of a != "2024""
+                    ::std::concat!("Test failed: group ", ::std::stringify!($group), " of ", ::std::stringify!($value), " != ", ::std::stringify!($expected))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, ::std::option::Option::Some(::std::format_args!($($arg)+))))
+            }
+        }
+    }};
+}
+
+/// Tests that two grids (like `Vec<Vec<T>>`) are equal to each other, reporting the first cell
+/// that differs.
+///
+/// The row counts are compared first, then the length of every row, and finally the cells. The
+/// cells are compared using [`PartialEq`] and must implement [`Debug`](std::fmt::Debug).
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_grid_eq;
+/// let a = vec![vec![1, 2, 3], vec![4, 5, 6]];
+/// let b = vec![vec![1, 2, 3], vec![4, 5, 7]];
+/// test_grid_eq!(a, a.clone()).expect("This is true");
+/// println!("{:?}", test_grid_eq!(a, b, "after {} steps", 3));
+/// // prints:
+/// // [src/main.rs:5:1]: Test failed: a != b: after 3 steps
+/// // first difference at (1, 2)
+/// // a: 6
+/// // b: 7
+/// ```
+#[macro_export]
+macro_rules! test_grid_eq {
+    ($left:expr, $right:expr $(,)?) => {{
+        match $crate::helpers::grid_difference(&$left, &$right, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a != b"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                } else {
+                    // "Test failed: a != b"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, ::std::option::Option::None))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {{
+        match $crate::helpers::grid_difference(&$left, &$right, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a != b"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                } else {
+                    // "Test failed: a != b"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, ::std::option::Option::Some(::std::format_args!($($arg)+))))
+            }
+        }
+    }};
+}
+
+/// Tests that comparing two expressions (using [`Ord`]) gives the expected [`Ordering`](std::cmp::Ordering).
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use std::cmp::Ordering;
+/// use test_eq::test_cmp;
+/// let a = "apple";
+/// let b = "banana";
+/// test_cmp!(a, b, Ordering::Less).expect("This is true");
+/// println!("{:?}", test_cmp!(b, a, Ordering::Less, "sorting {} words", 2));
+/// // prints:
+/// // [src/main.rs:6:1]: Test failed: b.cmp(&a) != Ordering::Less: sorting 2 words
+/// // b: "banana"
+/// // a: "apple"
+/// // expected: Less
+/// // actual: Greater
+/// ```
+#[macro_export]
+macro_rules! test_cmp {
+    ($left:expr, $right:expr, $expected:expr $(,)?) => {{
+        match $crate::helpers::cmp_difference(&$left, &$right, $expected, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a.cmp(&b) != Ordering::Less"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), ".cmp(&", ::std::stringify!($right), ") != ", ::std::stringify!($expected))
+                } else {
+                    // "Test failed: a.cmp(&b) != Ordering::Less"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), ".cmp(&", ::std::stringify!($right), ") != ", ::std::stringify!($expected))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, ::std::option::Option::None))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $expected:expr, $($arg:tt)+) => {{
+        match $crate::helpers::cmp_difference(&$left, &$right, $expected, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a.cmp(&b) != Ordering::Less"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), ".cmp(&", ::std::stringify!($right), ") != ", ::std::stringify!($expected))
+                } else {
+                    // "Test failed: a.cmp(&b) != Ordering::Less"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), ".cmp(&", ::std::stringify!($right), ") != ", ::std::stringify!($expected))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, ::std::option::Option::Some(::std::format_args!($($arg)+))))
+            }
+        }
+    }};
+}
+
+/// Tests that an expression is equal to (using [`PartialEq`]) the expected value for the current platform.
+///
+/// The `windows` value is used on Windows, the `unix` value on Unix-like platforms and the `default`
+/// value everywhere else. All values are type checked on every platform, which saves duplicating
+/// the test with `#[cfg]` attributes.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_eq_platform;
+/// let separator = std::path::MAIN_SEPARATOR;
+/// test_eq_platform!(separator, windows = '\\', unix = '/', default = '/').expect("This is true");
+/// # #[cfg(unix)]
+/// println!("{:?}", test_eq_platform!(separator, windows = '/', unix = '\\', default = '\\', "in {}", "paths"));
+/// // prints (on Unix):
+/// // [src/main.rs:5:1]: Test failed: separator != expected value for this platform: in paths
+/// // separator: '/'
+/// // unix: '\\'
+/// ```
+#[macro_export]
+macro_rules! test_eq_platform {
+    ($actual:expr, windows = $windows:expr, unix = $unix:expr, default = $default:expr $(,)?) => {
+        $crate::test_eq_platform!(@compare $actual, $windows, $unix, $default, ::std::option::Option::None)
+    };
+    ($actual:expr, windows = $windows:expr, unix = $unix:expr, default = $default:expr, $($arg:tt)+) => {
+        $crate::test_eq_platform!(@compare $actual, $windows, $unix, $default, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+    (@compare $actual:expr, $windows:expr, $unix:expr, $default:expr, $args:expr) => {{
+        let (expected, platform) = if ::std::cfg!(windows) {
+            (&$windows, "windows")
+        } else if ::std::cfg!(unix) {
+            (&$unix, "unix")
+        } else {
+            (&$default, "default")
+        };
+        match $crate::helpers::platform_difference(&$actual, expected, platform, ::std::stringify!($actual)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a != expected value for this platform"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($actual), " != expected value for this platform")
+                } else {
+                    // "Test failed: a != expected value for this platform"
+                    ::std::concat!("Test failed: ", ::std::stringify!($actual), " != expected value for this platform")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+}
+
+/// Tests that two slices are equal to each other (using [`PartialEq`]), reporting every index that differs.
+///
+/// If the lengths differ, only the lengths are shown. Otherwise every differing index is shown with
+/// both values, up to 10 indices. The cap can be changed with `cap = n`.
+///
+/// With the `diff` feature, the failure message also shows the length of the longest common
+/// subsequence of the two slices. It is not computed when the product of the lengths is over a million.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_slice_eq_all;
+/// let a = [1, 2, 3, 4, 5];
+/// let b = [1, 0, 3, 0, 0];
+/// test_slice_eq_all!(a, a).expect("This is true");
+/// println!("{:?}", test_slice_eq_all!(a, b, cap = 2, "after {} steps", 3));
+/// // prints:
+/// // [src/main.rs:5:1]: Test failed: a != b: after 3 steps
+/// // 3 differing indices (a != b):
+/// //   [1]: 2 != 0
+/// //   [3]: 4 != 0
+/// //   ... and 1 more
+/// ```
+#[macro_export]
+macro_rules! test_slice_eq_all {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::test_slice_eq_all!(@compare $left, $right, $crate::helpers::DEFAULT_DIFFERENCE_CAP, ::std::option::Option::None)
+    };
+    ($left:expr, $right:expr, cap = $cap:expr $(,)?) => {
+        $crate::test_slice_eq_all!(@compare $left, $right, $cap, ::std::option::Option::None)
+    };
+    ($left:expr, $right:expr, cap = $cap:expr, $($arg:tt)+) => {
+        $crate::test_slice_eq_all!(@compare $left, $right, $cap, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+    (@compare $left:expr, $right:expr, $cap:expr, $args:expr) => {{
+        match $crate::helpers::all_differences(&$left, &$right, $cap, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a != b"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                } else {
+                    // "Test failed: a != b"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::test_slice_eq_all!(@compare $left, $right, $crate::helpers::DEFAULT_DIFFERENCE_CAP, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that a byte slice is valid UTF-8.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_valid_utf8;
+/// let a = "héllo".as_bytes().to_vec();
+/// let b = &a[..2];
+/// test_valid_utf8!(a).expect("This is true");
+/// println!("{:?}", test_valid_utf8!(b, "a is {:?}", a));
+/// // prints:
+/// // [src/main.rs:5:1]: Test failed: b is not valid UTF-8: a is [104, 195, 169, 108, 108, 111]
+/// // invalid sequence at offset 1: [c3]
+/// // error: incomplete utf-8 byte sequence from index 1
+/// ```
+#[macro_export]
+macro_rules! test_valid_utf8 {
+    ($bytes:expr $(,)?) => {
+        $crate::test_valid_utf8!(@check $bytes, ::std::option::Option::None)
+    };
+    (@check $bytes:expr, $args:expr) => {{
+        match $crate::helpers::utf8_error(&$bytes) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a is not valid UTF-8"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($bytes), " is not valid UTF-8")
+                } else {
+                    // "Test failed: a is not valid UTF-8"
+                    ::std::concat!("Test failed: ", ::std::stringify!($bytes), " is not valid UTF-8")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($bytes:expr, $($arg:tt)+) => {
+        $crate::test_valid_utf8!(@check $bytes, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two expressions are equal to each other (using [`PartialEq`]), leaving zero-sized
+/// marker fields out of the failure message.
+///
+/// Fields holding a [`PhantomData`](std::marker::PhantomData) or [`PhantomPinned`](std::marker::PhantomPinned)
+/// are removed from the [`Debug`](std::fmt::Debug) output, as they never affect the comparison.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use std::marker::PhantomData;
+/// use test_eq::test_eq_ignore_zst;
+/// #[derive(Debug, PartialEq)]
+/// struct Meters<T> {
+///     value: u32,
+///     _unit: PhantomData<T>,
+/// }
+/// let a = Meters::<f32> { value: 1, _unit: PhantomData };
+/// let b = Meters::<f32> { value: 2, _unit: PhantomData };
+/// test_eq_ignore_zst!(a, a).expect("This is true");
+/// println!("{:?}", test_eq_ignore_zst!(a, b, "after {} steps", 3));
+/// // prints:
+/// // [src/main.rs:11:1]: Test failed: a != b: after 3 steps
+/// // a: Meters { value: 1 }
+/// // b: Meters { value: 2 }
+/// ```
+#[macro_export]
+macro_rules! test_eq_ignore_zst {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::test_eq_ignore_zst!(@compare $left, $right, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $args:expr) => {{
+        match $crate::helpers::ignore_zst_difference(&$left, &$right, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a != b"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                } else {
+                    // "Test failed: a != b"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::test_eq_ignore_zst!(@compare $left, $right, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that an unsigned integer is a power of two.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_power_of_two;
+/// let a = 64_u32;
+/// let b = 65_u32;
+/// test_power_of_two!(a).expect("This is true");
+/// println!("{:?}", test_power_of_two!(b, "alignment of {}", "buffer"));
+/// // prints:
+/// // [src/main.rs:5:1]: Test failed: b is not a power of two: alignment of buffer
+/// // b: 65 (0b1000001)
+/// ```
+#[macro_export]
+macro_rules! test_power_of_two {
+    ($value:expr $(,)?) => {
+        $crate::test_power_of_two!(@check $value, ::std::option::Option::None)
+    };
+    (@check $value:expr, $args:expr) => {{
+        match $crate::helpers::power_of_two($value, ::std::stringify!($value)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a is not a power of two"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($value), " is not a power of two")
+                } else {
+                    // "Test failed: a is not a power of two"
+                    ::std::concat!("Test failed: ", ::std::stringify!($value), " is not a power of two")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($value:expr, $($arg:tt)+) => {
+        $crate::test_power_of_two!(@check $value, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that an [`io::Result`](std::io::Result) is an error of the expected [`ErrorKind`](std::io::ErrorKind),
+/// regardless of the error message.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use std::io::{Error, ErrorKind};
+/// use test_eq::test_io_err_kind;
+/// let a: std::io::Result<()> = Err(Error::new(ErrorKind::NotFound, "config.toml is missing"));
+/// test_io_err_kind!(a, ErrorKind::NotFound).expect("This is true");
+/// println!("{:?}", test_io_err_kind!(a, ErrorKind::PermissionDenied, "opening {}", "config.toml"));
+/// // prints:
+/// // [src/main.rs:5:1]: Test failed: a is not an error of kind ErrorKind::PermissionDenied: opening config.toml
+/// // expected kind: PermissionDenied
+/// // actual kind: NotFound
+/// // error: config.toml is missing
+/// ```
+#[macro_export]
+macro_rules! test_io_err_kind {
+    ($result:expr, $kind:expr $(,)?) => {
+        $crate::test_io_err_kind!(@check $result, $kind, ::std::option::Option::None)
+    };
+    (@check $result:expr, $kind:expr, $args:expr) => {{
+        match $crate::helpers::io_error_kind(&$result, $kind, ::std::stringify!($result)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a is not an error of kind ErrorKind::NotFound"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($result), " is not an error of kind ", ::std::stringify!($kind))
+                } else {
+                    // "Test failed: a is not an error of kind ErrorKind::NotFound"
+                    ::std::concat!("Test failed: ", ::std::stringify!($result), " is not an error of kind ", ::std::stringify!($kind))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($result:expr, $kind:expr, $($arg:tt)+) => {
+        $crate::test_io_err_kind!(@check $result, $kind, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that running a block leaves a value unchanged (using [`PartialEq`]).
+///
+/// The value is cloned before the block runs, and compared with the clone afterwards.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_unchanged;
+/// let mut a = vec![3, 1, 2];
+/// test_unchanged!(a, { a.iter().max() }).expect("This is true");
+/// println!("{:?}", test_unchanged!(a, { a.sort_unstable() }, "sorting {} items", 3));
+/// // prints:
+/// // [src/main.rs:4:1]: Test failed: a was changed: sorting 3 items
+/// // before: [3, 1, 2]
+/// // after: [1, 2, 3]
+/// ```
+#[macro_export]
+macro_rules! test_unchanged {
+    ($value:expr, $operation:block $(,)?) => {
+        $crate::test_unchanged!(@check $value, $operation, ::std::option::Option::None)
+    };
+    (@check $value:expr, $operation:block, $args:expr) => {{
+        let before = ::std::clone::Clone::clone(&$value);
+        let _ = $operation;
+        if !($value == before) {
+            let message = if $crate::__LINE_INFO {
+                // "[src/main:2:5]: Test failed: a was changed"
+                ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($value), " was changed")
+            } else {
+                // "Test failed: a was changed"
+                ::std::concat!("Test failed: ", ::std::stringify!($value), " was changed")
+            };
+
+            ::std::result::Result::Err($crate::TestFailure::test_failed_two_idents(message, "before", &before, "after", &$value, $args))
+        } else {
+            ::std::result::Result::Ok(())
+        }
+    }};
+    ($value:expr, $operation:block, $($arg:tt)+) => {
+        $crate::test_unchanged!(@check $value, $operation, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that a list of lines is equal to the lines of a multiline string.
+///
+/// The string is split on `\n`, and every line is compared with the matching element of the list.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_lines_eq;
+/// let a = vec!["first".to_string(), "second".to_string()];
+/// test_lines_eq!(a, "first\nsecond").expect("This is true");
+/// println!("{:?}", test_lines_eq!(a, "first\nsecond\nthird", "output of {}", "ls"));
+/// // prints:
+/// // [src/main.rs:4:1]: Test failed: a != "first\nsecond\nthird": output of ls
+/// // line counts differ: a has 2 lines, "first\nsecond\nthird" has 3
+/// // first missing line: "third"
+/// ```
+#[macro_export]
+macro_rules! test_lines_eq {
+    ($lines:expr, $expected:expr $(,)?) => {
+        $crate::test_lines_eq!(@compare $lines, $expected, ::std::option::Option::None)
+    };
+    (@compare $lines:expr, $expected:expr, $args:expr) => {{
+        match $crate::helpers::lines_difference(&$lines, $expected, ::std::stringify!($lines), ::std::stringify!($expected)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a != b"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($lines), " != ", ::std::stringify!($expected))
+                } else {
+                    // "Test failed: a != b"
+                    ::std::concat!("Test failed: ", ::std::stringify!($lines), " != ", ::std::stringify!($expected))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($lines:expr, $expected:expr, $($arg:tt)+) => {
+        $crate::test_lines_eq!(@compare $lines, $expected, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that a pointer or reference is aligned to the given number of bytes.
+///
+/// The alignment must be a power of two.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_aligned;
+/// let a = [0_u64; 2];
+/// let b = a.as_ptr().cast::<u8>().wrapping_add(1);
+/// test_aligned!(&a, 8).expect("This is true");
+/// println!("{:?}", test_aligned!(b, 8, "buffer of {} bytes", 16));
+/// // prints:
+/// // [src/main.rs:5:1]: Test failed: b is not aligned to 8 bytes: buffer of 16 bytes
+/// // b: 0x7ffc1c9d1b61
+/// // required alignment: 8
+/// ```
+#[macro_export]
+macro_rules! test_aligned {
+    ($ptr:expr, $align:expr $(,)?) => {
+        $crate::test_aligned!(@check $ptr, $align, ::std::option::Option::None)
+    };
+    (@check $ptr:expr, $align:expr, $args:expr) => {{
+        match $crate::helpers::alignment_error(&$ptr, $align, ::std::stringify!($ptr)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a is not aligned to 8 bytes"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($ptr), " is not aligned to ", ::std::stringify!($align), " bytes")
+                } else {
+                    // "Test failed: a is not aligned to 8 bytes"
+                    ::std::concat!("Test failed: ", ::std::stringify!($ptr), " is not aligned to ", ::std::stringify!($align), " bytes")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($ptr:expr, $align:expr, $($arg:tt)+) => {
+        $crate::test_aligned!(@check $ptr, $align, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two expressions have the same string representation (using [`Display`](std::fmt::Display)).
+///
+/// This is useful for values that have a canonical string form, but don't implement [`PartialEq`]
+/// or implement it differently.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+/// use test_eq::test_eq_str_repr;
+/// let a = IpAddr::V4(Ipv4Addr::LOCALHOST);
+/// let b = "127.0.0.1";
+/// let c = IpAddr::V6(Ipv6Addr::LOCALHOST);
+/// test_eq_str_repr!(a, b).expect("This is true");
+/// println!("{:?}", test_eq_str_repr!(a, c, "binding to {}", "localhost"));
+/// // prints:
+/// // [src/main.rs:7:1]: Test failed: a != c: binding to localhost
+/// // a: "127.0.0.1"
+/// // c: "::1"
+/// ```
+#[macro_export]
+macro_rules! test_eq_str_repr {
+    ($left:expr, $right:expr $(,)?) => {{
+        match (::std::string::ToString::to_string(&$left), ::std::string::ToString::to_string(&$right)) {
+            (left_val, right_val) => {
+                if !(left_val == right_val) {
+                    let message = if $crate::__LINE_INFO {
+                        // "[src/main:2:5]: Test failed: a != b"
+                        ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                    } else {
+                        // "Test failed: a != b"
+                        ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                    };
+
+                    ::std::result::Result::Err($crate::TestFailure::test_failed_two_idents(message, ::std::stringify!($left), &left_val, ::std::stringify!($right), &right_val, ::std::option::Option::None))
+                } else {
+                    ::std::result::Result::Ok(())
+                }
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {{
+        match (::std::string::ToString::to_string(&$left), ::std::string::ToString::to_string(&$right)) {
+            (left_val, right_val) => {
+                if !(left_val == right_val) {
+                    let message = if $crate::__LINE_INFO {
+                        // "[src/main:2:5]: Test failed: a != b"
+                        ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                    } else {
+                        // "Test failed: a != b"
+                        ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                    };
+
+                    ::std::result::Result::Err($crate::TestFailure::test_failed_two_idents(message, ::std::stringify!($left), &left_val, ::std::stringify!($right), &right_val, ::std::option::Option::Some(::std::format_args!($($arg)+))))
+                } else {
+                    ::std::result::Result::Ok(())
+                }
+            }
+        }
+    }};
+}
+
+/// Tests that a slice or string reads the same forwards and backwards (using [`PartialEq`]).
+///
+/// Strings are compared by [`char`].
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_palindrome;
+/// let a = [1, 2, 3, 2, 1];
+/// let b = "racecars";
+/// test_palindrome!(a).expect("This is true");
+/// println!("{:?}", test_palindrome!(b, "a {} word", "long"));
+/// // prints:
+/// // [src/main.rs:5:1]: Test failed: b is not a palindrome: a long word
+/// // first asymmetry at indices 0 and 7: 'r' != 's'
+/// ```
+#[macro_export]
+macro_rules! test_palindrome {
+    ($value:expr $(,)?) => {
+        $crate::test_palindrome!(@check $value, ::std::option::Option::None)
+    };
+    (@check $value:expr, $args:expr) => {{
+        match $crate::helpers::palindrome_error(&$value) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a is not a palindrome"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($value), " is not a palindrome")
+                } else {
+                    // "Test failed: a is not a palindrome"
+                    ::std::concat!("Test failed: ", ::std::stringify!($value), " is not a palindrome")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($value:expr, $($arg:tt)+) => {
+        $crate::test_palindrome!(@check $value, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two maps contain the same values (using [`Eq`] and [`Hash`](std::hash::Hash)),
+/// ignoring the keys.
+///
+/// Values that occur multiple times must occur the same number of times in both maps. Both
+/// [`HashMap`](std::collections::HashMap) and [`BTreeMap`](std::collections::BTreeMap) are supported.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use std::collections::BTreeMap;
+/// use test_eq::test_values_eq_unordered;
+/// let a = BTreeMap::from([("alice", 1), ("bob", 2)]);
+/// let b = BTreeMap::from([("carol", 2), ("dave", 1)]);
+/// let c = BTreeMap::from([("alice", 1), ("bob", 3)]);
+/// test_values_eq_unordered!(a, b).expect("This is true");
+/// println!("{:?}", test_values_eq_unordered!(a, c, "scores of {} players", 2));
+/// // prints:
+/// // [src/main.rs:7:1]: Test failed: a.values() != c.values() (unordered): scores of 2 players
+/// // only in a: [2]
+/// // only in c: [3]
+/// ```
+#[macro_export]
+macro_rules! test_values_eq_unordered {
+    ($left:expr, $right:expr $(,)?) => {{
+        match $crate::helpers::multiset_difference($crate::helpers::MapValues::map_values(&$left), $crate::helpers::MapValues::map_values(&$right), ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a.values() != b.values() (unordered)"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), ".values() != ", ::std::stringify!($right), ".values() (unordered)")
+                } else {
+                    // "Test failed: a.values() != b.values() (unordered)"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), ".values() != ", ::std::stringify!($right), ".values() (unordered)")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, ::std::option::Option::None))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {{
+        match $crate::helpers::multiset_difference($crate::helpers::MapValues::map_values(&$left), $crate::helpers::MapValues::map_values(&$right), ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a.values() != b.values() (unordered)"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), ".values() != ", ::std::stringify!($right), ".values() (unordered)")
+                } else {
+                    // "Test failed: a.values() != b.values() (unordered)"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), ".values() != ", ::std::stringify!($right), ".values() (unordered)")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, ::std::option::Option::Some(::std::format_args!($($arg)+))))
+            }
+        }
+    }};
+}
+
+/// Tests that a string only contains characters from the allowed set.
+///
+/// The position of a character is counted in [`char`]s, not bytes.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_charset;
+/// let a = "deadbeef";
+/// let b = String::from("c0ffee_g");
+/// test_charset!(a, "0123456789abcdef").expect("This is true");
+/// println!("{:?}", test_charset!(b, "0123456789abcdef", "parsing {}", "hex"));
+/// // prints:
+/// // [src/main.rs:5:1]: Test failed: b contains characters outside of "0123456789abcdef": parsing hex
+/// // disallowed character '_' at position 6 (byte offset 6)
+/// // b: "c0ffee_g"
+/// // allowed: "0123456789abcdef"
+/// ```
+#[macro_export]
+macro_rules! test_charset {
+    ($value:expr, $allowed:expr $(,)?) => {
+        $crate::test_charset!(@check $value, $allowed, ::std::option::Option::None)
+    };
+    (@check $value:expr, $allowed:expr, $args:expr) => {{
+        match $crate::helpers::charset_error(&$value, $allowed, ::std::stringify!($value)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a contains characters outside of "abc""
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($value), " contains characters outside of ", ::std::stringify!($allowed))
+                } else {
+                    // "Test failed: a contains characters outside of "abc""
+                    ::std::concat!("Test failed: ", ::std::stringify!($value), " contains characters outside of ", ::std::stringify!($allowed))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($value:expr, $allowed:expr, $($arg:tt)+) => {
+        $crate::test_charset!(@check $value, $allowed, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two iterators yield the same items (using [`PartialEq`]), showing the items around
+/// the first difference.
+///
+/// By default two items before and after the first difference are shown from both iterators,
+/// which makes it easy to tell an inserted item from a changed item. The number of items can be
+/// changed with `context = n`.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_iter_eq;
+/// let a = [1, 2, 3, 4, 5];
+/// let b = [1, 2, 9, 3, 4, 5];
+/// test_iter_eq!(a, 1..=5).expect("This is true");
+/// println!("{:?}", test_iter_eq!(a, b, context = 1, "after {} steps", 3));
+/// // prints:
+/// // [src/main.rs:5:1]: Test failed: a != b: after 3 steps
+/// // first difference at index 2: 3 != 9
+/// // a[1..4]: [2, 3, 4]
+/// // b[1..4]: [2, 9, 3]
+/// ```
+#[macro_export]
+macro_rules! test_iter_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::test_iter_eq!(@compare $left, $right, $crate::helpers::DEFAULT_CONTEXT, ::std::option::Option::None)
+    };
+    ($left:expr, $right:expr, context = $context:expr $(,)?) => {
+        $crate::test_iter_eq!(@compare $left, $right, $context, ::std::option::Option::None)
+    };
+    ($left:expr, $right:expr, context = $context:expr, $($arg:tt)+) => {
+        $crate::test_iter_eq!(@compare $left, $right, $context, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+    (@compare $left:expr, $right:expr, $context:expr, $args:expr) => {{
+        match $crate::helpers::iter_difference_context($left, $right, $context, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a != b"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                } else {
+                    // "Test failed: a != b"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::test_iter_eq!(@compare $left, $right, $crate::helpers::DEFAULT_CONTEXT, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two JSON strings contain the same JSON, ignoring whitespace and the order of keys.
+///
+/// Both strings are parsed into a [`serde_json::Value`](https://docs.rs/serde_json/latest/serde_json/enum.Value.html).
+/// If either string is not valid JSON the test fails with the parse error.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// This macro requires the `serde_json` feature.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_json_str_eq;
+/// let a = r#"{"name": "alice", "roles": ["admin", "user"]}"#;
+/// let b = r#"{ "roles": [ "admin", "user" ], "name": "alice" }"#;
+/// let c = r#"{"name": "alice", "roles": ["admin"]}"#;
+/// test_json_str_eq!(a, b).expect("This is true");
+/// println!("{:?}", test_json_str_eq!(a, c, "user {}", 1));
+/// // prints:
+/// // [src/main.rs:6:1]: Test failed: a != c: user 1
+/// // first difference at $.roles[1]
+/// // a: "user"
+/// // c: <missing>
+/// ```
+#[cfg(feature = "serde_json")]
+#[macro_export]
+macro_rules! test_json_str_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::test_json_str_eq!(@compare $left, $right, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $args:expr) => {{
+        match $crate::helpers::json_str_difference(::std::convert::AsRef::<str>::as_ref(&$left), ::std::convert::AsRef::<str>::as_ref(&$right), ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a != b"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                } else {
+                    // "Test failed: a != b"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::test_json_str_eq!(@compare $left, $right, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two expressions are equal to each other after projecting them with a function
+/// (using [`PartialEq`] on the results).
+///
+/// Both expressions must have the same type, and the function gets a reference to each of them.
+/// This makes it possible to compare values that don't implement [`PartialEq`], like trait objects.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_eq_via;
+/// trait Shape {
+///     fn area(&self) -> u32;
+/// }
+/// struct Square(u32);
+/// impl Shape for Square {
+///     fn area(&self) -> u32 { self.0 * self.0 }
+/// }
+/// struct Rectangle(u32, u32);
+/// impl Shape for Rectangle {
+///     fn area(&self) -> u32 { self.0 * self.1 }
+/// }
+/// let a: Box<dyn Shape> = Box::new(Square(4));
+/// let b: Box<dyn Shape> = Box::new(Rectangle(2, 8));
+/// let c: Box<dyn Shape> = Box::new(Rectangle(3, 5));
+/// test_eq_via!(a, b, |shape| shape.area()).expect("This is true");
+/// println!("{:?}", test_eq_via!(a, c, |shape| shape.area(), "{} shapes", 2));
+/// // prints:
+/// // [src/main.rs:16:1]: Test failed: a != c (via |shape| shape.area()): 2 shapes
+/// // a: 16
+/// // c: 15
+/// ```
+#[macro_export]
+macro_rules! test_eq_via {
+    ($left:expr, $right:expr, $via:expr $(,)?) => {
+        $crate::test_eq_via!(@compare $left, $right, $via, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $via:expr, $args:expr) => {{
+        match $crate::helpers::project(&$left, &$right, $via) {
+            (left_val, right_val) => {
+                if !(left_val == right_val) {
+                    let message = if $crate::__LINE_INFO {
+                        // "[src/main:2:5]: Test failed: a != b (via |x| x.key())"
+                        ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right), " (via ", ::std::stringify!($via), ")")
+                    } else {
+                        // "Test failed: a != b (via |x| x.key())"
+                        ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right), " (via ", ::std::stringify!($via), ")")
+                    };
+
+                    ::std::result::Result::Err($crate::TestFailure::test_failed_two_idents(message, ::std::stringify!($left), &left_val, ::std::stringify!($right), &right_val, $args))
+                } else {
+                    ::std::result::Result::Ok(())
+                }
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $via:expr, $($arg:tt)+) => {
+        $crate::test_eq_via!(@compare $left, $right, $via, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that a closure returns the same result (using [`PartialEq`]) every time it is called.
+///
+/// The closure is called the given number of times, and every result is compared with the first.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_deterministic;
+/// let a = [3, 1, 2];
+/// test_deterministic!(10, || a.iter().sum::<i32>()).expect("This is true");
+/// let mut calls = 0;
+/// println!("{:?}", test_deterministic!(3, || { calls += 1; calls }, "counting {}", "calls"));
+/// // prints:
+/// // [src/main.rs:6:1]: Test failed: || { calls += 1; calls } is not deterministic over 3 runs: counting calls
+/// // run 1 differs from run 0
+/// // run 0: 1
+/// // run 1: 2
+/// ```
+#[macro_export]
+macro_rules! test_deterministic {
+    ($runs:expr, $compute:expr $(,)?) => {
+        $crate::test_deterministic!(@check $runs, $compute, ::std::option::Option::None)
+    };
+    (@check $runs:expr, $compute:expr, $args:expr) => {{
+        match $crate::helpers::deterministic_error($runs, $compute) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: || compute() is not deterministic over 3 runs"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($compute), " is not deterministic over ", ::std::stringify!($runs), " runs")
+                } else {
+                    // "Test failed: || compute() is not deterministic over 3 runs"
+                    ::std::concat!("Test failed: ", ::std::stringify!($compute), " is not deterministic over ", ::std::stringify!($runs), " runs")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($runs:expr, $compute:expr, $($arg:tt)+) => {
+        $crate::test_deterministic!(@check $runs, $compute, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two strings are equal to each other after collapsing whitespace.
+///
+/// Every run of whitespace is replaced with a single space, and leading and trailing whitespace
+/// is removed.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_eq_ws_collapsed;
+/// let a = "Hello,   world!\n";
+/// let b = " Hello, world!";
+/// let c = String::from("Hello,\tWorld!");
+/// test_eq_ws_collapsed!(a, b).expect("This is true");
+/// println!("{:?}", test_eq_ws_collapsed!(a, c, "greeting {}", 1));
+/// // prints:
+/// // [src/main.rs:6:1]: Test failed: a != c (whitespace collapsed): greeting 1
+/// // a: "Hello,   world!\n"
+/// // c: "Hello,\tWorld!"
+/// // collapsed a: "Hello, world!"
+/// // collapsed c: "Hello, World!"
+/// ```
+#[macro_export]
+macro_rules! test_eq_ws_collapsed {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::test_eq_ws_collapsed!(@compare $left, $right, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $args:expr) => {{
+        match $crate::helpers::ws_collapsed_difference(&$left, &$right, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a != b (whitespace collapsed)"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right), " (whitespace collapsed)")
+                } else {
+                    // "Test failed: a != b (whitespace collapsed)"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right), " (whitespace collapsed)")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::test_eq_ws_collapsed!(@compare $left, $right, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two directories contain files with the same relative paths, ignoring the contents
+/// of the files.
+///
+/// Both directories are searched recursively. Empty directories are ignored. If a directory can't
+/// be read, the test fails with the IO error.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```no_run
+/// use test_eq::test_dir_eq;
+/// let a = "target/expected";
+/// let b = "target/output";
+/// println!("{:?}", test_dir_eq!(a, b, "after {} builds", 2));
+/// // prints:
+/// // [src/main.rs:4:1]: Test failed: a != b: after 2 builds
+/// // only in a: ["docs/index.html"]
+/// // only in b: ["docs/index.htm", "tmp/build.log"]
+/// ```
+#[macro_export]
+macro_rules! test_dir_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::test_dir_eq!(@compare $left, $right, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $args:expr) => {{
+        match $crate::helpers::dir_difference(&$left, &$right, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a != b"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                } else {
+                    // "Test failed: a != b"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::test_dir_eq!(@compare $left, $right, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two float vectors are close to each other, by the Euclidean norm of their difference.
+///
+/// The vectors must have the same length, and the norm `sqrt(sum((a[i] - b[i])^2))` must not
+/// exceed the tolerance. A `NaN` norm always fails.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_vec_norm_close;
+/// let a = [1.0, 2.0, 3.0];
+/// let b = [1.0, 2.1, 3.0];
+/// let c = [1.0, 2.0, 5.0];
+/// test_vec_norm_close!(a, b, 0.2).expect("This is true");
+/// println!("{:?}", test_vec_norm_close!(a, c, 0.2, "velocity at step {}", 3));
+/// // prints:
+/// // [src/main.rs:6:1]: Test failed: a is not close to c: velocity at step 3
+/// // norm of difference: 2 (allowed: 0.2)
+/// // a: [1.0, 2.0, 3.0]
+/// // c: [1.0, 2.0, 5.0]
+/// ```
+#[macro_export]
+macro_rules! test_vec_norm_close {
+    ($left:expr, $right:expr, $tolerance:expr $(,)?) => {
+        $crate::test_vec_norm_close!(@compare $left, $right, $tolerance, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $tolerance:expr, $args:expr) => {{
+        match $crate::helpers::norm_difference(&$left, &$right, $tolerance, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a is not close to b"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " is not close to ", ::std::stringify!($right))
+                } else {
+                    // "Test failed: a is not close to b"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " is not close to ", ::std::stringify!($right))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $tolerance:expr, $($arg:tt)+) => {
+        $crate::test_vec_norm_close!(@compare $left, $right, $tolerance, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that the [`PartialOrd`] and [`Ord`] implementations of a type agree on two values.
+///
+/// `a.partial_cmp(&b)` must be `Some(a.cmp(&b))`. This is useful for testing types with a
+/// hand-written implementation of these traits.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use std::cmp::Ordering;
+/// use test_eq::test_cmp_consistent;
+/// #[derive(Debug, PartialEq, Eq)]
+/// struct Version(&'static str);
+/// impl Ord for Version {
+///     fn cmp(&self, other: &Self) -> Ordering {
+///         self.0.len().cmp(&other.0.len()).then(self.0.cmp(other.0))
+///     }
+/// }
+/// impl PartialOrd for Version {
+///     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+///         // Wrong, this compares "10" and "9" as strings
+///         self.0.partial_cmp(other.0)
+///     }
+/// }
+/// let a = Version("10");
+/// let b = Version("9");
+/// test_cmp_consistent!(a, a).expect("This is true");
+/// println!("{:?}", test_cmp_consistent!(a, b, "comparing {} versions", 2));
+/// // prints:
+/// // [src/main.rs:18:1]: Test failed: a.partial_cmp(&b) is inconsistent with a.cmp(&b): comparing 2 versions
+/// // partial_cmp returned Some(Less), but cmp returned Greater
+/// // a: Version("10")
+/// // b: Version("9")
+/// ```
+#[macro_export]
+macro_rules! test_cmp_consistent {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::test_cmp_consistent!(@compare $left, $right, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $args:expr) => {{
+        match $crate::helpers::cmp_consistency(&$left, &$right, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a.partial_cmp(&b) is inconsistent with a.cmp(&b)"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), ".partial_cmp(&", ::std::stringify!($right), ") is inconsistent with ", ::std::stringify!($left), ".cmp(&", ::std::stringify!($right), ")")
+                } else {
+                    // "Test failed: a.partial_cmp(&b) is inconsistent with a.cmp(&b)"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), ".partial_cmp(&", ::std::stringify!($right), ") is inconsistent with ", ::std::stringify!($left), ".cmp(&", ::std::stringify!($right), ")")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::test_cmp_consistent!(@compare $left, $right, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that a string parses (using [`FromStr`](std::str::FromStr)) to a value equal to the
+/// expected value (using [`PartialEq`]).
+///
+/// The string is parsed into the type of the expected value. Parse errors are reported separately
+/// from values that parse but aren't equal.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_parse_eq;
+/// let a = "1.5";
+/// test_parse_eq!(a, 1.5_f64).expect("This is true");
+/// println!("{:?}", test_parse_eq!(a, 2.5_f64, "parsing {}", "floats"));
+/// // prints:
+/// // [src/main.rs:4:1]: Test failed: a.parse() != 2.5_f64: parsing floats
+/// // parsed: 1.5
+/// // 2.5_f64: 2.5
+/// // input: "1.5"
+/// ```
+#[macro_export]
+macro_rules! test_parse_eq {
+    ($input:expr, $expected:expr $(,)?) => {
+        $crate::test_parse_eq!(@compare $input, $expected, ::std::option::Option::None)
+    };
+    (@compare $input:expr, $expected:expr, $args:expr) => {{
+        match $crate::helpers::parse_difference(&$input, &$expected, ::std::stringify!($expected)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a.parse() != b"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($input), ".parse() != ", ::std::stringify!($expected))
+                } else {
+                    // "Test failed: a.parse() != b"
+                    ::std::concat!("Test failed: ", ::std::stringify!($input), ".parse() != ", ::std::stringify!($expected))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($input:expr, $expected:expr, $($arg:tt)+) => {
+        $crate::test_parse_eq!(@compare $input, $expected, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that an iterator has no items left.
+///
+/// This calls [`next`](Iterator::next) once, so on failure the remaining item is consumed.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_exhausted;
+/// let mut a = [1, 2, 3].into_iter();
+/// a.next();
+/// a.next();
+/// println!("{:?}", test_exhausted!(a, "after {} items", 2));
+/// // prints:
+/// // [src/main.rs:5:1]: Test failed: a is not exhausted: after 2 items
+/// // next item: 3
+/// test_exhausted!(a).expect("This is true");
+/// ```
+#[macro_export]
+macro_rules! test_exhausted {
+    ($iter:expr $(,)?) => {
+        $crate::test_exhausted!(@check $iter, ::std::option::Option::None)
+    };
+    (@check $iter:expr, $args:expr) => {{
+        match ::std::iter::Iterator::next(&mut $iter) {
+            ::std::option::Option::None => ::std::result::Result::Ok(()),
+            ::std::option::Option::Some(item) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a is not exhausted"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($iter), " is not exhausted")
+                } else {
+                    // "Test failed: a is not exhausted"
+                    ::std::concat!("Test failed: ", ::std::stringify!($iter), " is not exhausted")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_one_ident(message, "next item", &item, $args))
+            }
+        }
+    }};
+    ($iter:expr, $($arg:tt)+) => {
+        $crate::test_exhausted!(@check $iter, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that every element of a slice is equal to a value (using [`PartialEq`]).
+///
+/// An empty slice always passes.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_all_equal;
+/// let mut a = vec![0; 8];
+/// test_all_equal!(a, 0).expect("This is true");
+/// a[5] = 3;
+/// println!("{:?}", test_all_equal!(a, 0, "after {} writes", 1));
+/// // prints:
+/// // [src/main.rs:5:1]: Test failed: a is not all equal to 0: after 1 writes
+/// // first difference at index 5
+/// // a[5]: 3
+/// // 0: 0
+/// ```
+#[macro_export]
+macro_rules! test_all_equal {
+    ($slice:expr, $value:expr $(,)?) => {
+        $crate::test_all_equal!(@check $slice, $value, ::std::option::Option::None)
+    };
+    (@check $slice:expr, $value:expr, $args:expr) => {{
+        match $crate::helpers::all_equal_error(&$slice, &$value, ::std::stringify!($slice), ::std::stringify!($value)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a is not all equal to b"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($slice), " is not all equal to ", ::std::stringify!($value))
+                } else {
+                    // "Test failed: a is not all equal to b"
+                    ::std::concat!("Test failed: ", ::std::stringify!($slice), " is not all equal to ", ::std::stringify!($value))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($slice:expr, $value:expr, $($arg:tt)+) => {
+        $crate::test_all_equal!(@check $slice, $value, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two floats are equal to each other after rounding both to a number of decimal places.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_eq_rounded;
+/// let a = 10.0 / 3.0;
+/// let b = 3.333;
+/// test_eq_rounded!(a, b, 3).expect("This is true");
+/// println!("{:?}", test_eq_rounded!(a, b, 4, "price in {}", "euro"));
+/// // prints:
+/// // [src/main.rs:5:1]: Test failed: a != b (rounded to 4 decimals): price in euro
+/// // a: 3.3333333333333335 (rounded: 3.3333)
+/// // b: 3.333 (rounded: 3.3330)
+/// ```
+#[macro_export]
+macro_rules! test_eq_rounded {
+    ($left:expr, $right:expr, $decimals:expr $(,)?) => {
+        $crate::test_eq_rounded!(@compare $left, $right, $decimals, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $decimals:expr, $args:expr) => {{
+        match $crate::helpers::rounded_difference($left, $right, $decimals, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a != b (rounded to 3 decimals)"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right), " (rounded to ", ::std::stringify!($decimals), " decimals)")
+                } else {
+                    // "Test failed: a != b (rounded to 3 decimals)"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right), " (rounded to ", ::std::stringify!($decimals), " decimals)")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $decimals:expr, $($arg:tt)+) => {
+        $crate::test_eq_rounded!(@compare $left, $right, $decimals, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that running a block increases the value of an expression (using [`PartialOrd`]).
+///
+/// The value is copied before the block runs, and must be strictly greater afterwards.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_increased;
+/// let mut a = 5;
+/// test_increased!(a, { a += 1; }).expect("This is true");
+/// println!("{:?}", test_increased!(a, { a -= 1; }, "after {} steps", 2));
+/// // prints:
+/// // [src/main.rs:4:1]: Test failed: a did not increase: after 2 steps
+/// // before: 6
+/// // after: 5
+/// ```
+#[macro_export]
+macro_rules! test_increased {
+    ($value:expr, $operation:block $(,)?) => {
+        $crate::test_increased!(@check $value, $operation, ::std::option::Option::None)
+    };
+    (@check $value:expr, $operation:block, $args:expr) => {{
+        let before = $value;
+        let _ = $operation;
+        let after = $value;
+        if after > before {
+            ::std::result::Result::Ok(())
+        } else {
+            let message = if $crate::__LINE_INFO {
+                // "[src/main:2:5]: Test failed: a did not increase"
+                ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($value), " did not increase")
+            } else {
+                // "Test failed: a did not increase"
+                ::std::concat!("Test failed: ", ::std::stringify!($value), " did not increase")
+            };
+
+            ::std::result::Result::Err($crate::TestFailure::test_failed_two_idents(message, "before", &before, "after", &after, $args))
+        }
+    }};
+    ($value:expr, $operation:block, $($arg:tt)+) => {
+        $crate::test_increased!(@check $value, $operation, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that running a block decreases the value of an expression (using [`PartialOrd`]).
+///
+/// The value is copied before the block runs, and must be strictly smaller afterwards.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_decreased;
+/// let mut a = 5;
+/// test_decreased!(a, { a -= 1; }).expect("This is true");
+/// println!("{:?}", test_decreased!(a, { a += 1; }, "after {} steps", 2));
+/// // prints:
+/// // [src/main.rs:4:1]: Test failed: a did not decrease: after 2 steps
+/// // before: 4
+/// // after: 5
+/// ```
+#[macro_export]
+macro_rules! test_decreased {
+    ($value:expr, $operation:block $(,)?) => {
+        $crate::test_decreased!(@check $value, $operation, ::std::option::Option::None)
+    };
+    (@check $value:expr, $operation:block, $args:expr) => {{
+        let before = $value;
+        let _ = $operation;
+        let after = $value;
+        if after < before {
+            ::std::result::Result::Ok(())
+        } else {
+            let message = if $crate::__LINE_INFO {
+                // "[src/main:2:5]: Test failed: a did not decrease"
+                ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($value), " did not decrease")
+            } else {
+                // "Test failed: a did not decrease"
+                ::std::concat!("Test failed: ", ::std::stringify!($value), " did not decrease")
+            };
+
+            ::std::result::Result::Err($crate::TestFailure::test_failed_two_idents(message, "before", &before, "after", &after, $args))
+        }
+    }};
+    ($value:expr, $operation:block, $($arg:tt)+) => {
+        $crate::test_decreased!(@check $value, $operation, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two enum values are the same variant, ignoring the data inside the variants.
+///
+/// The variants are compared with [`std::mem::discriminant`], the data is never compared. On failure
+/// both values are shown using [`Debug`](std::fmt::Debug).
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_same_variant;
+/// let a: Result<u8, &str> = Ok(1);
+/// let b: Result<u8, &str> = Ok(2);
+/// let c: Result<u8, &str> = Err("overflow");
+/// test_same_variant!(a, b).expect("This is true");
+/// println!("{:?}", test_same_variant!(a, c, "parsing {}", "bytes"));
+/// // prints:
+/// // [src/main.rs:6:1]: Test failed: a and c are different variants: parsing bytes
+/// // a: Ok(1)
+/// // c: Err("overflow")
+/// ```
+#[macro_export]
+macro_rules! test_same_variant {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::test_same_variant!(@compare $left, $right, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $args:expr) => {{
+        match (&$left, &$right) {
+            (left_val, right_val) => {
+                if ::std::mem::discriminant(left_val) == ::std::mem::discriminant(right_val) {
+                    ::std::result::Result::Ok(())
+                } else {
+                    let message = if $crate::__LINE_INFO {
+                        // "[src/main:2:5]: Test failed: a and b are different variants"
+                        ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " and ", ::std::stringify!($right), " are different variants")
+                    } else {
+                        // "Test failed: a and b are different variants"
+                        ::std::concat!("Test failed: ", ::std::stringify!($left), " and ", ::std::stringify!($right), " are different variants")
+                    };
+
+                    ::std::result::Result::Err($crate::TestFailure::test_failed_two_idents(message, ::std::stringify!($left), left_val, ::std::stringify!($right), right_val, $args))
+                }
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::test_same_variant!(@compare $left, $right, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two byte slices are equal to each other, comparing them in constant time.
+///
+/// This is meant for secrets like keys and MACs. The comparison takes the same time no matter
+/// where the slices differ, and the failure message only shows the lengths, never the bytes.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// This macro requires the `constant-time` feature.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_ct_eq;
+/// let a = [0x13_u8; 32];
+/// let b = [0x13_u8; 32];
+/// let c = [0x37_u8; 32];
+/// test_ct_eq!(a, b).expect("This is true");
+/// println!("{:?}", test_ct_eq!(a, c, "key {}", 1));
+/// // prints:
+/// // [src/main.rs:6:1]: Test failed: a != c: key 1
+/// // the contents differ, the bytes are not shown
+/// // a: 32 bytes
+/// // c: 32 bytes
+/// ```
+#[cfg(feature = "constant-time")]
+#[macro_export]
+macro_rules! test_ct_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::test_ct_eq!(@compare $left, $right, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $args:expr) => {{
+        match $crate::helpers::ct_difference(&$left, &$right, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a != b"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                } else {
+                    // "Test failed: a != b"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::test_ct_eq!(@compare $left, $right, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that a [`Result`] is [`Ok`] and that the value inside satisfies a predicate.
+///
+/// The predicate gets a reference to the value. If the [`Result`] is an [`Err`], the error is shown.
+/// If the value does not satisfy the predicate, the value is shown instead.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_ok_satisfies;
+/// let a: Result<i32, String> = Ok(5);
+/// let b: Result<i32, String> = Ok(-3);
+/// test_ok_satisfies!(a, |v| *v > 0).expect("This is true");
+/// println!("{:?}", test_ok_satisfies!(b, |v| *v > 0, "for input {}", 2));
+/// // prints:
+/// // [src/main.rs:5:1]: Test failed: b does not satisfy |v| *v > 0: for input 2
+/// // value: -3
+/// ```
+#[macro_export]
+macro_rules! test_ok_satisfies {
+    ($result:expr, $predicate:expr $(,)?) => {
+        $crate::test_ok_satisfies!(@check $result, $predicate, ::std::option::Option::None)
+    };
+    (@check $result:expr, $predicate:expr, $args:expr) => {{
+        match &$result {
+            ::std::result::Result::Ok(value) => {
+                if $crate::helpers::satisfies(value, $predicate) {
+                    ::std::result::Result::Ok(())
+                } else {
+                    let message = if $crate::__LINE_INFO {
+                        // "[src/main:2:5]: Test failed: parse(a) does not satisfy |v| *v > 0"
+                        ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($result), " does not satisfy ", ::std::stringify!($predicate))
+                    } else {
+                        // "Test failed: parse(a) does not satisfy |v| *v > 0"
+                        ::std::concat!("Test failed: ", ::std::stringify!($result), " does not satisfy ", ::std::stringify!($predicate))
+                    };
+
+                    ::std::result::Result::Err($crate::TestFailure::test_failed_one_ident(message, "value", value, $args))
+                }
+            }
+            ::std::result::Result::Err(err) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: parse(a) is not Ok"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($result), " is not Ok")
+                } else {
+                    // "Test failed: parse(a) is not Ok"
+                    ::std::concat!("Test failed: ", ::std::stringify!($result), " is not Ok")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_one_ident(message, "error", err, $args))
+            }
+        }
+    }};
+    ($result:expr, $predicate:expr, $($arg:tt)+) => {
+        $crate::test_ok_satisfies!(@check $result, $predicate, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two images are equal to each other, allowing every pixel to differ by a tolerance.
+///
+/// Both images must be byte slices (or anything that implements <code>[AsRef]<\[u8\]></code>) of the
+/// same length, where every byte is a pixel (or a channel of a pixel). On failure, the first
+/// offending pixel is shown with both values, together with the number of offending pixels.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_image_close;
+/// let a = [0_u8, 128, 255, 64];
+/// let b = [1_u8, 127, 255, 64];
+/// let c = [0_u8, 140, 255, 90];
+/// test_image_close!(a, b, 2).expect("This is true");
+/// println!("{:?}", test_image_close!(a, c, 2, "for frame {}", 7));
+/// // prints:
+/// // [src/main.rs:6:1]: Test failed: a is not close to c (tolerance 2): for frame 7
+/// // 2 of 4 pixels differ by more than 2, the first at index 1
+/// // a: 128
+/// // c: 140
+/// ```
+#[macro_export]
+macro_rules! test_image_close {
+    ($left:expr, $right:expr, $tolerance:expr $(,)?) => {
+        $crate::test_image_close!(@compare $left, $right, $tolerance, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $tolerance:expr, $args:expr) => {{
+        match $crate::helpers::image_difference(&$left, &$right, $tolerance, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a is not close to b (tolerance 2)"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " is not close to ", ::std::stringify!($right), " (tolerance ", ::std::stringify!($tolerance), ")")
+                } else {
+                    // "Test failed: a is not close to b (tolerance 2)"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " is not close to ", ::std::stringify!($right), " (tolerance ", ::std::stringify!($tolerance), ")")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $tolerance:expr, $($arg:tt)+) => {
+        $crate::test_image_close!(@compare $left, $right, $tolerance, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two values serialize to the same number of bytes.
+///
+/// Both values must implement [`serde::Serialize`], and are serialized as JSON. Only the bytes are
+/// counted, so this is a cheap way to catch values with a very different shape.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// This macro requires the `serde` feature.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_serialized_len_eq;
+/// let a = ("abc", [1, 2]);
+/// let b = ("xyz", [3, 4]);
+/// let c = ("abc", [1, 2, 3]);
+/// test_serialized_len_eq!(a, b).expect("This is true");
+/// println!("{:?}", test_serialized_len_eq!(a, c, "for version {}", 2));
+/// // prints:
+/// // [src/main.rs:6:1]: Test failed: a and c have a different serialized length: for version 2
+/// // a: 13 bytes
+/// // c: 15 bytes
+/// ```
+#[cfg(feature = "serde")]
+#[macro_export]
+macro_rules! test_serialized_len_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::test_serialized_len_eq!(@compare $left, $right, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $args:expr) => {{
+        match $crate::helpers::serialized_len_difference(&$left, &$right, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a and b have a different serialized length"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " and ", ::std::stringify!($right), " have a different serialized length")
+                } else {
+                    // "Test failed: a and b have a different serialized length"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " and ", ::std::stringify!($right), " have a different serialized length")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::test_serialized_len_eq!(@compare $left, $right, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that a block does not allocate on the heap.
+///
+/// The allocations are counted by a global allocator that wraps the
+/// [system allocator](std::alloc::System). Enabling the feature installs this allocator for the
+/// whole program, so it cannot be combined with another `#[global_allocator]`. Only the
+/// allocations of the current thread are counted, so tests running in parallel don't interfere,
+/// but allocations that the block causes on other threads are missed. Reallocations are counted as
+/// allocations too.
+///
+/// The value of the block is dropped after the allocations are counted.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// This macro requires the `alloc-counting` feature.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_no_alloc;
+/// let a = [1, 2, 3];
+/// test_no_alloc!({ a.iter().sum::<i32>() }).expect("This is true");
+/// println!("{:?}", test_no_alloc!({ a.to_vec() }, "for {} items", a.len()));
+/// // prints:
+/// // [src/main.rs:4:1]: Test failed: { a.to_vec() } allocated: for 3 items
+/// // allocations: 1
+/// ```
+#[cfg(feature = "alloc-counting")]
+#[macro_export]
+macro_rules! test_no_alloc {
+    ($code:block $(,)?) => {
+        $crate::test_no_alloc!(@check $code, ::std::option::Option::None)
+    };
+    (@check $code:block, $args:expr) => {{
+        let before = $crate::alloc::allocations();
+        let _value = $code;
+        let allocations = $crate::alloc::allocations() - before;
+        if allocations == 0 {
+            ::std::result::Result::Ok(())
+        } else {
+            let message = if $crate::__LINE_INFO {
+                // "[src/main:2:5]: Test failed: { compute() } allocated"
+                ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($code), " allocated")
+            } else {
+                // "Test failed: { compute() } allocated"
+                ::std::concat!("Test failed: ", ::std::stringify!($code), " allocated")
+            };
+
+            ::std::result::Result::Err($crate::TestFailure::test_failed_one_ident(message, "allocations", &allocations, $args))
+        }
+    }};
+    ($code:block, $($arg:tt)+) => {
+        $crate::test_no_alloc!(@check $code, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that a string is one of the expected strings, suggesting the closest one on failure.
+///
+/// The value can be anything that implements <code>[AsRef]<[str]></code>, and the expected strings
+/// anything that can be iterated over by reference, like an array or a [`Vec`] of strings. On failure,
+/// the expected string with the smallest edit distance to the value is suggested.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_one_of_suggest;
+/// let a = "apple";
+/// let b = "bananna";
+/// test_one_of_suggest!(a, ["apple", "banana", "cherry"]).expect("This is true");
+/// println!("{:?}", test_one_of_suggest!(b, ["apple", "banana", "cherry"], "in basket {}", 2));
+/// // prints:
+/// // [src/main.rs:5:1]: Test failed: b is not one of ["apple", "banana", "cherry"]: in basket 2
+/// // b: "bananna"
+/// // expected one of: ["apple", "banana", "cherry"]
+/// // did you mean "banana"?
+/// ```
+#[macro_export]
+macro_rules! test_one_of_suggest {
+    ($value:expr, $expected:expr $(,)?) => {
+        $crate::test_one_of_suggest!(@check $value, $expected, ::std::option::Option::None)
+    };
+    (@check $value:expr, $expected:expr, $args:expr) => {{
+        match $crate::helpers::one_of_suggestion(&$value, &$expected, ::std::stringify!($value)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a is not one of ["x", "y"]"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($value), " is not one of ", ::std::stringify!($expected))
+                } else {
+                    // "Test failed: a is not one of ["x", "y"]"
+                    ::std::concat!("Test failed: ", ::std::stringify!($value), " is not one of ", ::std::stringify!($expected))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($value:expr, $expected:expr, $($arg:tt)+) => {
+        $crate::test_one_of_suggest!(@check $value, $expected, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two [`Duration`](std::time::Duration)s have the same order of magnitude.
+///
+/// The test passes if the larger duration is at most `factor` times the smaller one. The factor
+/// defaults to 10 and can be changed with `factor = n`. On failure, the ratio is shown.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use test_eq::test_same_magnitude;
+/// let a = Duration::from_millis(40);
+/// let b = Duration::from_millis(250);
+/// let c = Duration::from_millis(500);
+/// test_same_magnitude!(a, b).expect("This is true");
+/// println!("{:?}", test_same_magnitude!(a, c, factor = 5, "for {} requests", 100));
+/// // prints:
+/// // [src/main.rs:7:1]: Test failed: a and c do not have the same order of magnitude: for 100 requests
+/// // ratio: 12.50x (maximum: 5x)
+/// // a: 40ms
+/// // c: 500ms
+/// ```
+#[macro_export]
+macro_rules! test_same_magnitude {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::test_same_magnitude!(@compare $left, $right, $crate::helpers::DEFAULT_MAGNITUDE_FACTOR, ::std::option::Option::None)
+    };
+    ($left:expr, $right:expr, factor = $factor:expr $(,)?) => {
+        $crate::test_same_magnitude!(@compare $left, $right, $factor, ::std::option::Option::None)
+    };
+    ($left:expr, $right:expr, factor = $factor:expr, $($arg:tt)+) => {
+        $crate::test_same_magnitude!(@compare $left, $right, $factor, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+    (@compare $left:expr, $right:expr, $factor:expr, $args:expr) => {{
+        match $crate::helpers::magnitude_difference($left, $right, $factor, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a and b do not have the same order of magnitude"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " and ", ::std::stringify!($right), " do not have the same order of magnitude")
+                } else {
+                    // "Test failed: a and b do not have the same order of magnitude"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " and ", ::std::stringify!($right), " do not have the same order of magnitude")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::test_same_magnitude!(@compare $left, $right, $crate::helpers::DEFAULT_MAGNITUDE_FACTOR, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two edge lists contain the same edges, ignoring the order and duplicates.
+///
+/// Both edge lists must be slices (or anything that implements <code>[AsRef]<\[(u32, u32)\]></code>)
+/// of `(from, to)` pairs. The edges are directed by default, so `(1, 2)` and `(2, 1)` are different
+/// edges. With `directed = false` the direction is ignored, and the edges are shown with the smallest
+/// node first. On failure, the edges that are only in one of the lists are shown.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_edges_eq_unordered;
+/// let a = [(1, 2), (2, 3), (3, 1)];
+/// let b = [(3, 1), (1, 2), (2, 3)];
+/// let c = [(2, 1), (3, 2)];
+/// test_edges_eq_unordered!(a, b).expect("This is true");
+/// println!("{:?}", test_edges_eq_unordered!(a, c, directed = false, "for graph {}", 4));
+/// // prints:
+/// // [src/main.rs:6:1]: Test failed: a != c (unordered edges): for graph 4
+/// // only in a: [(1, 3)]
+/// ```
+#[macro_export]
+macro_rules! test_edges_eq_unordered {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::test_edges_eq_unordered!(@compare $left, $right, true, ::std::option::Option::None)
+    };
+    ($left:expr, $right:expr, directed = $directed:expr $(,)?) => {
+        $crate::test_edges_eq_unordered!(@compare $left, $right, $directed, ::std::option::Option::None)
+    };
+    ($left:expr, $right:expr, directed = $directed:expr, $($arg:tt)+) => {
+        $crate::test_edges_eq_unordered!(@compare $left, $right, $directed, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+    (@compare $left:expr, $right:expr, $directed:expr, $args:expr) => {{
+        match $crate::helpers::edges_difference(&$left, &$right, $directed, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a != b (unordered edges)"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right), " (unordered edges)")
+                } else {
+                    // "Test failed: a != b (unordered edges)"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right), " (unordered edges)")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::test_edges_eq_unordered!(@compare $left, $right, true, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two integers differ by at most a tolerance.
+///
+/// Both integers must have the same type, and the tolerance is the unsigned type of the same size.
+/// The difference is calculated without overflowing, also for unsigned integers.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_near_int;
+/// let a: u32 = 41;
+/// let b: u32 = 42;
+/// let c: u32 = 45;
+/// test_near_int!(a, b, 1).expect("This is true");
+/// println!("{:?}", test_near_int!(c, a, 1, "after {} retries", 3));
+/// // prints:
+/// // [src/main.rs:6:1]: Test failed: c is not within 1 of a: after 3 retries
+/// // difference: 4 (maximum: 1)
+/// // c: 45
+/// // a: 41
+/// ```
+#[macro_export]
+macro_rules! test_near_int {
+    ($left:expr, $right:expr, $tolerance:expr $(,)?) => {
+        $crate::test_near_int!(@compare $left, $right, $tolerance, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $tolerance:expr, $args:expr) => {{
+        match $crate::helpers::near_int_difference($left, $right, $tolerance, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a is not within 1 of b"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " is not within ", ::std::stringify!($tolerance), " of ", ::std::stringify!($right))
+                } else {
+                    // "Test failed: a is not within 1 of b"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " is not within ", ::std::stringify!($tolerance), " of ", ::std::stringify!($right))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $tolerance:expr, $($arg:tt)+) => {
+        $crate::test_near_int!(@compare $left, $right, $tolerance, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that the listed fields of a struct are equal to the expected values.
+///
+/// Fields that are not listed are ignored. Every listed field is compared with [`PartialEq`], like
+/// [`test_eq!`], and every field that differs is reported by name.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_fields_match;
+/// struct User {
+///     name: String,
+///     age: u32,
+///     admin: bool,
+/// }
+/// let user = User { name: String::from("Alice"), age: 30, admin: false };
+/// test_fields_match!(user, { name: "Alice", age: 30 }).expect("This is true");
+/// println!("{:?}", test_fields_match!(user, { name: "Bob", age: 30, admin: true }, "for id {}", 7));
+/// // prints:
+/// // 2 tests failed: for id 7
+/// // 1: [src/main.rs:8:1]: Test failed: user.name != "Bob"
+/// //    user.name: "Alice"
+/// //    "Bob": "Bob"
+/// // 2: [src/main.rs:8:1]: Test failed: user.admin != true
+/// //    user.admin: false
+/// //    true: true
+/// ```
+#[macro_export]
+macro_rules! test_fields_match {
+    ($actual:expr, { $($field:ident : $expected:expr),+ $(,)? } $(,)?) => {
+        $crate::test_fields_match!(@check $actual, { $($field: $expected),+ }, ::std::option::Option::None)
+    };
+    (@check $actual:expr, { $($field:ident : $expected:expr),+ }, $args:expr) => {{
+        match &$actual {
+            actual => {
+                let mut failures = ::std::vec::Vec::new();
+                $(
+                    match &$expected {
+                        expected => {
+                            if !(actual.$field == *expected) {
+                                let message = if $crate::__LINE_INFO {
+                                    // "[src/main:2:5]: Test failed: user.name != "Bob""
+                                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($actual), ".", ::std::stringify!($field), " != ", ::std::stringify!($expected))
+                                } else {
+                                    // "Test failed: user.name != "Bob""
+                                    ::std::concat!("Test failed: ", ::std::stringify!($actual), ".", ::std::stringify!($field), " != ", ::std::stringify!($expected))
+                                };
+                                let field = ::std::concat!(::std::stringify!($actual), ".", ::std::stringify!($field));
+
+                                failures.push($crate::TestFailure::test_failed_two_idents(message, field, &actual.$field, ::std::stringify!($expected), expected, ::std::option::Option::None));
+                            }
+                        }
+                    }
+                )+
+                if failures.is_empty() {
+                    ::std::result::Result::Ok(())
+                } else {
+                    ::std::result::Result::Err($crate::TestFailure::tests_failed(failures, $args))
+                }
+            }
+        }
+    }};
+    ($actual:expr, { $($field:ident : $expected:expr),+ $(,)? }, $($arg:tt)+) => {
+        $crate::test_fields_match!(@check $actual, { $($field: $expected),+ }, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two closures produce the same outputs in the same order for the same inputs.
+///
+/// Both closures get a reference to the inputs and must return something that implements
+/// [`IntoIterator`]. The outputs are compared one by one, like [`test_iter_eq!`], and on failure the
+/// first index where they diverge is shown with the outputs around it.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_order_preserved;
+/// let a = [3, 1, 2];
+/// let double = |v: &[i32; 3]| v.map(|x| x * 2);
+/// let filtered = |v: &[i32; 3]| v.iter().filter(|x| **x > 0).map(|x| x * 2).collect::<Vec<_>>();
+/// let sorted = |v: &[i32; 3]| { let mut v = v.map(|x| x * 2); v.sort(); v };
+/// test_order_preserved!(double, filtered, a).expect("This is true");
+/// println!("{:?}", test_order_preserved!(double, sorted, a, "for batch {}", 1));
+/// // prints:
+/// // [src/main.rs:7:1]: Test failed: double and sorted have a different order for a: for batch 1
+/// // first difference at index 0: 6 != 2
+/// // double[0..3]: [6, 2, 4]
+/// // sorted[0..3]: [2, 4, 6]
+/// ```
+#[macro_export]
+macro_rules! test_order_preserved {
+    ($first:expr, $second:expr, $inputs:expr $(,)?) => {
+        $crate::test_order_preserved!(@compare $first, $second, $inputs, ::std::option::Option::None)
+    };
+    (@compare $first:expr, $second:expr, $inputs:expr, $args:expr) => {{
+        match $crate::helpers::order_difference(&$inputs, $first, $second, ::std::stringify!($first), ::std::stringify!($second)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: f and g have a different order for inputs"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($first), " and ", ::std::stringify!($second), " have a different order for ", ::std::stringify!($inputs))
+                } else {
+                    // "Test failed: f and g have a different order for inputs"
+                    ::std::concat!("Test failed: ", ::std::stringify!($first), " and ", ::std::stringify!($second), " have a different order for ", ::std::stringify!($inputs))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($first:expr, $second:expr, $inputs:expr, $($arg:tt)+) => {
+        $crate::test_order_preserved!(@compare $first, $second, $inputs, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two bit sequences are equal to each other, showing the indices of the differing bits.
+///
+/// By default both sequences are slices of [`bool`] (or anything that implements
+/// <code>[AsRef]<\[bool\]></code>). With `bits = n` both sequences are packed bytes (anything that
+/// implements <code>[AsRef]<\[u8\]></code>) holding `n` bits, where bit `i` is bit `i % 8` of byte
+/// `i / 8`, counting from the least significant bit. On failure, the first differing bit is shown,
+/// followed by every differing bit, up to 10 bits.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_bits_eq_diff;
+/// let a = [true, false, true, true];
+/// let b = [true, false, true, true];
+/// let c = [0b1010_0101_u8, 0b0011];
+/// let d = [0b1010_0001_u8, 0b0111];
+/// test_bits_eq_diff!(a, b).expect("This is true");
+/// println!("{:?}", test_bits_eq_diff!(c, d, bits = 12, "for flags {}", 3));
+/// // prints:
+/// // [src/main.rs:7:1]: Test failed: c != d: for flags 3
+/// // first differing bit: 2
+/// // 2 differing bits (c != d):
+/// //   [2]: true != false
+/// //   [10]: false != true
+/// ```
+#[macro_export]
+macro_rules! test_bits_eq_diff {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::test_bits_eq_diff!(@report $left, $right, $crate::helpers::bools_difference(&$left, &$right, ::std::stringify!($left), ::std::stringify!($right)), ::std::option::Option::None)
+    };
+    ($left:expr, $right:expr, bits = $bits:expr $(,)?) => {
+        $crate::test_bits_eq_diff!(@report $left, $right, $crate::helpers::packed_bits_difference(&$left, &$right, $bits, ::std::stringify!($left), ::std::stringify!($right)), ::std::option::Option::None)
+    };
+    ($left:expr, $right:expr, bits = $bits:expr, $($arg:tt)+) => {
+        $crate::test_bits_eq_diff!(@report $left, $right, $crate::helpers::packed_bits_difference(&$left, &$right, $bits, ::std::stringify!($left), ::std::stringify!($right)), ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+    (@report $left:expr, $right:expr, $difference:expr, $args:expr) => {{
+        match $difference {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a != b"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                } else {
+                    // "Test failed: a != b"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::test_bits_eq_diff!(@report $left, $right, $crate::helpers::bools_difference(&$left, &$right, ::std::stringify!($left), ::std::stringify!($right)), ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that an implementation returns the same output as a reference implementation.
+///
+/// Both functions get a reference to the input, and their outputs are compared with [`PartialEq`].
+/// This is useful for differential testing, for example of an optimized function against a simple one.
+/// On failure, the input and both outputs are shown.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_matches_reference;
+/// let fast = |n: &u64| n * (n + 1) / 2;
+/// let slow = |n: &u64| (1..=*n).sum::<u64>();
+/// let wrong = |n: &u64| n * n / 2;
+/// test_matches_reference!(100, fast, slow).expect("This is true");
+/// println!("{:?}", test_matches_reference!(5, wrong, slow, "in round {}", 1));
+/// // prints:
+/// // [src/main.rs:6:1]: Test failed: wrong does not match slow: in round 1
+/// // 5: 5
+/// // wrong: 12
+/// // slow: 15
+/// ```
+#[macro_export]
+macro_rules! test_matches_reference {
+    ($input:expr, $implementation:expr, $reference:expr $(,)?) => {
+        $crate::test_matches_reference!(@compare $input, $implementation, $reference, ::std::option::Option::None)
+    };
+    (@compare $input:expr, $implementation:expr, $reference:expr, $args:expr) => {{
+        match $crate::helpers::reference_difference(&$input, $implementation, $reference, ::std::stringify!($input), ::std::stringify!($implementation), ::std::stringify!($reference)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: fast does not match slow"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($implementation), " does not match ", ::std::stringify!($reference))
+                } else {
+                    // "Test failed: fast does not match slow"
+                    ::std::concat!("Test failed: ", ::std::stringify!($implementation), " does not match ", ::std::stringify!($reference))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($input:expr, $implementation:expr, $reference:expr, $($arg:tt)+) => {
+        $crate::test_matches_reference!(@compare $input, $implementation, $reference, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two values are equal to each other after normalizing them with a function.
+///
+/// The function is given as a path, like `normalize` or `str::to_lowercase`, so it can be defined
+/// once and reused by many tests. It gets a reference to each value, with the usual deref coercions,
+/// and the results are compared with [`PartialEq`]. On failure, both the original and the normalized
+/// values are shown. To compare with a closure, use [`test_eq_via!`].
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_eq_after_fn;
+/// /// Convert a `+hh:mm` offset to `Z` when it is zero.
+/// fn to_utc(timestamp: &str) -> String {
+///     timestamp.replace("+00:00", "Z")
+/// }
+/// let a = "2024-05-01T12:00:00Z";
+/// let b = String::from("2024-05-01T12:00:00+00:00");
+/// let c = "2024-05-01T13:00:00+00:00";
+/// test_eq_after_fn!(a, b, to_utc).expect("This is true");
+/// println!("{:?}", test_eq_after_fn!(a, c, to_utc, "in log {}", 2));
+/// // prints:
+/// // [src/main.rs:9:1]: Test failed: a != c (after to_utc): in log 2
+/// // a: "2024-05-01T12:00:00Z"
+/// // c: "2024-05-01T13:00:00+00:00"
+/// // normalized a: "2024-05-01T12:00:00Z"
+/// // normalized c: "2024-05-01T13:00:00Z"
+/// ```
+#[macro_export]
+macro_rules! test_eq_after_fn {
+    ($left:expr, $right:expr, $normalize:path $(,)?) => {
+        $crate::test_eq_after_fn!(@compare $left, $right, $normalize, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $normalize:path, $args:expr) => {{
+        match (&$left, &$right) {
+            (left_val, right_val) => {
+                match $crate::helpers::normalized_difference(left_val, right_val, &$normalize(left_val), &$normalize(right_val), ::std::stringify!($left), ::std::stringify!($right)) {
+                    ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+                    ::std::result::Result::Err(details) => {
+                        let message = if $crate::__LINE_INFO {
+                            // "[src/main:2:5]: Test failed: a != b (after normalize)"
+                            ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right), " (after ", ::std::stringify!($normalize), ")")
+                        } else {
+                            // "Test failed: a != b (after normalize)"
+                            ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right), " (after ", ::std::stringify!($normalize), ")")
+                        };
+
+                        ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+                    }
+                }
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $normalize:path, $($arg:tt)+) => {
+        $crate::test_eq_after_fn!(@compare $left, $right, $normalize, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two maps contain the same keys, ignoring the values.
+///
+/// Both [`HashMap`](std::collections::HashMap) and [`BTreeMap`](std::collections::BTreeMap) are
+/// supported, and they can be mixed. Each map looks up the keys of the other, so the keys of a
+/// `HashMap` must implement [`Eq`] and [`Hash`](std::hash::Hash), the keys of a `BTreeMap` must
+/// implement [`Ord`], and all keys must implement [`Debug`]. On failure, the keys that are only in one
+/// of the maps are shown, sorted by their [`Debug`] output.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use std::collections::BTreeMap;
+/// use test_eq::test_keys_eq;
+/// let a = BTreeMap::from([("alice", 1), ("bob", 2)]);
+/// let b = BTreeMap::from([("bob", 5), ("alice", 8)]);
+/// let c = BTreeMap::from([("alice", 1), ("carol", 2)]);
+/// test_keys_eq!(a, b).expect("This is true");
+/// println!("{:?}", test_keys_eq!(a, c, "players of round {}", 2));
+/// // prints:
+/// // [src/main.rs:7:1]: Test failed: a.keys() != c.keys() (unordered): players of round 2
+/// // only in a: ["bob"]
+/// // only in c: ["carol"]
+/// ```
+#[macro_export]
+macro_rules! test_keys_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::test_keys_eq!(@compare $left, $right, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $args:expr) => {{
+        match $crate::helpers::keys_difference(&$left, &$right, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a.keys() != b.keys() (unordered)"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), ".keys() != ", ::std::stringify!($right), ".keys() (unordered)")
+                } else {
+                    // "Test failed: a.keys() != b.keys() (unordered)"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), ".keys() != ", ::std::stringify!($right), ".keys() (unordered)")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::test_keys_eq!(@compare $left, $right, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two slices are equal, or differ by a single inserted or deleted element.
+///
+/// Both expressions can be anything that implements <code>[AsRef]<\[T\]></code>, where `T`
+/// implements [`PartialEq`] and [`Debug`]. A replaced element is not an insertion or deletion, so it
+/// fails the test. On failure, the position of the first difference is shown.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_eq_edit1;
+/// let a = ['g', 'a', 't', 'c'];
+/// let b = ['g', 'a', 'a', 't', 'c'];
+/// let c = ['g', 'c', 't', 'c'];
+/// test_eq_edit1!(a, b).expect("This is true");
+/// println!("{:?}", test_eq_edit1!(a, c, "in read {}", 12));
+/// // prints:
+/// // [src/main.rs:6:1]: Test failed: a and c differ by more than one insertion or deletion: in read 12
+/// // substitution at index 1, which is not an insertion or deletion
+/// // a[1]: 'a'
+/// // c[1]: 'c'
+/// ```
+#[macro_export]
+macro_rules! test_eq_edit1 {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::test_eq_edit1!(@compare $left, $right, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $args:expr) => {{
+        match $crate::helpers::edit1_difference(&$left, &$right, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a and b differ by more than one insertion or deletion"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " and ", ::std::stringify!($right), " differ by more than one insertion or deletion")
+                } else {
+                    // "Test failed: a and b differ by more than one insertion or deletion"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " and ", ::std::stringify!($right), " differ by more than one insertion or deletion")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::test_eq_edit1!(@compare $left, $right, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that the left expression is a permutation of the right expression.
+///
+/// Both expressions can be anything that implements [`IntoIterator`], like a [`Vec`] and a
+/// [`range`](std::ops::Range), and the items must implement [`Eq`], [`Hash`](std::hash::Hash) and
+/// [`Debug`]. Every item of the right expression must occur exactly as often in the left expression.
+/// On failure, the extra items (only in the left expression) and the missing items (only in the right
+/// expression) are shown.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_is_permutation_of;
+/// let a = vec![3, 0, 2, 1];
+/// let b = vec![3, 0, 3, 1];
+/// test_is_permutation_of!(a, 0..4).expect("This is true");
+/// println!("{:?}", test_is_permutation_of!(b, 0..4, "for seed {}", 42));
+/// // prints:
+/// // [src/main.rs:5:1]: Test failed: b is not a permutation of 0..4: for seed 42
+/// // only in b: [3]
+/// // only in 0..4: [2]
+/// ```
+#[macro_export]
+macro_rules! test_is_permutation_of {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::test_is_permutation_of!(@compare $left, $right, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $args:expr) => {{
+        match $crate::helpers::multiset_difference($left, $right, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a is not a permutation of 0..n"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " is not a permutation of ", ::std::stringify!($right))
+                } else {
+                    // "Test failed: a is not a permutation of 0..n"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " is not a permutation of ", ::std::stringify!($right))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::test_is_permutation_of!(@compare $left, $right, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two values have the same [`Debug`] output, showing only the lines that differ.
+///
+/// The values are formatted with `{:#?}` and compared line by line, so this works for types that
+/// implement [`Debug`] but not [`PartialEq`]. For structs, every field is on its own line, so only the
+/// fields that differ are shown. Lines only in the left value start with `-`, lines only in the right
+/// value with `+`.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_debug_diff;
+/// #[derive(Debug)]
+/// struct Config {
+///     name: &'static str,
+///     retries: u32,
+///     verbose: bool,
+/// }
+/// let a = Config { name: "server", retries: 3, verbose: false };
+/// let b = Config { name: "server", retries: 3, verbose: false };
+/// let c = Config { name: "server", retries: 5, verbose: false };
+/// test_debug_diff!(a, b).expect("This is true");
+/// println!("{:?}", test_debug_diff!(a, c, "for profile {}", "release"));
+/// // prints:
+/// // [src/main.rs:11:1]: Test failed: a != c (Debug): for profile release
+/// // differing lines (-a, +c):
+/// // -    retries: 3,
+/// // +    retries: 5,
+/// ```
+#[macro_export]
+macro_rules! test_debug_diff {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::test_debug_diff!(@compare $left, $right, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $args:expr) => {{
+        match $crate::helpers::debug_difference(&$left, &$right, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a != b (Debug)"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right), " (Debug)")
+                } else {
+                    // "Test failed: a != b (Debug)"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right), " (Debug)")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::test_debug_diff!(@compare $left, $right, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that a test passes within a number of attempts.
+///
+/// The closure must return a <code>[Result]<(), [TestFailure]></code>, so it can use any of the
+/// other `test_*!` macros. It is called until it passes, at most the given number of times. On
+/// failure, the failure of the last attempt is shown. This counts attempts and does not wait between
+/// them, so the closure should do the polling or waiting itself.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::{test_eq, test_eventually};
+/// let mut polls = 0;
+/// test_eventually!(5, || { polls += 1; test_eq!(polls, 3) }).expect("This is true");
+/// let ready = false;
+/// println!("{:?}", test_eventually!(3, || test_eq!(ready, true), "for job {}", 7));
+/// // prints:
+/// // [src/main.rs:5:1]: Test failed: || test_eq!(ready, true) did not pass within 3 attempts: for job 7
+/// // all 3 attempts failed, the last with:
+/// //    [src/main.rs:5:1]: Test failed: ready != true
+/// //    ready: false
+/// //    true: true
+/// ```
+#[macro_export]
+macro_rules! test_eventually {
+    ($attempts:expr, $attempt:expr $(,)?) => {
+        $crate::test_eventually!(@check $attempts, $attempt, ::std::option::Option::None)
+    };
+    (@check $attempts:expr, $attempt:expr, $args:expr) => {{
+        match $crate::helpers::eventually_error::<_, $crate::TestFailure>($attempts, $attempt) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: || poll() did not pass within 5 attempts"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($attempt), " did not pass within ", ::std::stringify!($attempts), " attempts")
+                } else {
+                    // "Test failed: || poll() did not pass within 5 attempts"
+                    ::std::concat!("Test failed: ", ::std::stringify!($attempt), " did not pass within ", ::std::stringify!($attempts), " attempts")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($attempts:expr, $attempt:expr, $($arg:tt)+) => {
+        $crate::test_eventually!(@check $attempts, $attempt, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two expressions are equal to each other (using [`PartialEq`]), using custom labels in
+/// the failure message.
+///
+/// Every expression is preceded by a string literal and `=>`. The labels replace the expression
+/// text in the failure message, which helps when the expressions are long or unclear.
+/// Otherwise this works like [`test_eq!`].
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_eq_labeled;
+/// let response = (200, "OK");
+/// test_eq_labeled!("expected" => 200, "status" => response.0).expect("This is true");
+/// println!("{:?}", test_eq_labeled!("expected" => "Created", "reason" => response.1, "for {}", "POST"));
+/// // prints:
+/// // [src/main.rs:4:1]: Test failed: expected != reason: for POST
+/// // expected: "Created"
+/// // reason: "OK"
+/// ```
+#[macro_export]
+macro_rules! test_eq_labeled {
+    ($left_label:literal => $left:expr, $right_label:literal => $right:expr $(,)?) => {
+        $crate::test_eq_labeled!(@compare $left_label => $left, $right_label => $right, ::std::option::Option::None)
+    };
+    (@compare $left_label:literal => $left:expr, $right_label:literal => $right:expr, $args:expr) => {{
+        match (&$left, &$right) {
+            (left_val, right_val) => {
+                if !(left_val == right_val) {
+                    let message = if $crate::__LINE_INFO {
+                        // "[src/main:2:5]: Test failed: expected != actual"
+                        ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", $left_label, " != ", $right_label)
+                    } else {
+                        // "Test failed: expected != actual"
+                        ::std::concat!("Test failed: ", $left_label, " != ", $right_label)
+                    };
+
+                    // The reborrows below are intentional, see `test_eq!`.
+                    ::std::result::Result::Err($crate::TestFailure::test_failed_comparison(message, $left_label, &*left_val, $right_label, &*right_val, $args).with_delta($crate::helpers::Delta(&*left_val, &*right_val).delta()))
+                } else {
+                    ::std::result::Result::Ok(())
+                }
+            }
+        }
+    }};
+    ($left_label:literal => $left:expr, $right_label:literal => $right:expr, $($arg:tt)+) => {
+        $crate::test_eq_labeled!(@compare $left_label => $left, $right_label => $right, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two byte slices are equal to each other, showing them as text when possible.
+///
+/// Both expressions can be anything that implements <code>[AsRef]<\[u8\]></code>. If both are valid
+/// UTF-8, the failure message shows the lines that differ, like [`test_debug_diff!`]. Otherwise, or if
+/// only the line endings differ, it shows a hex dump of the first difference. The failure message
+/// says which of the two was used.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_bytes_text_eq;
+/// let a = b"GET / HTTP/1.1\r\nHost: a\r\n".to_vec();
+/// let b = b"GET / HTTP/1.1\r\nHost: a\r\n".to_vec();
+/// let c = b"GET / HTTP/1.1\r\nHost: b\r\n".to_vec();
+/// test_bytes_text_eq!(a, b).expect("This is true");
+/// println!("{:?}", test_bytes_text_eq!(a, c, "for request {}", 1));
+/// // prints:
+/// // [src/main.rs:6:1]: Test failed: a != c: for request 1
+/// // compared as text
+/// // differing lines (-a, +c):
+/// // -Host: a
+/// // +Host: b
+/// ```
+#[macro_export]
+macro_rules! test_bytes_text_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::test_bytes_text_eq!(@compare $left, $right, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $args:expr) => {{
+        match $crate::helpers::bytes_text_difference(&$left, &$right, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a != b"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                } else {
+                    // "Test failed: a != b"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::test_bytes_text_eq!(@compare $left, $right, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that the [`Display`](std::fmt::Display) output of a value is equal to an expected string.
+///
+/// The expected string can be anything that implements <code>[AsRef]<[str]></code>. On failure,
+/// the actual [`Display`](std::fmt::Display) output is shown. To compare the output of two values,
+/// use [`test_eq_str_repr!`].
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_display_eq;
+/// struct Meters(f64);
+/// impl std::fmt::Display for Meters {
+///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+///         write!(f, "{:.2} m", self.0)
+///     }
+/// }
+/// let a = Meters(2.5);
+/// test_display_eq!(a, "2.50 m").expect("This is true");
+/// println!("{:?}", test_display_eq!(a, "2.5 m", "with {} decimals", 1));
+/// // prints:
+/// // [src/main.rs:10:1]: Test failed: a is not displayed as "2.5 m": with 1 decimals
+/// // a: "2.50 m"
+/// // "2.5 m": "2.5 m"
+/// ```
+#[macro_export]
+macro_rules! test_display_eq {
+    ($value:expr, $expected:expr $(,)?) => {
+        $crate::test_display_eq!(@compare $value, $expected, ::std::option::Option::None)
+    };
+    (@compare $value:expr, $expected:expr, $args:expr) => {{
+        match (::std::string::ToString::to_string(&$value), &$expected) {
+            (value_val, expected_val) => {
+                let expected_val: &str = ::std::convert::AsRef::as_ref(expected_val);
+                if !(value_val == expected_val) {
+                    let message = if $crate::__LINE_INFO {
+                        // "[src/main:2:5]: Test failed: a is not displayed as "3.14 m""
+                        ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($value), " is not displayed as ", ::std::stringify!($expected))
+                    } else {
+                        // "Test failed: a is not displayed as "3.14 m""
+                        ::std::concat!("Test failed: ", ::std::stringify!($value), " is not displayed as ", ::std::stringify!($expected))
+                    };
+
+                    ::std::result::Result::Err($crate::TestFailure::test_failed_two_idents(message, ::std::stringify!($value), &value_val, ::std::stringify!($expected), expected_val, $args))
+                } else {
+                    ::std::result::Result::Ok(())
+                }
+            }
+        }
+    }};
+    ($value:expr, $expected:expr, $($arg:tt)+) => {
+        $crate::test_display_eq!(@compare $value, $expected, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that a builder builds a value equal to the expected value (using [`PartialEq`]).
+///
+/// If the build expression returns a [`Result`], the error is reported when it is an [`Err`], and
+/// the value is compared when it is [`Ok`]. Otherwise, the value is compared directly, like
+/// [`test_eq!`]. The error must implement [`Debug`].
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_build_eq;
+/// #[derive(Debug, PartialEq)]
+/// struct Server { port: u16 }
+/// #[derive(Default)]
+/// struct ServerBuilder { port: Option<u16> }
+/// impl ServerBuilder {
+///     fn port(self, port: u16) -> Self { Self { port: Some(port) } }
+///     fn build(self) -> Result<Server, &'static str> {
+///         self.port.map(|port| Server { port }).ok_or("port is required")
+///     }
+/// }
+/// test_build_eq!(ServerBuilder::default().port(80).build(), Server { port: 80 }).expect("This is true");
+/// println!("{:?}", test_build_eq!(ServerBuilder::default().build(), Server { port: 80 }, "in {} mode", "test"));
+/// // prints:
+/// // [src/main.rs:11:1]: Test failed: ServerBuilder::default().build() failed to build: in test mode
+/// // error: "port is required"
+/// ```
+#[macro_export]
+macro_rules! test_build_eq {
+    ($built:expr, $expected:expr $(,)?) => {
+        $crate::test_build_eq!(@compare $built, $expected, ::std::option::Option::None)
+    };
+    (@compare $built:expr, $expected:expr, $args:expr) => {{
+        match $built {
+            built => match $crate::helpers::Built(&built).kind().into_built(built) {
+                ::std::result::Result::Ok(built_val) => match (&built_val, &$expected) {
+                    (left_val, right_val) => {
+                        if !(left_val == right_val) {
+                            let message = if $crate::__LINE_INFO {
+                                // "[src/main:2:5]: Test failed: builder.build() != expected"
+                                ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($built), " != ", ::std::stringify!($expected))
+                            } else {
+                                // "Test failed: builder.build() != expected"
+                                ::std::concat!("Test failed: ", ::std::stringify!($built), " != ", ::std::stringify!($expected))
+                            };
+
+                            // The reborrows below are intentional, see `test_eq!`.
+                            ::std::result::Result::Err($crate::TestFailure::test_failed_comparison(message, ::std::stringify!($built), &*left_val, ::std::stringify!($expected), &*right_val, $args))
+                        } else {
+                            ::std::result::Result::Ok(())
+                        }
+                    }
+                },
+                ::std::result::Result::Err(error) => {
+                    let message = if $crate::__LINE_INFO {
+                        // "[src/main:2:5]: Test failed: builder.build() failed to build"
+                        ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($built), " failed to build")
+                    } else {
+                        // "Test failed: builder.build() failed to build"
+                        ::std::concat!("Test failed: ", ::std::stringify!($built), " failed to build")
+                    };
+
+                    ::std::result::Result::Err($crate::TestFailure::test_failed_one_ident(message, "error", &error, $args))
+                }
+            },
+        }
+    }};
+    ($built:expr, $expected:expr, $($arg:tt)+) => {
+        $crate::test_build_eq!(@compare $built, $expected, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two collections of numbers have the same minimum, maximum and sum.
+///
+/// The order of the elements is ignored. Every statistic that differs is reported. Sums of integers
+/// that overflow fail the test instead of panicking, and sums of floats depend on the order of the
+/// elements, so they may differ slightly.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_stats_eq;
+/// test_stats_eq!([3, 1, 2], [1, 2, 3]).expect("This is true");
+/// println!("{:?}", test_stats_eq!([3, 1, 2], [1, 3, 3], "in {} run", "the first"));
+/// // prints:
+/// // [src/main.rs:3:1]: Test failed: [3, 1, 2] and [1, 3, 3] have different statistics: in the first run
+/// // sum differs (delta: -1)
+/// //     [3, 1, 2]: 6
+/// //     [1, 3, 3]: 7
+/// ```
+#[macro_export]
+macro_rules! test_stats_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::test_stats_eq!(@compare $left, $right, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $args:expr) => {{
+        match $crate::helpers::stats_difference(&$left, &$right, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a and b have different statistics"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " and ", ::std::stringify!($right), " have different statistics")
+                } else {
+                    // "Test failed: a and b have different statistics"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " and ", ::std::stringify!($right), " have different statistics")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::test_stats_eq!(@compare $left, $right, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that a string matches a glob pattern.
+///
+/// The first expression must implement <code>[AsRef]<[str]></code>. The pattern uses the syntax of the
+/// [`glob`](https://docs.rs/glob) crate. A `*` does not match a `/`, while a `**` matches any number of
+/// directories. The test fails if the pattern is invalid or does not match.
+///
+/// This macro is only available with the `glob` feature.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_glob_match;
+/// let path = "src/helpers/mod.rs";
+/// test_glob_match!(path, "src/**/*.rs").expect("This is true");
+/// println!("{:?}", test_glob_match!(path, "src/*.rs", "in {} mode", "strict"));
+/// // prints:
+/// // [src/main.rs:4:1]: Test failed: path does not match "src/*.rs": in strict mode
+/// // path: "src/helpers/mod.rs"
+/// // pattern: "src/*.rs"
+/// ```
+#[cfg(feature = "glob")]
+#[macro_export]
+macro_rules! test_glob_match {
+    ($value:expr, $pattern:expr $(,)?) => {
+        $crate::test_glob_match!(@check $value, $pattern, ::std::option::Option::None)
+    };
+    (@check $value:expr, $pattern:expr, $args:expr) => {{
+        match $crate::helpers::glob_error(&$value, $pattern, ::std::stringify!($value)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a does not match "*.rs""
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($value), " does not match ", ::std::stringify!($pattern))
+                } else {
+                    // "Test failed: a does not match "*.rs""
+                    ::std::concat!("Test failed: ", ::std::stringify!($value), " does not match ", ::std::stringify!($pattern))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($value:expr, $pattern:expr, $($arg:tt)+) => {
+        $crate::test_glob_match!(@check $value, $pattern, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two optional floats are both [`None`], or both [`Some`] and within `epsilon` of each other.
+///
+/// The operands must be an <code>[Option]<[f32]></code> or an <code>[Option]<[f64]></code>. A [`Some`] and
+/// a [`None`] are never equal. The difference is computed as an [`f64`].
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_opt_approx_eq;
+/// let a = Some(0.5);
+/// test_opt_approx_eq!(a, Some(0.501), 0.01).expect("This is true");
+/// println!("{:?}", test_opt_approx_eq!(a, Some(0.6), 0.01, "for sensor {}", 2));
+/// // prints:
+/// // [src/main.rs:4:1]: Test failed: a is not within 0.01 of Some(0.6): for sensor 2
+/// // difference: 0.09999999999999998 (allowed: 0.01)
+/// // a: 0.5
+/// // Some(0.6): 0.6
+/// ```
+#[macro_export]
+macro_rules! test_opt_approx_eq {
+    ($left:expr, $right:expr, $epsilon:expr $(,)?) => {
+        $crate::test_opt_approx_eq!(@compare $left, $right, $epsilon, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $epsilon:expr, $args:expr) => {{
+        match $crate::helpers::opt_approx_difference($left, $right, $epsilon, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a is not within 0.01 of b"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " is not within ", ::std::stringify!($epsilon), " of ", ::std::stringify!($right))
+                } else {
+                    // "Test failed: a is not within 0.01 of b"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " is not within ", ::std::stringify!($epsilon), " of ", ::std::stringify!($right))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $epsilon:expr, $($arg:tt)+) => {
+        $crate::test_opt_approx_eq!(@compare $left, $right, $epsilon, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that the value behind a weak reference is still alive, so it can be upgraded.
+///
+/// The reference must be a [`std::rc::Weak`] or a [`std::sync::Weak`]. The reference is not upgraded,
+/// so this does not keep the value alive. See also [`test_weak_dead!`](crate::test_weak_dead).
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use std::rc::Rc;
+/// use test_eq::test_weak_alive;
+/// let a = Rc::new(5);
+/// let weak = Rc::downgrade(&a);
+/// test_weak_alive!(weak).expect("This is true");
+/// drop(a);
+/// println!("{:?}", test_weak_alive!(weak, "for cache {}", "users"));
+/// // prints:
+/// // [src/main.rs:7:1]: Test failed: weak reference is dead: for cache users
+/// // strong references to weak: 0
+/// ```
+#[macro_export]
+macro_rules! test_weak_alive {
+    ($weak:expr $(,)?) => {
+        $crate::test_weak_alive!(@check $weak, ::std::option::Option::None)
+    };
+    (@check $weak:expr, $args:expr) => {{
+        match $crate::helpers::weak_state_error(&$weak, true, ::std::stringify!($weak)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: weak reference is dead"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: weak reference is dead")
+                } else {
+                    // "Test failed: weak reference is dead"
+                    "Test failed: weak reference is dead"
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($weak:expr, $($arg:tt)+) => {
+        $crate::test_weak_alive!(@check $weak, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that the value behind a weak reference has been dropped, so it can no longer be upgraded.
+///
+/// The reference must be a [`std::rc::Weak`] or a [`std::sync::Weak`]. The reference is not upgraded,
+/// so this does not keep the value alive. See also [`test_weak_alive!`].
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use test_eq::test_weak_dead;
+/// let a = Arc::new(5);
+/// let weak = Arc::downgrade(&a);
+/// println!("{:?}", test_weak_dead!(weak, "for cache {}", "users"));
+/// // prints:
+/// // [src/main.rs:5:1]: Test failed: weak reference is alive: for cache users
+/// // strong references to weak: 1
+/// drop(a);
+/// test_weak_dead!(weak).expect("This is true");
+/// ```
+#[macro_export]
+macro_rules! test_weak_dead {
+    ($weak:expr $(,)?) => {
+        $crate::test_weak_dead!(@check $weak, ::std::option::Option::None)
+    };
+    (@check $weak:expr, $args:expr) => {{
+        match $crate::helpers::weak_state_error(&$weak, false, ::std::stringify!($weak)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: weak reference is alive"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: weak reference is alive")
+                } else {
+                    // "Test failed: weak reference is alive"
+                    "Test failed: weak reference is alive"
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($weak:expr, $($arg:tt)+) => {
+        $crate::test_weak_dead!(@check $weak, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two numbers are equal after clamping both into the range `low..=high`.
+///
+/// Values outside the range are replaced by the nearest bound, so two values that saturate to the same
+/// bound are equal. The operands and bounds must be of the same type and implement [`PartialOrd`]. The
+/// test fails if the range is empty.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_eq_clamped;
+/// let a = 300;
+/// test_eq_clamped!(a, 1000, 0, 255).expect("This is true");
+/// println!("{:?}", test_eq_clamped!(a, 200, 0, 255, "for channel {}", "red"));
+/// // prints:
+/// // [src/main.rs:4:1]: Test failed: a != 200 when clamped to 0..=255: for channel red
+/// // a: 300 (clamped: 255)
+/// // 200: 200 (clamped: 200)
+/// ```
+#[macro_export]
+macro_rules! test_eq_clamped {
+    ($left:expr, $right:expr, $low:expr, $high:expr $(,)?) => {
+        $crate::test_eq_clamped!(@compare $left, $right, $low, $high, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $low:expr, $high:expr, $args:expr) => {{
+        match $crate::helpers::clamped_difference($left, $right, $low, $high, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a != b when clamped to 0..=255"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right), " when clamped to ", ::std::stringify!($low), "..=", ::std::stringify!($high))
+                } else {
+                    // "Test failed: a != b when clamped to 0..=255"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right), " when clamped to ", ::std::stringify!($low), "..=", ::std::stringify!($high))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $low:expr, $high:expr, $($arg:tt)+) => {
+        $crate::test_eq_clamped!(@compare $left, $right, $low, $high, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that the output of a function does not depend on the order of its input.
+///
+/// The function is called with a clone of the input and with a shuffled clone of the input, and the
+/// outputs are compared with [`PartialEq`]. The input must implement [`Clone`] and
+/// <code>[AsMut]<\[T\]></code>, like a [`Vec`] or an array, and the output must implement [`Debug`].
+/// If the input has more than one element, the shuffle always changes the order.
+///
+/// The shuffle is deterministic. Its seed can be changed with `seed = n`.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_permutation_invariant;
+/// let a = vec![3, 1, 4, 1, 5];
+/// test_permutation_invariant!(|items: Vec<i32>| items.iter().sum::<i32>(), a).expect("This is true");
+/// println!("{:?}", test_permutation_invariant!(|items: Vec<i32>| items[0], a, seed = 7, "for {} items", a.len()));
+/// // prints:
+/// // [src/main.rs:4:1]: Test failed: |items: Vec<i32>| items[0] depends on the order of a: for 5 items
+/// // output for a: 3
+/// // output for shuffled a (seed 7): 5
+/// ```
+#[macro_export]
+macro_rules! test_permutation_invariant {
+    ($function:expr, $input:expr $(,)?) => {
+        $crate::test_permutation_invariant!(@check $function, $input, $crate::helpers::DEFAULT_SHUFFLE_SEED, ::std::option::Option::None)
+    };
+    ($function:expr, $input:expr, seed = $seed:expr $(,)?) => {
+        $crate::test_permutation_invariant!(@check $function, $input, $seed, ::std::option::Option::None)
+    };
+    ($function:expr, $input:expr, seed = $seed:expr, $($arg:tt)+) => {
+        $crate::test_permutation_invariant!(@check $function, $input, $seed, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+    (@check $function:expr, $input:expr, $seed:expr, $args:expr) => {{
+        match $crate::helpers::permutation_difference(&$input, $function, $seed, ::std::stringify!($input)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: f depends on the order of a"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($function), " depends on the order of ", ::std::stringify!($input))
+                } else {
+                    // "Test failed: f depends on the order of a"
+                    ::std::concat!("Test failed: ", ::std::stringify!($function), " depends on the order of ", ::std::stringify!($input))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($function:expr, $input:expr, $($arg:tt)+) => {
+        $crate::test_permutation_invariant!(@check $function, $input, $crate::helpers::DEFAULT_SHUFFLE_SEED, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two values are equal (using [`PartialEq`]) after mapping both to a canonical form.
+///
+/// Unlike [`test_eq_via!`], the two values can be of different types, like two versions of an enum
+/// with renamed variants. The mapping is expanded once for each side, so a closure like
+/// `|x| x.canonical()` can call a trait method that both types implement. The mapping receives a
+/// reference and runs once for each side. On failure, the canonical forms are shown.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_eq_mapped;
+/// enum OldLevel { Warn, Fatal }
+/// enum NewLevel { Warning, Error }
+/// trait Canonical { fn canonical(&self) -> &'static str; }
+/// impl Canonical for OldLevel {
+///     fn canonical(&self) -> &'static str {
+///         match self { Self::Warn => "warning", Self::Fatal => "error" }
+///     }
+/// }
+/// impl Canonical for NewLevel {
+///     fn canonical(&self) -> &'static str {
+///         match self { Self::Warning => "warning", Self::Error => "error" }
+///     }
+/// }
+/// let a = OldLevel::Fatal;
+/// test_eq_mapped!(a, NewLevel::Error, |x| x.canonical()).expect("This is true");
+/// println!("{:?}", test_eq_mapped!(a, NewLevel::Warning, |x| x.canonical(), "in version {}", 2));
+/// // prints:
+/// // [src/main.rs:17:1]: Test failed: a != NewLevel::Warning (mapped with |x| x.canonical()): in version 2
+/// // a: "error"
+/// // NewLevel::Warning: "warning"
+/// ```
+#[macro_export]
+macro_rules! test_eq_mapped {
+    ($left:expr, $right:expr, $via:expr $(,)?) => {
+        $crate::test_eq_mapped!(@compare $left, $right, $via, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $via:expr, $args:expr) => {{
+        match ($crate::helpers::project_one(&$left, $via), $crate::helpers::project_one(&$right, $via)) {
+            (left_val, right_val) => {
+                if !(left_val == right_val) {
+                    let message = if $crate::__LINE_INFO {
+                        // "[src/main:2:5]: Test failed: a != b (mapped with |x| x.canonical())"
+                        ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right), " (mapped with ", ::std::stringify!($via), ")")
+                    } else {
+                        // "Test failed: a != b (mapped with |x| x.canonical())"
+                        ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right), " (mapped with ", ::std::stringify!($via), ")")
+                    };
+
+                    ::std::result::Result::Err($crate::TestFailure::test_failed_two_idents(message, ::std::stringify!($left), &left_val, ::std::stringify!($right), &right_val, $args))
+                } else {
+                    ::std::result::Result::Ok(())
+                }
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $via:expr, $($arg:tt)+) => {
+        $crate::test_eq_mapped!(@compare $left, $right, $via, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that the running sum of a slice never drops below zero.
+///
+/// The expression must implement <code>[AsRef]<\[T\]></code>, where `T` is a signed number. The sums
+/// start at [`Default::default`], which is zero for numbers. On failure, the first index where the
+/// running sum is negative is shown with the sum at that point. An integer sum that overflows fails
+/// the test as well.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_prefix_sums_nonneg;
+/// test_prefix_sums_nonneg!([5, -3, -2, 4]).expect("This is true");
+/// let a = [5, -3, -4, 4];
+/// println!("{:?}", test_prefix_sums_nonneg!(a, "for account {}", 17));
+/// // prints:
+/// // [src/main.rs:4:1]: Test failed: the running sum of a is negative: for account 17
+/// // running sum at index 2: -2
+/// // a[2]: -4
+/// ```
+#[macro_export]
+macro_rules! test_prefix_sums_nonneg {
+    ($slice:expr $(,)?) => {
+        $crate::test_prefix_sums_nonneg!(@check $slice, ::std::option::Option::None)
+    };
+    (@check $slice:expr, $args:expr) => {{
+        match $crate::helpers::prefix_sums_error(&$slice, ::std::stringify!($slice)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: the running sum of a is negative"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: the running sum of ", ::std::stringify!($slice), " is negative")
+                } else {
+                    // "Test failed: the running sum of a is negative"
+                    ::std::concat!("Test failed: the running sum of ", ::std::stringify!($slice), " is negative")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($slice:expr, $($arg:tt)+) => {
+        $crate::test_prefix_sums_nonneg!(@check $slice, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Runs a block of tests in `main`, and turns the result into an [`ExitCode`](std::process::ExitCode).
+///
+/// The tests in the block are combined with `?`, so the block stops at the first failure. The failure
+/// is printed to stderr and the exit code is [`ExitCode::FAILURE`](std::process::ExitCode::FAILURE).
+/// If all tests pass, the exit code is [`ExitCode::SUCCESS`](std::process::ExitCode::SUCCESS). This is
+/// useful for example binaries, which can't use `#[test]`.
+///
+/// # Examples
+/// ```
+/// use std::process::ExitCode;
+/// use test_eq::{test_eq, test_main, test_ne};
+/// fn main() -> ExitCode {
+///     test_main! {
+///         let a = 3;
+///         test_eq!(a * 2, 6)?;
+///         test_ne!(a, 4, "a is {}", a)?;
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! test_main {
+    ($($body:tt)*) => {
+        $crate::helpers::run_main(|| {
+            { $($body)* }
+            ::std::result::Result::Ok(())
+        })
+    };
+}
+
+/// Tests that two slices are equal (using [`PartialEq`]) when trailing default elements are ignored.
+///
+/// Both expressions must implement <code>[AsRef]<\[T\]></code>, where `T` implements [`Default`]. The
+/// elements equal to `T::default()` at the end of both slices are removed before comparing, like the
+/// zero padding of a buffer. On failure, the trimmed slices and the first index where they differ are
+/// shown.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_eq_ignore_trailing_default;
+/// let a = vec![0x7f, 0x45, 0, 0, 0];
+/// test_eq_ignore_trailing_default!(a, [0x7f, 0x45]).expect("This is true");
+/// println!("{:?}", test_eq_ignore_trailing_default!(a, [0x7f, 0x46, 0], "in frame {}", 3));
+/// // prints:
+/// // [src/main.rs:4:1]: Test failed: a != [0x7f, 0x46, 0] ignoring trailing defaults: in frame 3
+/// // trimmed a: [127, 69]
+/// // trimmed [0x7f, 0x46, 0]: [127, 70]
+/// // first difference at index 1: 69 != 70
+/// ```
+#[macro_export]
+macro_rules! test_eq_ignore_trailing_default {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::test_eq_ignore_trailing_default!(@compare $left, $right, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $args:expr) => {{
+        match $crate::helpers::trailing_default_difference(&$left, &$right, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a != b ignoring trailing defaults"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right), " ignoring trailing defaults")
+                } else {
+                    // "Test failed: a != b ignoring trailing defaults"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right), " ignoring trailing defaults")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::test_eq_ignore_trailing_default!(@compare $left, $right, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that the left expression is equal to the right expression multiplied by a factor.
+///
+/// This compares numbers in different units, like meters and millimeters with a factor of `0.001`.
+/// The test passes when `|left - right * factor| <= tolerance`. The tolerance defaults to `1e-9` and
+/// can be changed with `tolerance = t`. All operands must implement <code>[Into]<[f64]></code> and
+/// [`Copy`], which includes [`f32`], [`f64`] and the integers up to 32 bits.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_eq_scaled;
+/// let meters = 1.5;
+/// let millimeters = 1502.0;
+/// test_eq_scaled!(meters, millimeters, 0.001, tolerance = 0.005).expect("This is true");
+/// println!("{:?}", test_eq_scaled!(meters, millimeters, 0.001, "for part {}", 8));
+/// // prints:
+/// // [src/main.rs:5:1]: Test failed: meters != millimeters * 0.001: for part 8
+/// // meters: 1.5
+/// // millimeters: 1502.0
+/// // millimeters * 0.001: 1.502
+/// // difference: 0.0020000000000000018 (allowed: 1e-9)
+/// ```
+#[macro_export]
+macro_rules! test_eq_scaled {
+    ($left:expr, $right:expr, $factor:expr $(,)?) => {
+        $crate::test_eq_scaled!(@compare $left, $right, $factor, $crate::helpers::DEFAULT_SCALED_TOLERANCE, ::std::option::Option::None)
+    };
+    ($left:expr, $right:expr, $factor:expr, tolerance = $tolerance:expr $(,)?) => {
+        $crate::test_eq_scaled!(@compare $left, $right, $factor, $tolerance, ::std::option::Option::None)
+    };
+    ($left:expr, $right:expr, $factor:expr, tolerance = $tolerance:expr, $($arg:tt)+) => {
+        $crate::test_eq_scaled!(@compare $left, $right, $factor, $tolerance, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+    (@compare $left:expr, $right:expr, $factor:expr, $tolerance:expr, $args:expr) => {{
+        match $crate::helpers::scaled_difference($left, $right, $factor, $tolerance, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a != b * 0.001"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right), " * ", ::std::stringify!($factor))
+                } else {
+                    // "Test failed: a != b * 0.001"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right), " * ", ::std::stringify!($factor))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $factor:expr, $($arg:tt)+) => {
+        $crate::test_eq_scaled!(@compare $left, $right, $factor, $crate::helpers::DEFAULT_SCALED_TOLERANCE, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two regular expressions agree on whether they match each of the sample strings.
+///
+/// The samples must implement <code>[AsRef]<\[T\]></code>, where `T` implements <code>[AsRef]<[str]></code>.
+/// The patterns use the syntax of the [`regex`](https://docs.rs/regex) crate and are compiled every time
+/// the test runs. A pattern matches a sample if it matches anywhere in it, like [`regex::Regex::is_match`].
+/// The test fails if a pattern is invalid, or on the first sample where the patterns disagree.
+///
+/// This macro is only available with the `regex` feature.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_regex_equiv;
+/// let old = r"^[0-9]+$";
+/// let new = r"^\d+$";
+/// test_regex_equiv!(old, r"^[0-9][0-9]*$", ["", "7", "42", "4a"]).expect("This is true");
+/// println!("{:?}", test_regex_equiv!(old, new, ["42", "٤٢"], "after {}", "refactoring"));
+/// // prints:
+/// // [src/main.rs:5:1]: Test failed: old and new do not match the same samples: after refactoring
+/// // sample 1: "٤٢"
+/// // old does not match: "^[0-9]+$"
+/// // new matches: "^\\d+$"
+/// ```
+#[cfg(feature = "regex")]
+#[macro_export]
+macro_rules! test_regex_equiv {
+    ($left:expr, $right:expr, $samples:expr $(,)?) => {
+        $crate::test_regex_equiv!(@compare $left, $right, $samples, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $samples:expr, $args:expr) => {{
+        match $crate::helpers::regex_equiv_difference($left, $right, &$samples, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a and b do not match the same samples"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " and ", ::std::stringify!($right), " do not match the same samples")
+                } else {
+                    // "Test failed: a and b do not match the same samples"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " and ", ::std::stringify!($right), " do not match the same samples")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $samples:expr, $($arg:tt)+) => {
+        $crate::test_regex_equiv!(@compare $left, $right, $samples, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that every value of a map is less than or equal to a threshold (using [`PartialOrd`]).
+///
+/// The map must be a [`HashMap`](std::collections::HashMap) or a
+/// [`BTreeMap`](std::collections::BTreeMap), and the keys and values must implement [`Debug`]. On
+/// failure, every entry with a larger value is shown, sorted by key.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use std::collections::HashMap;
+/// use test_eq::test_all_values_le;
+/// let memory = HashMap::from([("parser", 120), ("cache", 900), ("index", 450)]);
+/// test_all_values_le!(memory, 1000).expect("This is true");
+/// println!("{:?}", test_all_values_le!(memory, 400, "in {} MiB", "resident"));
+/// // prints:
+/// // [src/main.rs:5:1]: Test failed: a value of memory exceeds 400: in resident MiB
+/// // 2 of 3 values exceed 400
+/// // memory["cache"]: 900
+/// // memory["index"]: 450
+/// ```
+#[macro_export]
+macro_rules! test_all_values_le {
+    ($map:expr, $threshold:expr $(,)?) => {
+        $crate::test_all_values_le!(@check $map, $threshold, ::std::option::Option::None)
+    };
+    (@check $map:expr, $threshold:expr, $args:expr) => {{
+        match $crate::helpers::values_le_error(&$map, &$threshold, ::std::stringify!($map)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a value of map exceeds 10"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: a value of ", ::std::stringify!($map), " exceeds ", ::std::stringify!($threshold))
+                } else {
+                    // "Test failed: a value of map exceeds 10"
+                    ::std::concat!("Test failed: a value of ", ::std::stringify!($map), " exceeds ", ::std::stringify!($threshold))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($map:expr, $threshold:expr, $($arg:tt)+) => {
+        $crate::test_all_values_le!(@check $map, $threshold, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two expressions are equal to each other (using [`PartialEq`]), keeping clones of the
+/// values on failure.
+///
+/// This is like [`test_eq!`], but the error is a [`StructuredFailure`] that carries a
+/// [`Diff::ValueMismatch`] with clones of both values, so code like a property testing shrinker can
+/// inspect them. Both expressions must implement [`Clone`]. The failure message is the same as the
+/// message of [`test_eq!`], and `?` converts the error into a [`TestFailure`].
+///
+/// This macro returns a <code>[Result]<(), [StructuredFailure]></code> and hints the compiler that the
+/// failure case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::{test_eq_structured, Diff};
+/// let a = vec![1, 2];
+/// let b = vec![1, 3];
+/// test_eq_structured!(a, a.clone()).expect("This is true");
+/// let failure = test_eq_structured!(a, b, "for seed {}", 4).unwrap_err();
+/// if let Diff::ValueMismatch { left, right } = failure.diff() {
+///     assert_eq!(left, &[1, 2]);
+///     assert_eq!(right, &[1, 3]);
+/// }
+/// println!("{failure:?}");
+/// // prints:
+/// // [src/main.rs:5:15]: Test failed: a != b: for seed 4
+/// // a: [1, 2]
+/// // b: [1, 3]
+/// ```
+#[macro_export]
+macro_rules! test_eq_structured {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::test_eq_structured!(@compare $left, $right, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $args:expr) => {{
+        match (&$left, &$right) {
+            (left_val, right_val) => {
+                if !(left_val == right_val) {
+                    let message = if $crate::__LINE_INFO {
+                        // "[src/main:2:5]: Test failed: a * 2 != b * 5"
+                        ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                    } else {
+                        // "Test failed: a * 2 != b * 5"
+                        ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                    };
+
+                    // The reborrows below are intentional, see `test_eq!`.
+                    let failure = $crate::TestFailure::test_failed_comparison(message, ::std::stringify!($left), &*left_val, ::std::stringify!($right), &*right_val, $args).with_delta($crate::helpers::Delta(&*left_val, &*right_val).delta()).with_timing($crate::helpers::comparison_time(|| left_val == right_val));
+                    let diff = $crate::Diff::ValueMismatch {
+                        left: ::std::clone::Clone::clone(&*left_val),
+                        right: ::std::clone::Clone::clone(&*right_val),
+                    };
+                    ::std::result::Result::Err($crate::StructuredFailure::new(failure, diff))
+                } else {
+                    ::std::result::Result::Ok(())
+                }
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::test_eq_structured!(@compare $left, $right, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two [`f64`] values are close, allowing one ULP of rounding error per operation.
+///
+/// A ULP (unit in the last place) is the distance to the next representable float. The test passes
+/// when the number of floats between the values is at most `ops`, the number of floating point
+/// operations that were used to compute them. This is a rough model of how rounding errors
+/// accumulate. `0.0` and `-0.0` are equal, and NaN is never close to anything.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_eq_ulps_scaled;
+/// let a = 0.1 + 0.2;
+/// test_eq_ulps_scaled!(a, 0.3, 1).expect("This is true");
+/// println!("{:?}", test_eq_ulps_scaled!(a, 0.3, 0, "for {}", "sums"));
+/// // prints:
+/// // [src/main.rs:4:1]: Test failed: a is not within 0 ULPs of 0.3: for sums
+/// // distance: 1 ULPs (allowed: 0, one per operation)
+/// // a: 0.30000000000000004
+/// // 0.3: 0.3
+/// ```
+#[macro_export]
+macro_rules! test_eq_ulps_scaled {
+    ($left:expr, $right:expr, $ops:expr $(,)?) => {
+        $crate::test_eq_ulps_scaled!(@compare $left, $right, $ops, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $ops:expr, $args:expr) => {{
+        match $crate::helpers::ulps_scaled_difference($left, $right, $ops, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a is not within ops ULPs of b"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " is not within ", ::std::stringify!($ops), " ULPs of ", ::std::stringify!($right))
+                } else {
+                    // "Test failed: a is not within ops ULPs of b"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " is not within ", ::std::stringify!($ops), " ULPs of ", ::std::stringify!($right))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $ops:expr, $($arg:tt)+) => {
+        $crate::test_eq_ulps_scaled!(@compare $left, $right, $ops, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that a sorted slice contains a value, using a binary search.
+///
+/// The expression must implement <code>[AsRef]<\[T\]></code>, where `T` implements [`Ord`]. Unlike
+/// [`test_any!`], this takes logarithmic time. On failure, the index where the value would be
+/// inserted is shown with the elements around it.
+///
+/// The slice must be sorted in ascending order. If it is not, the result is unspecified, and the
+/// test can fail even though the slice contains the value.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_binary_search;
+/// let primes = [2, 3, 5, 7, 11, 13];
+/// test_binary_search!(primes, 7).expect("This is true");
+/// println!("{:?}", test_binary_search!(primes, 9, "below {}", 15));
+/// // prints:
+/// // [src/main.rs:4:1]: Test failed: 9 is not in primes: below 15
+/// // insertion point: 4
+/// // primes[3..5]: 7, 11
+/// ```
+#[macro_export]
+macro_rules! test_binary_search {
+    ($slice:expr, $target:expr $(,)?) => {
+        $crate::test_binary_search!(@check $slice, $target, ::std::option::Option::None)
+    };
+    (@check $slice:expr, $target:expr, $args:expr) => {{
+        match $crate::helpers::binary_search_error(&$slice, &$target, ::std::stringify!($slice)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: 9 is not in a"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($target), " is not in ", ::std::stringify!($slice))
+                } else {
+                    // "Test failed: 9 is not in a"
+                    ::std::concat!("Test failed: ", ::std::stringify!($target), " is not in ", ::std::stringify!($slice))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($slice:expr, $target:expr, $($arg:tt)+) => {
+        $crate::test_binary_search!(@check $slice, $target, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two slices of [`f64`] are equal to each other, where a NaN matches anything.
+///
+/// Both expressions must implement <code>[AsRef]<\[[f64]\]></code>. The slices are compared element by
+/// element, and every index where either side is NaN is skipped, so NaN can mark a value that does not
+/// matter. On failure, the length difference or the first index that differs is shown.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_eq_nan_wildcard;
+/// let a = [1.0, 2.5, 3.0];
+/// test_eq_nan_wildcard!(a, [1.0, f64::NAN, 3.0]).expect("This is true");
+/// println!("{:?}", test_eq_nan_wildcard!(a, [f64::NAN, 2.5, 4.0], "for row {}", 1));
+/// // prints:
+/// // [src/main.rs:4:1]: Test failed: a != [f64::NAN, 2.5, 4.0] ignoring NaN: for row 1
+/// // first difference at index 2: 3.0 != 4.0
+/// ```
+#[macro_export]
+macro_rules! test_eq_nan_wildcard {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::test_eq_nan_wildcard!(@compare $left, $right, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $args:expr) => {{
+        match $crate::helpers::nan_wildcard_difference(&$left, &$right, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a != b ignoring NaN"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right), " ignoring NaN")
+                } else {
+                    // "Test failed: a != b ignoring NaN"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right), " ignoring NaN")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::test_eq_nan_wildcard!(@compare $left, $right, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that the value a cache holds for a key is equal to a fresh computation (using [`PartialEq`]).
+///
+/// The cached value is looked up with `cache.get(&key)`, which works for a
+/// [`HashMap`](std::collections::HashMap), a [`BTreeMap`](std::collections::BTreeMap), or any type with
+/// a `get` method that returns an [`Option`] of a reference. The computation is called with a reference
+/// to the key, and only if the key is cached. The test fails if the key is not cached, or the values
+/// differ.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use std::collections::HashMap;
+/// use test_eq::test_cache_consistent;
+/// let square = |n: &u32| n * n;
+/// let cache = HashMap::from([(3, 9), (4, 15)]);
+/// test_cache_consistent!(cache, 3, square).expect("This is true");
+/// println!("{:?}", test_cache_consistent!(cache, 4, square, "after {} inserts", 2));
+/// // prints:
+/// // [src/main.rs:6:1]: Test failed: cache is not consistent for 4: after 2 inserts
+/// // 4: 4
+/// // cached: 15
+/// // computed: 16
+/// ```
+#[macro_export]
+macro_rules! test_cache_consistent {
+    ($cache:expr, $key:expr, $compute:expr $(,)?) => {
+        $crate::test_cache_consistent!(@check $cache, $key, $compute, ::std::option::Option::None)
+    };
+    (@check $cache:expr, $key:expr, $compute:expr, $args:expr) => {{
+        match &$key {
+            key => match $crate::helpers::cache_difference($cache.get(key), key, $compute, ::std::stringify!($key)) {
+                ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+                ::std::result::Result::Err(details) => {
+                    let message = if $crate::__LINE_INFO {
+                        // "[src/main:2:5]: Test failed: cache is not consistent for key"
+                        ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($cache), " is not consistent for ", ::std::stringify!($key))
+                    } else {
+                        // "Test failed: cache is not consistent for key"
+                        ::std::concat!("Test failed: ", ::std::stringify!($cache), " is not consistent for ", ::std::stringify!($key))
+                    };
+
+                    ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+                }
+            },
+        }
+    }};
+    ($cache:expr, $key:expr, $compute:expr, $($arg:tt)+) => {
+        $crate::test_cache_consistent!(@check $cache, $key, $compute, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two JSON values are equal to each other, reporting every path where they differ.
+///
+/// Both expressions must be a [`serde_json::Value`](https://docs.rs/serde_json/latest/serde_json/enum.Value.html).
+/// Objects are compared by key, so the order of keys does not matter. Every difference is shown with
+/// its JSON pointer, like `/items/2/name`, up to 10 differences. The cap can be changed with `cap = n`.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// This macro requires the `serde_json` feature.
+///
+/// # Examples
+/// ```
+/// use serde_json::json;
+/// use test_eq::test_json_eq_all;
+/// let a = json!({"name": "alice", "roles": ["admin", "user"], "age": 30});
+/// let b = json!({"name": "alice", "roles": ["admin", "guest"], "age": 31});
+/// test_json_eq_all!(a, a.clone()).expect("This is true");
+/// println!("{:?}", test_json_eq_all!(a, b, "user {}", 1));
+/// // prints:
+/// // [src/main.rs:6:1]: Test failed: a != b: user 1
+/// // 2 differing paths (a != b):
+/// //   /age: 30 != 31
+/// //   /roles/1: "user" != "guest"
+/// ```
+#[cfg(feature = "serde_json")]
+#[macro_export]
+macro_rules! test_json_eq_all {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::test_json_eq_all!(@compare $left, $right, $crate::helpers::DEFAULT_DIFFERENCE_CAP, ::std::option::Option::None)
+    };
+    ($left:expr, $right:expr, cap = $cap:expr $(,)?) => {
+        $crate::test_json_eq_all!(@compare $left, $right, $cap, ::std::option::Option::None)
+    };
+    ($left:expr, $right:expr, cap = $cap:expr, $($arg:tt)+) => {
+        $crate::test_json_eq_all!(@compare $left, $right, $cap, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+    (@compare $left:expr, $right:expr, $cap:expr, $args:expr) => {{
+        match $crate::helpers::json_all_differences(&$left, &$right, $cap, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a != b"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                } else {
+                    // "Test failed: a != b"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::test_json_eq_all!(@compare $left, $right, $crate::helpers::DEFAULT_DIFFERENCE_CAP, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that no element of a slice is equal to a value (using [`PartialEq`]).
+///
+/// This is the inverse of [`test_all_equal!`]. An empty slice always passes.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_none_equal;
+/// let mut a = vec![1, 2, 3];
+/// test_none_equal!(a, 0).expect("This is true");
+/// a[1] = 0;
+/// println!("{:?}", test_none_equal!(a, 0, "after {} writes", 1));
+/// // prints:
+/// // [src/main.rs:5:1]: Test failed: an element of a is equal to 0: after 1 writes
+/// // first match at index 1
+/// // a[1]: 0
+/// ```
+#[macro_export]
+macro_rules! test_none_equal {
+    ($slice:expr, $forbidden:expr $(,)?) => {
+        $crate::test_none_equal!(@check $slice, $forbidden, ::std::option::Option::None)
+    };
+    (@check $slice:expr, $forbidden:expr, $args:expr) => {{
+        match $crate::helpers::none_equal_error(&$slice, &$forbidden, ::std::stringify!($slice)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: an element of a is equal to b"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: an element of ", ::std::stringify!($slice), " is equal to ", ::std::stringify!($forbidden))
+                } else {
+                    // "Test failed: an element of a is equal to b"
+                    ::std::concat!("Test failed: an element of ", ::std::stringify!($slice), " is equal to ", ::std::stringify!($forbidden))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($slice:expr, $forbidden:expr, $($arg:tt)+) => {
+        $crate::test_none_equal!(@check $slice, $forbidden, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two strings are equal after normalizing both to Unicode Normalization Form C (NFC).
+///
+/// This makes a precomposed character like `"\u{e9}"` equal to the same character written with a
+/// combining mark, like `"e\u{301}"`. The strings must implement <code>[AsRef]<[str]></code>. On failure,
+/// the code points of both strings are shown, before and after normalization.
+///
+/// This macro is only available with the `unicode` feature.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_eq_nfc;
+/// let composed = "caf\u{e9}";
+/// let decomposed = "cafe\u{301}";
+/// let other = "cafe";
+/// test_eq_nfc!(composed, decomposed).expect("This is true");
+/// println!("{:?}", test_eq_nfc!(decomposed, other, "for the {} menu", "french"));
+/// // prints:
+/// // [src/main.rs:6:1]: Test failed: decomposed and other are not equal in NFC: for the french menu
+/// // decomposed: "café"
+/// // decomposed code points: U+0063 U+0061 U+0066 U+0065 U+0301
+/// // decomposed in NFC: U+0063 U+0061 U+0066 U+00E9
+/// // other: "cafe"
+/// // other code points: U+0063 U+0061 U+0066 U+0065
+/// // other in NFC: U+0063 U+0061 U+0066 U+0065
+/// ```
+#[cfg(feature = "unicode")]
+#[macro_export]
+macro_rules! test_eq_nfc {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::test_eq_nfc!(@compare $left, $right, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $args:expr) => {{
+        match $crate::helpers::nfc_difference(&$left, &$right, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a and b are not equal in NFC"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " and ", ::std::stringify!($right), " are not equal in NFC")
+                } else {
+                    // "Test failed: a and b are not equal in NFC"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " and ", ::std::stringify!($right), " are not equal in NFC")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::test_eq_nfc!(@compare $left, $right, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that the length of the output of a function grows with the input size as expected.
+///
+/// The function is called with every size in the sizes, which must implement
+/// <code>[AsRef]<\[[usize]\]></code>, and the `len()` of its output is compared with the expected
+/// length for that size. On failure, the first size where the lengths differ is shown.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_output_len_eq;
+/// let sizes = [0, 1, 2, 3];
+/// let hex = |n: usize| "ff".repeat(n);
+/// let padded = |n: usize| "ff".repeat(n.max(2));
+/// let twice = |n: usize| n * 2;
+/// test_output_len_eq!(sizes, hex, twice).expect("This is true");
+/// println!("{:?}", test_output_len_eq!(sizes, padded, twice, "for {} encoding", "hex"));
+/// // prints:
+/// // [src/main.rs:7:1]: Test failed: the output length of padded does not match twice: for hex encoding
+/// // first difference at size 0
+/// // padded(0).len(): 4
+/// // twice(0): 0
+/// ```
+#[macro_export]
+macro_rules! test_output_len_eq {
+    ($sizes:expr, $f:expr, $expected:expr $(,)?) => {
+        $crate::test_output_len_eq!(@compare $sizes, $f, $expected, ::std::option::Option::None)
+    };
+    (@compare $sizes:expr, $f:expr, $expected:expr, $args:expr) => {{
+        match ($f, $expected) {
+            (f, expected) => match $crate::helpers::output_len_difference(&$sizes, |size: usize| f(size).len(), expected, ::std::stringify!($f), ::std::stringify!($expected)) {
+                ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+                ::std::result::Result::Err(details) => {
+                    let message = if $crate::__LINE_INFO {
+                        // "[src/main:2:5]: Test failed: the output length of f does not match expected"
+                        ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: the output length of ", ::std::stringify!($f), " does not match ", ::std::stringify!($expected))
+                    } else {
+                        // "Test failed: the output length of f does not match expected"
+                        ::std::concat!("Test failed: the output length of ", ::std::stringify!($f), " does not match ", ::std::stringify!($expected))
+                    };
+
+                    ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+                }
+            },
+        }
+    }};
+    ($sizes:expr, $f:expr, $expected:expr, $($arg:tt)+) => {
+        $crate::test_output_len_eq!(@compare $sizes, $f, $expected, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that an expression matches a pattern, like [`matches!`].
+///
+/// The pattern can have a guard, like `Some(x) if x > 3`. This makes it possible to test the shape of
+/// an enum that doesn't implement [`PartialEq`]. Like with a `match`, the expression is moved if the
+/// pattern binds by value. On failure, the value is shown using [`Debug`].
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_matches;
+/// let a = Some(5);
+/// let b = Some(2);
+/// test_matches!(a, Some(x) if x > 3).expect("This is true");
+/// println!("{:?}", test_matches!(b, Some(x) if x > 3, "for level {}", 7));
+/// // prints:
+/// // [src/main.rs:5:1]: Test failed: b does not match Some(x) if x > 3: for level 7
+/// // b: Some(2)
+/// ```
+#[macro_export]
+macro_rules! test_matches {
+    ($left:expr, $(|)? $($pattern:pat_param)|+ $(if $guard:expr)? $(,)?) => {{
+        match $left {
+            $($pattern)|+ $(if $guard)? => ::std::result::Result::Ok(()),
+            ref left_val => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a does not match Some(_)"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " does not match ", ::std::stringify!($($pattern)|+ $(if $guard)?))
+                } else {
+                    // "Test failed: a does not match Some(_)"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " does not match ", ::std::stringify!($($pattern)|+ $(if $guard)?))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_one_ident(message, ::std::stringify!($left), &*left_val, ::std::option::Option::None))
+            }
+        }
+    }};
+    ($left:expr, $(|)? $($pattern:pat_param)|+ $(if $guard:expr)?, $($arg:tt)+) => {{
+        match $left {
+            $($pattern)|+ $(if $guard)? => ::std::result::Result::Ok(()),
+            ref left_val => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a does not match Some(_)"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " does not match ", ::std::stringify!($($pattern)|+ $(if $guard)?))
+                } else {
+                    // "Test failed: a does not match Some(_)"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " does not match ", ::std::stringify!($($pattern)|+ $(if $guard)?))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_one_ident(message, ::std::stringify!($left), &*left_val, ::std::option::Option::Some(::std::format_args!($($arg)+))))
+            }
+        }
+    }};
+}
+
+/// Tests that two expressions are equal to each other (using [`PartialEq`]), showing their hashes on
+/// failure.
+///
+/// Both values must have the same type, which implements [`Hash`](std::hash::Hash), [`PartialEq`] and
+/// [`Debug`]. The hashes are calculated with the
+/// [`DefaultHasher`](std::collections::hash_map::DefaultHasher) and are only calculated if the test
+/// fails. Seeing them next to the values helps to find an inconsistency between the implementations of
+/// [`Hash`](std::hash::Hash) and [`PartialEq`].
+/// The hashes depend on the version of the standard library, so don't compare them with fixed values.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_eq_with_hash;
+/// let a = "key";
+/// let b = "key";
+/// let c = "Key";
+/// test_eq_with_hash!(a, b).expect("This is true");
+/// println!("{:?}", test_eq_with_hash!(a, c, "in bucket {}", 3));
+/// // prints:
+/// // [src/main.rs:6:1]: Test failed: a != c: in bucket 3
+/// // a: "key"
+/// // c: "Key"
+/// // hash of a: 0x5f3d3f1cfa4c3b8a
+/// // hash of c: 0x0e8bca6c8d62b2a1
+/// ```
+#[macro_export]
+macro_rules! test_eq_with_hash {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::test_eq_with_hash!(@compare $left, $right, ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $args:expr) => {{
+        match $crate::helpers::hash_difference(&$left, &$right, ::std::stringify!($left), ::std::stringify!($right)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a != b"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                } else {
+                    // "Test failed: a != b"
+                    ::std::concat!("Test failed: ", ::std::stringify!($left), " != ", ::std::stringify!($right))
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::test_eq_with_hash!(@compare $left, $right, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that two floats differ by at most an epsilon.
+///
+/// The operands must both be an [`f32`] or both be an [`f64`]. Without an epsilon, the machine epsilon
+/// of the type is used, [`f32::EPSILON`] or [`f64::EPSILON`]. A NaN is never close to anything.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added after the epsilon, with [`std::fmt`] support. To add one without
+/// choosing an epsilon, pass the machine epsilon explicitly.
+///
+/// # Examples
+/// ```
+/// use test_eq::test_approx_eq;
+/// let a = 0.1 + 0.2;
+/// let b = 0.3;
+/// test_approx_eq!(a, b).expect("This is true");
+/// test_approx_eq!(a, 0.31, 0.05).expect("This is true");
+/// println!("{:?}", test_approx_eq!(a, b * 2.0, 0.001, "for step {}", 3));
+/// // prints:
+/// // [src/main.rs:6:1]: Test failed: |a - b * 2.0| > 0.001: for step 3
+/// // a: 0.30000000000000004
+/// // b * 2.0: 0.6
+/// // difference: 0.29999999999999993
+/// // epsilon: 0.001
+/// ```
+#[macro_export]
+macro_rules! test_approx_eq {
+    ($left:expr, $right:literal $(,)?) => {
+        $crate::test_approx_eq!(@compare $left, $right, ::std::option::Option::Some(::std::stringify!($left)), ::std::option::Option::None, ::std::option::Option::None, "EPSILON", ::std::option::Option::None)
+    };
+    ($left:literal, $right:expr $(,)?) => {
+        $crate::test_approx_eq!(@compare $left, $right, ::std::option::Option::None, ::std::option::Option::Some(::std::stringify!($right)), ::std::option::Option::None, "EPSILON", ::std::option::Option::None)
+    };
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::test_approx_eq!(@compare $left, $right, ::std::option::Option::Some(::std::stringify!($left)), ::std::option::Option::Some(::std::stringify!($right)), ::std::option::Option::None, "EPSILON", ::std::option::Option::None)
+    };
+    (@compare $left:expr, $right:expr, $left_ident:expr, $right_ident:expr, $epsilon:expr, $epsilon_ident:expr, $args:expr) => {{
+        match (&$left, &$right) {
+            (left_val, right_val) => match $crate::helpers::approx_difference(*left_val, *right_val, $epsilon, $left_ident, $right_ident) {
+                ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+                ::std::result::Result::Err(details) => {
+                    let message = if $crate::__LINE_INFO {
+                        // "[src/main:2:5]: Test failed: |a - b| > epsilon"
+                        ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: |", ::std::stringify!($left), " - ", ::std::stringify!($right), "| > ", $epsilon_ident)
+                    } else {
+                        // "Test failed: |a - b| > epsilon"
+                        ::std::concat!("Test failed: |", ::std::stringify!($left), " - ", ::std::stringify!($right), "| > ", $epsilon_ident)
+                    };
+
+                    ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+                }
+            },
+        }
+    }};
+    ($left:expr, $right:literal, $epsilon:expr $(,)?) => {
+        $crate::test_approx_eq!(@compare $left, $right, ::std::option::Option::Some(::std::stringify!($left)), ::std::option::Option::None, ::std::option::Option::Some($epsilon), ::std::stringify!($epsilon), ::std::option::Option::None)
+    };
+    ($left:literal, $right:expr, $epsilon:expr $(,)?) => {
+        $crate::test_approx_eq!(@compare $left, $right, ::std::option::Option::None, ::std::option::Option::Some(::std::stringify!($right)), ::std::option::Option::Some($epsilon), ::std::stringify!($epsilon), ::std::option::Option::None)
+    };
+    ($left:expr, $right:expr, $epsilon:expr $(,)?) => {
+        $crate::test_approx_eq!(@compare $left, $right, ::std::option::Option::Some(::std::stringify!($left)), ::std::option::Option::Some(::std::stringify!($right)), ::std::option::Option::Some($epsilon), ::std::stringify!($epsilon), ::std::option::Option::None)
+    };
+    ($left:expr, $right:literal, $epsilon:expr, $($arg:tt)+) => {
+        $crate::test_approx_eq!(@compare $left, $right, ::std::option::Option::Some(::std::stringify!($left)), ::std::option::Option::None, ::std::option::Option::Some($epsilon), ::std::stringify!($epsilon), ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+    ($left:literal, $right:expr, $epsilon:expr, $($arg:tt)+) => {
+        $crate::test_approx_eq!(@compare $left, $right, ::std::option::Option::None, ::std::option::Option::Some(::std::stringify!($right)), ::std::option::Option::Some($epsilon), ::std::stringify!($epsilon), ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+    ($left:expr, $right:expr, $epsilon:expr, $($arg:tt)+) => {
+        $crate::test_approx_eq!(@compare $left, $right, ::std::option::Option::Some(::std::stringify!($left)), ::std::option::Option::Some(::std::stringify!($right)), ::std::option::Option::Some($epsilon), ::std::stringify!($epsilon), ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Tests that a set of integers contains every integer between its smallest and largest value.
+///
+/// The set can be a [`BTreeSet`](std::collections::BTreeSet) or a sorted slice, anything where a
+/// reference implements [`IntoIterator`] over the integers in ascending order. A slice may contain
+/// duplicates, but fails if it isn't sorted. An empty set is contiguous. On failure, the first gap is
+/// shown.
+///
+/// This macro returns a <code>[Result]<(), [TestFailure]></code> and hints the compiler that the failure
+/// case is unlikely to happen.
+///
+/// A custom message can be added, with [`std::fmt`] support.
+///
+/// # Examples
+/// ```
+/// use std::collections::BTreeSet;
+/// use test_eq::test_contiguous;
+/// let ids = BTreeSet::from([3, 4, 5, 6]);
+/// let reused = BTreeSet::from([3, 4, 7, 8]);
+/// test_contiguous!(ids).expect("This is true");
+/// println!("{:?}", test_contiguous!(reused, "after {} frees", 2));
+/// // prints:
+/// // [src/main.rs:6:1]: Test failed: reused is not contiguous: after 2 frees
+/// // first gap: 5 is missing between 4 and 7
+/// ```
+#[macro_export]
+macro_rules! test_contiguous {
+    ($set:expr $(,)?) => {
+        $crate::test_contiguous!(@check $set, ::std::option::Option::None)
+    };
+    (@check $set:expr, $args:expr) => {{
+        match $crate::helpers::contiguous_error(&$set, ::std::stringify!($set)) {
+            ::std::result::Result::Ok(()) => ::std::result::Result::Ok(()),
+            ::std::result::Result::Err(details) => {
+                let message = if $crate::__LINE_INFO {
+                    // "[src/main:2:5]: Test failed: a is not contiguous"
+                    ::std::concat!('[', ::std::file!(), ':', ::std::line!(), ':', ::std::column!(), "]: Test failed: ", ::std::stringify!($set), " is not contiguous")
+                } else {
+                    // "Test failed: a is not contiguous"
+                    ::std::concat!("Test failed: ", ::std::stringify!($set), " is not contiguous")
+                };
+
+                ::std::result::Result::Err($crate::TestFailure::test_failed_details(message, &details, $args))
+            }
+        }
+    }};
+    ($set:expr, $($arg:tt)+) => {
+        $crate::test_contiguous!(@check $set, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
@@ -0,0 +1,30 @@
+//! The prefix is global, so it is tested in its own test binary.
+
+use test_eq::{set_prefix, test_eq, test_ok};
+
+#[test]
+fn test_set_prefix() {
+    set_prefix("Assertion failed: ").expect("Prefix was not set yet");
+    assert_eq!(
+        set_prefix("Other prefix: "),
+        Err("Other prefix: "),
+        "Prefix can only be set once"
+    );
+
+    let a = 3;
+    let b = a * 2;
+    let failure = test_eq!(a, b).unwrap_err().to_string();
+    assert!(failure.contains("Assertion failed: a != b"), "{failure}");
+    assert!(!failure.contains("Test failed"), "{failure}");
+    let failure = test_eq!(a, 4, "and b is {}", b).unwrap_err().to_string();
+    assert!(
+        failure.contains("Assertion failed: a != 4: and b is 6"),
+        "{failure}"
+    );
+    let c: Result<(), std::fmt::Error> = Err(std::fmt::Error);
+    let failure = test_ok!(c).unwrap_err().to_string();
+    assert!(
+        failure.contains("Assertion failed: c is not Ok"),
+        "{failure}"
+    );
+}
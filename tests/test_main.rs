@@ -0,0 +1,30 @@
+//! `test_main!` sets the exit code of a process, so it is tested by running the `test_main` example.
+
+use std::process::{Command, Output};
+
+fn run_example(greeting: &str) -> Output {
+    Command::new(env!("CARGO"))
+        .args(["run", "--quiet", "--example", "test_main", "--", greeting])
+        .output()
+        .expect("Cargo can be started")
+}
+
+#[test]
+fn test_test_main() {
+    let output = run_example("Hello, world!");
+    assert!(output.status.success(), "{output:?}");
+    assert!(output.stderr.is_empty(), "{output:?}");
+
+    let output = run_example("Hello, moon!");
+    assert_eq!(output.status.code(), Some(1), "{output:?}");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Test failed: greeting.len() != 13: greeting is \"Hello, moon!\""),
+        "{stderr}"
+    );
+    assert!(stderr.contains("greeting.len(): 12"), "{stderr}");
+    assert!(
+        !stderr.contains("greeting != \"Hello, world!\""),
+        "{stderr}"
+    );
+}